@@ -0,0 +1,73 @@
+use ckb_nostr_utils::error::Error as NostrError;
+#[cfg(feature = "ckb-std")]
+use ckb_std::error::SysError;
+
+exit_codes! {
+    /// Exit codes of the nostr binding type script
+    pub enum BindingError {
+        IndexOutOfBound = 1 => "index out of bound",
+        ItemMissing = 2 => "item missing",
+        LengthNotEnough = 3 => "length not enough",
+        Encoding = 4 => "invalid molecule encoding",
+        // nostr binding type script error code starts from 50
+        ValidationFail = 50 => "invalid signature",
+        WitnessNotExisting = 51 => "bound event not found in witness output type",
+        WrongArgsLength = 52 => "wrong length of script args",
+        InvalidPublicKey = 53 => "invalid public key",
+        InvalidEventId = 54 => "event id doesn't match the event or script args",
+        InvalidSignatureFormat = 55 => "invalid signature format",
+        UnknownKey = 56 => "unknown key in event json",
+        Json = 57 => "invalid event json",
+        GlobalUniqueIdNotFound = 58 => "global unique id tag not found",
+        TooManyTypeIdCell = 59 => "too many binding cells of the type script",
+        TypeIdNotMatch = 60 => "global unique id doesn't match the first input",
+        InvalidFlags = 61 => "invalid flags of script args",
+        DataCommitmentMismatched = 62 => "cell data doesn't match the bound event",
+        FileHashNotFound = 63 => "file hash tag not found",
+        DataChanged = 64 => "immutable cell data changed",
+        NonCanonicalEvent = 65 => "archived event isn't canonical json",
+        InvalidCellData = 66 => "invalid cell data",
+        ExpirationNotFound = 67 => "expiration tag not found",
+        ExpirationMismatched = 68 => "expiration tag doesn't match cell data",
+        BindingExpired = 69 => "binding expired",
+        RoyaltyNotFound = 70 => "royalty tag not found",
+        RoyaltyMismatched = 71 => "royalty tag doesn't match cell data",
+        InvalidRoyaltyRate = 72 => "invalid royalty rate",
+        RoyaltyNotPaid = 73 => "royalty not paid",
+        MintLockMismatched = 74 => "lock of minted cell doesn't match mint_to tag",
+        // errors of ckb-nostr-utils, pow difficulty type script error code
+        // starts from 80
+        InvalidDelegation = 75 => "invalid delegation tag",
+        InvalidLockArgs = 76 => "invalid nostr lock args",
+        InvalidDifficultyConfig = 77 => "invalid difficulty config",
+    }
+}
+
+#[cfg(feature = "ckb-std")]
+impl From<SysError> for BindingError {
+    fn from(err: SysError) -> Self {
+        match err {
+            SysError::IndexOutOfBound => Self::IndexOutOfBound,
+            SysError::ItemMissing => Self::ItemMissing,
+            SysError::LengthNotEnough(_) => Self::LengthNotEnough,
+            SysError::Encoding => Self::Encoding,
+            SysError::Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+        }
+    }
+}
+
+impl From<NostrError> for BindingError {
+    fn from(err: NostrError) -> Self {
+        match err {
+            NostrError::InvalidPublicKey => Self::InvalidPublicKey,
+            NostrError::InvalidEventId => Self::InvalidEventId,
+            NostrError::ValidationFail => Self::ValidationFail,
+            NostrError::InvalidSignatureFormat => Self::InvalidSignatureFormat,
+            NostrError::UnknownKey(_) => Self::UnknownKey,
+            NostrError::Json(_) => Self::Json,
+            NostrError::InvalidDelegation => Self::InvalidDelegation,
+            NostrError::InvalidLockArgs => Self::InvalidLockArgs,
+            NostrError::InvalidDifficultyConfig => Self::InvalidDifficultyConfig,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use ckb_nostr_utils::error::Error as NostrError;
+#[cfg(feature = "ckb-std")]
+use ckb_std::error::SysError;
+
+exit_codes! {
+    /// Exit codes of the pow difficulty type script
+    pub enum DifficultyError {
+        IndexOutOfBound = 1 => "index out of bound",
+        ItemMissing = 2 => "item missing",
+        LengthNotEnough = 3 => "length not enough",
+        Encoding = 4 => "invalid molecule encoding",
+        // pow difficulty type script error code starts from 80
+        WrongArgsLength = 80 => "wrong length of script args",
+        TooManyConfigCell = 81 => "too many config cells of the type script",
+        TypeIdNotMatch = 82 => "type id doesn't match the first input",
+        InvalidConfigData = 83 => "invalid config cell data",
+        ConfigDestroyed = 84 => "config cell destroyed",
+        ConfigChanged = 85 => "immutable config fields changed",
+        InvalidClaimSince = 86 => "since of config input isn't the claim block number",
+        ClaimNotFound = 87 => "claim input locked by nostr lock not found",
+        RetargetMismatched = 88 => "config data doesn't match the recorded claim",
+    }
+}
+
+#[cfg(feature = "ckb-std")]
+impl From<SysError> for DifficultyError {
+    fn from(err: SysError) -> Self {
+        match err {
+            SysError::IndexOutOfBound => Self::IndexOutOfBound,
+            SysError::ItemMissing => Self::ItemMissing,
+            SysError::LengthNotEnough(_) => Self::LengthNotEnough,
+            SysError::Encoding => Self::Encoding,
+            SysError::Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+        }
+    }
+}
+
+impl From<NostrError> for DifficultyError {
+    fn from(_err: NostrError) -> Self {
+        Self::InvalidConfigData
+    }
+}
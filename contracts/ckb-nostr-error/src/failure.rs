@@ -0,0 +1,101 @@
+use crate::{ScriptError, ScriptKind};
+
+/// Cells of a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellField {
+    Inputs,
+    Outputs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptGroupType {
+    Lock,
+    Type,
+}
+
+/// Code hash of a script, by the hash type of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeHash {
+    /// hash type `data`, `data1` or `data2`
+    Data([u8; 32]),
+    Type([u8; 32]),
+}
+
+/// A script exiting with non-zero code, parsed from the message of a
+/// `ckb_error::Error` or of a `send_transaction` RPC error, e.g.
+///
+/// ```text
+/// TransactionScriptError { source: Inputs[0].Lock, cause: ValidationFailure: see error code 11 on page https://nervosnetwork.github.io/ckb-script-error-codes/by-type-hash/{code_hash}.html#11 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptFailure {
+    /// the first cell of the failed script group
+    pub source: Option<(CellField, usize, ScriptGroupType)>,
+    pub code_hash: Option<CodeHash>,
+    pub exit_code: i8,
+}
+
+impl ScriptFailure {
+    /// `None` if no script exits with non-zero code in the message
+    pub fn parse(message: &str) -> Option<Self> {
+        let exit_code = after(message, "see error code ")?;
+        let exit_code = exit_code[..exit_code.find(' ')?].parse().ok()?;
+        Some(Self {
+            source: parse_source(message),
+            code_hash: parse_code_hash(message),
+            exit_code,
+        })
+    }
+
+    #[cfg(feature = "ckb-error")]
+    pub fn from_ckb_error(err: &ckb_error::Error) -> Option<Self> {
+        use std::string::ToString;
+        Self::parse(&err.to_string())
+    }
+
+    /// Decode the exit code as one of `kind`, `None` if the script never exits
+    /// with it
+    pub fn decode(&self, kind: ScriptKind) -> Option<ScriptError> {
+        ScriptError::decode(kind, self.exit_code)
+    }
+}
+
+fn after<'a>(message: &'a str, pattern: &str) -> Option<&'a str> {
+    message
+        .find(pattern)
+        .map(|index| &message[index + pattern.len()..])
+}
+
+// `Inputs[0].Lock`
+fn parse_source(message: &str) -> Option<(CellField, usize, ScriptGroupType)> {
+    let source = after(message, "source: ")?;
+    let (field, source) = source.split_once('[')?;
+    let field = match field {
+        "Inputs" => CellField::Inputs,
+        "Outputs" => CellField::Outputs,
+        _ => return None,
+    };
+    let (index, source) = source.split_once("].")?;
+    let group_type = if source.starts_with("Lock") {
+        ScriptGroupType::Lock
+    } else if source.starts_with("Type") {
+        ScriptGroupType::Type
+    } else {
+        return None;
+    };
+    Some((field, index.parse().ok()?, group_type))
+}
+
+// `by-type-hash/{code_hash}` or `by-data-hash/{code_hash}` of the page
+fn parse_code_hash(message: &str) -> Option<CodeHash> {
+    let decode = |hex: &str| {
+        let mut hash = [0u8; 32];
+        hex::decode_to_slice(hex.get(..64)?, &mut hash).ok()?;
+        Some(hash)
+    };
+    if let Some(hex) = after(message, "by-type-hash/") {
+        decode(hex).map(CodeHash::Type)
+    } else {
+        decode(after(message, "by-data-hash/")?).map(CodeHash::Data)
+    }
+}
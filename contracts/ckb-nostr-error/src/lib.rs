@@ -0,0 +1,116 @@
+#![no_std]
+#[cfg(feature = "std")]
+extern crate std;
+
+#[macro_use]
+mod macros;
+
+pub mod binding;
+pub mod difficulty;
+pub mod failure;
+pub mod lock;
+
+use core::fmt;
+
+pub use binding::BindingError;
+pub use difficulty::DifficultyError;
+pub use failure::{CellField, CodeHash, ScriptFailure, ScriptGroupType};
+pub use lock::LockError;
+
+/// The scripts of nostr binding protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    NostrLock,
+    NostrBinding,
+    PowDifficulty,
+}
+
+impl fmt::Display for ScriptKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScriptKind::NostrLock => "nostr lock",
+            ScriptKind::NostrBinding => "nostr binding",
+            ScriptKind::PowDifficulty => "pow difficulty",
+        })
+    }
+}
+
+/// Exit code of any of the scripts. Codes 1 to 4 are shared by all of them,
+/// so the kind of the failed script is needed to decode one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptError {
+    NostrLock(LockError),
+    NostrBinding(BindingError),
+    PowDifficulty(DifficultyError),
+}
+
+impl ScriptError {
+    /// Decode `code` as an exit code of `kind`, `None` if the script never
+    /// exits with it
+    pub fn decode(kind: ScriptKind, code: i8) -> Option<Self> {
+        match kind {
+            ScriptKind::NostrLock => LockError::try_from(code).ok().map(Self::NostrLock),
+            ScriptKind::NostrBinding => BindingError::try_from(code).ok().map(Self::NostrBinding),
+            ScriptKind::PowDifficulty => DifficultyError::try_from(code)
+                .ok()
+                .map(Self::PowDifficulty),
+        }
+    }
+
+    pub fn kind(&self) -> ScriptKind {
+        match self {
+            ScriptError::NostrLock(_) => ScriptKind::NostrLock,
+            ScriptError::NostrBinding(_) => ScriptKind::NostrBinding,
+            ScriptError::PowDifficulty(_) => ScriptKind::PowDifficulty,
+        }
+    }
+
+    pub fn code(&self) -> i8 {
+        match *self {
+            ScriptError::NostrLock(err) => err as i8,
+            ScriptError::NostrBinding(err) => err as i8,
+            ScriptError::PowDifficulty(err) => err as i8,
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            ScriptError::NostrLock(err) => err.message(),
+            ScriptError::NostrBinding(err) => err.message(),
+            ScriptError::PowDifficulty(err) => err.message(),
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} error {}: {}",
+            self.kind(),
+            self.code(),
+            self.message()
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ScriptError {}
+
+impl From<LockError> for ScriptError {
+    fn from(err: LockError) -> Self {
+        Self::NostrLock(err)
+    }
+}
+
+impl From<BindingError> for ScriptError {
+    fn from(err: BindingError) -> Self {
+        Self::NostrBinding(err)
+    }
+}
+
+impl From<DifficultyError> for ScriptError {
+    fn from(err: DifficultyError) -> Self {
+        Self::PowDifficulty(err)
+    }
+}
@@ -0,0 +1,82 @@
+use ckb_nostr_utils::error::Error as NostrError;
+#[cfg(feature = "ckb-std")]
+use ckb_std::error::SysError;
+
+exit_codes! {
+    /// Exit codes of the nostr lock script
+    pub enum LockError {
+        IndexOutOfBound = 1 => "index out of bound",
+        ItemMissing = 2 => "item missing",
+        LengthNotEnough = 3 => "length not enough",
+        Encoding = 4 => "invalid molecule encoding",
+        // nostr lock script error code starts from 10
+        Unknown = 10 => "unknown syscall error",
+        SighashAllMismatched = 11 => "ckb_sighash_all tag doesn't match the transaction",
+        KindMismatched = 12 => "unexpected kind of unlock event",
+        ContentMismatched = 13 => "unexpected content of unlock event",
+        GenerateSighashAll = 14 => "failed to generate sighash_all",
+        InvalidScriptArgs = 15 => "invalid script args",
+        InvalidPublicKey = 16 => "invalid public key",
+        InvalidEventId = 17 => "event id doesn't match the event",
+        ValidationFail = 18 => "invalid signature",
+        InvalidSignatureFormat = 19 => "invalid signature format",
+        UnknownKey = 20 => "unknown key in event json",
+        Json = 21 => "invalid event json",
+        PubkeyNotEmpty = 22 => "pubkey hash of PoW lock must be empty",
+        WrongTargetDifficulty = 23 => "wrong target difficulty of nonce tag",
+        PoWDifficulty = 24 => "event id doesn't meet the PoW difficulty",
+        NonceNotFound = 25 => "nonce tag not found",
+        PubkeyNotFound = 26 => "event author doesn't match the pubkey hash",
+        WrongWitnessArgs = 27 => "unlock event not found in witness lock",
+        ChainMismatched = 28 => "genesis header of ckb_chain tag not in header deps",
+        SummaryMismatched = 29 => "summary content doesn't match the transaction",
+        TimestampNotProven = 30 => "timestamp not proven by header deps or since",
+        CreatedAtOutOfWindow = 31 => "created_at out of window",
+        RecoveryDelayNotReached = 32 => "recovery delay not reached",
+        InvalidDelegation = 33 => "invalid delegation tag",
+        DelegationConditionsNotMet = 34 => "delegation conditions not met",
+        InvalidSession = 35 => "invalid session",
+        SessionExpired = 36 => "session expired",
+        SessionCapacityExceeded = 37 => "session capacity exceeded",
+        SessionLockNotAllowed = 38 => "output lock not allowed by session",
+        AcpOutputMismatched = 39 => "anyone-can-pay output not found",
+        AcpAmountDecreased = 40 => "anyone-can-pay amount decreased",
+        DifficultyConfigNotFound = 41 => "difficulty config cell not found",
+        InvalidDifficultyConfig = 42 => "invalid difficulty config",
+        KeyAndPowDifficultyZero = 43 => "zero difficulty of key-and-pow lock",
+        KeyAndPowWrongTargetDifficulty = 44 => "wrong target difficulty of key-and-pow nonce tag",
+        KeyAndPowDifficulty = 45 => "event id doesn't meet the key-and-pow difficulty",
+        KeyAndPowNonceNotFound = 46 => "nonce tag of key-and-pow lock not found",
+        ContentConfigNotFound = 47 => "content config cell not found",
+        InvalidContentConfig = 48 => "invalid content config",
+    }
+}
+
+#[cfg(feature = "ckb-std")]
+impl From<SysError> for LockError {
+    fn from(err: SysError) -> Self {
+        match err {
+            SysError::IndexOutOfBound => Self::IndexOutOfBound,
+            SysError::ItemMissing => Self::ItemMissing,
+            SysError::LengthNotEnough(_) => Self::LengthNotEnough,
+            SysError::Encoding => Self::Encoding,
+            SysError::Unknown(_) => Self::Unknown,
+        }
+    }
+}
+
+impl From<NostrError> for LockError {
+    fn from(err: NostrError) -> Self {
+        match err {
+            NostrError::InvalidPublicKey => Self::InvalidPublicKey,
+            NostrError::InvalidEventId => Self::InvalidEventId,
+            NostrError::ValidationFail => Self::ValidationFail,
+            NostrError::InvalidSignatureFormat => Self::InvalidSignatureFormat,
+            NostrError::UnknownKey(_) => Self::UnknownKey,
+            NostrError::Json(_) => Self::Json,
+            NostrError::InvalidDelegation => Self::InvalidDelegation,
+            NostrError::InvalidLockArgs => Self::InvalidScriptArgs,
+            NostrError::InvalidDifficultyConfig => Self::InvalidDifficultyConfig,
+        }
+    }
+}
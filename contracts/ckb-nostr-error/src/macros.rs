@@ -0,0 +1,46 @@
+/// Define an exit code enum of a script, with `TryFrom<i8>` and a message for
+/// each code
+macro_rules! exit_codes {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $code:literal => $message:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(i8)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant = $code,)*
+        }
+
+        impl $name {
+            /// Why the script exits with this code
+            pub fn message(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $message,)*
+                }
+            }
+        }
+
+        impl TryFrom<i8> for $name {
+            type Error = i8;
+
+            fn try_from(code: i8) -> Result<Self, i8> {
+                match code {
+                    $($code => Ok(Self::$variant),)*
+                    code => Err(code),
+                }
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str(self.message())
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl std::error::Error for $name {}
+    };
+}
@@ -0,0 +1,108 @@
+//! Flags in nostr binding script args and the cell data they lay out, parsed
+//! by the type script and shared with host side tools building and reading
+//! binding cells.
+
+use core::fmt;
+
+// flags byte appended to script args
+// cell data commitment flags, at most one of them can be set
+// cell data equals the content of bound event
+pub const FLAG_DATA_CONTENT: u8 = 0x01;
+// cell data equals sha256 of the content of bound event
+pub const FLAG_DATA_CONTENT_HASH: u8 = 0x02;
+// cell data equals the `x` tag of a NIP-94 file metadata event
+pub const FLAG_DATA_FILE_HASH: u8 = 0x04;
+pub const FLAGS_DATA_COMMITMENT: u8 =
+    FLAG_DATA_CONTENT | FLAG_DATA_CONTENT_HASH | FLAG_DATA_FILE_HASH;
+// the bound event is archived in cell data as canonical JSON, can't be used
+// with data commitment
+pub const FLAG_ARCHIVE: u8 = 0x08;
+// cell data can't be changed on transfer
+pub const FLAGS_IMMUTABLE_DATA: u8 = FLAGS_DATA_COMMITMENT | FLAG_ARCHIVE;
+// cell data starts with the expiration timestamp of bound event, the binding
+// can only be burned after it
+pub const FLAG_EXPIRATION: u8 = 0x10;
+// cell data carries the royalty declared by bound event, which must be paid on
+// sales
+pub const FLAG_ROYALTY: u8 = 0x20;
+pub const FLAGS_ALL: u8 = FLAGS_IMMUTABLE_DATA | FLAG_EXPIRATION | FLAG_ROYALTY;
+pub const ROYALTY_RATE_BASE: u16 = 10000;
+
+/// Royalty paid to `recipient` on sales, `rate` in basis points
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Royalty {
+    pub recipient: [u8; 32],
+    pub rate: u16,
+    /// Royalty is also paid in this UDT when present
+    pub udt_type_hash: Option<[u8; 32]>,
+}
+
+impl Royalty {
+    /// `<recipient lock hash, 32 bytes><rate, 2 bytes><UDT type hash, 32 bytes>`
+    pub const LEN: usize = 66;
+
+    fn from_slice(data: &[u8]) -> Self {
+        let udt_type_hash: [u8; 32] = data[34..66].try_into().unwrap();
+        Self {
+            recipient: data[0..32].try_into().unwrap(),
+            rate: u16::from_le_bytes(data[32..34].try_into().unwrap()),
+            udt_type_hash: if udt_type_hash == [0u8; 32] {
+                None
+            } else {
+                Some(udt_type_hash)
+            },
+        }
+    }
+}
+
+// in the order of the values of `royalty` tag
+impl fmt::Display for Royalty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", hex::encode(self.recipient), self.rate)?;
+        if let Some(udt_type_hash) = &self.udt_type_hash {
+            write!(f, " {}", hex::encode(udt_type_hash))?;
+        }
+        Ok(())
+    }
+}
+
+/// Binding cell data laid out as
+/// `[expiration, 8 bytes][royalty, 66 bytes][payload]`. Each field before the
+/// payload is only present when its flag is set in script args.
+pub struct BindingData<'a> {
+    pub expiration: Option<u64>,
+    pub royalty: Option<Royalty>,
+    /// Committed to, or archiving, the bound event
+    pub payload: &'a [u8],
+}
+
+impl<'a> BindingData<'a> {
+    /// Split `data` by the fields of `flags`, `None` when it's too short for
+    /// them
+    pub fn parse(flags: u8, data: &'a [u8]) -> Option<Self> {
+        let mut rest = data;
+        let expiration = if flags & FLAG_EXPIRATION != 0 {
+            let (field, remaining) = split(rest, 8)?;
+            rest = remaining;
+            Some(u64::from_le_bytes(field.try_into().unwrap()))
+        } else {
+            None
+        };
+        let royalty = if flags & FLAG_ROYALTY != 0 {
+            let (field, remaining) = split(rest, Royalty::LEN)?;
+            rest = remaining;
+            Some(Royalty::from_slice(field))
+        } else {
+            None
+        };
+        Some(Self {
+            expiration,
+            royalty,
+            payload: rest,
+        })
+    }
+}
+
+fn split(data: &[u8], len: usize) -> Option<(&[u8], &[u8])> {
+    (data.len() >= len).then(|| data.split_at(len))
+}
@@ -0,0 +1,112 @@
+//! Delegated event signing
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/26.md>
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str::FromStr;
+use sha2::{digest::Digest, Sha256};
+
+use crate::error::Error;
+use crate::event::Event;
+use crate::key::public_key::PublicKey;
+
+pub const DELEGATION_TAG_NAME: &str = "delegation";
+
+/// A condition of delegation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// Event kind, e.g. kind=1
+    Kind(u16),
+    /// Creation time before, e.g. created_at<1679000000
+    CreatedBefore(u64),
+    /// Creation time after, e.g. created_at>1676000000
+    CreatedAfter(u64),
+}
+
+impl Condition {
+    /// Evaluate whether an event of `kind` created at `created_at` satisfies
+    /// this condition
+    pub fn evaluate(&self, kind: u16, created_at: u64) -> bool {
+        match self {
+            Self::Kind(k) => kind == *k,
+            Self::CreatedBefore(t) => created_at < *t,
+            Self::CreatedAfter(t) => created_at > *t,
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |_| Error::InvalidDelegation;
+        if let Some(kind) = s.strip_prefix("kind=") {
+            return Ok(Self::Kind(kind.parse().map_err(invalid)?));
+        }
+        if let Some(created_before) = s.strip_prefix("created_at<") {
+            return Ok(Self::CreatedBefore(
+                created_before.parse().map_err(invalid)?,
+            ));
+        }
+        if let Some(created_after) = s.strip_prefix("created_at>") {
+            return Ok(Self::CreatedAfter(created_after.parse().map_err(invalid)?));
+        }
+        Err(Error::InvalidDelegation)
+    }
+}
+
+/// Delegation tag: `["delegation", <delegator pubkey>, <conditions>, <token>]`
+pub struct Delegation {
+    delegator: PublicKey,
+    conditions: String,
+    token: String,
+}
+
+impl Delegation {
+    /// Find the delegation tag of `event`
+    pub fn from_event(event: &Event) -> Option<Result<Self, Error>> {
+        event.tags().iter().find_map(|tag| {
+            let tag = tag.as_vec();
+            if tag.first().map(String::as_str) != Some(DELEGATION_TAG_NAME) {
+                return None;
+            }
+            if tag.len() != 4 {
+                return Some(Err(Error::InvalidDelegation));
+            }
+            Some(PublicKey::from_hex(&tag[1]).map(|delegator| Self {
+                delegator,
+                conditions: tag[2].clone(),
+                token: tag[3].clone(),
+            }))
+        })
+    }
+
+    pub fn delegator(&self) -> &PublicKey {
+        &self.delegator
+    }
+
+    pub fn conditions(&self) -> Result<Vec<Condition>, Error> {
+        if self.conditions.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.conditions
+            .split('&')
+            .map(Condition::from_str)
+            .collect()
+    }
+
+    /// Verify the token is signed by delegator over
+    /// `nostr:delegation:<delegatee pubkey>:<conditions>`
+    pub fn verify_token(&self, delegatee: &PublicKey) -> Result<(), Error> {
+        let token = format!(
+            "nostr:{}:{}:{}",
+            DELEGATION_TAG_NAME,
+            delegatee.to_hex(),
+            self.conditions
+        );
+        let message = Sha256::digest(token.as_bytes());
+        self.delegator.verify_prehash(&message, &self.token)
+    }
+}
@@ -0,0 +1,93 @@
+//! Data of the PoW difficulty config cell, shared by the nostr lock script
+//! reading the difficulty and the type script retargeting it.
+
+use alloc::vec::Vec;
+
+use crate::error::Error;
+
+// <difficulty, 1 byte><min difficulty, 1 byte><target interval, 8 bytes><window, 1 byte>
+pub const DIFFICULTY_CONFIG_HEADER_LEN: usize = 11;
+pub const MAX_WINDOW: u8 = 64;
+
+/// Config data laid out as
+/// `<difficulty, 1 byte><min difficulty, 1 byte><target interval, 8 bytes><window, 1 byte><claims, 8 bytes each>`.
+/// Integers are little endian.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DifficultyConfig {
+    /// current PoW difficulty
+    pub difficulty: u8,
+    /// difficulty never retargets below this
+    pub min_difficulty: u8,
+    /// expected blocks between two claims
+    pub target_interval: u64,
+    /// difficulty retargets every `window` claims
+    pub window: u8,
+    /// block numbers of recent claims, ascending and fewer than `window`
+    pub claims: Vec<u64>,
+}
+
+impl DifficultyConfig {
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < DIFFICULTY_CONFIG_HEADER_LEN
+            || (data.len() - DIFFICULTY_CONFIG_HEADER_LEN) % 8 != 0
+        {
+            return Err(Error::InvalidDifficultyConfig);
+        }
+        let config = Self {
+            difficulty: data[0],
+            min_difficulty: data[1],
+            target_interval: u64::from_le_bytes(data[2..10].try_into().unwrap()),
+            window: data[10],
+            claims: data[DIFFICULTY_CONFIG_HEADER_LEN..]
+                .chunks(8)
+                .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+        };
+        if config.min_difficulty == 0
+            || config.difficulty < config.min_difficulty
+            || config.target_interval == 0
+            || config.window < 2
+            || config.window > MAX_WINDOW
+            || config.claims.len() >= config.window as usize
+            || config.claims.windows(2).any(|w| w[0] > w[1])
+        {
+            return Err(Error::InvalidDifficultyConfig);
+        }
+        Ok(config)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(DIFFICULTY_CONFIG_HEADER_LEN + self.claims.len() * 8);
+        data.push(self.difficulty);
+        data.push(self.min_difficulty);
+        data.extend(self.target_interval.to_le_bytes());
+        data.push(self.window);
+        for claim in &self.claims {
+            data.extend(claim.to_le_bytes());
+        }
+        data
+    }
+
+    /// The config after recording a claim at `block_number`. When `window`
+    /// claims are recorded, difficulty goes up by one if they came in less
+    /// than half of the expected blocks, or down by one if in more than
+    /// twice. Only the last claim is kept for the next window.
+    pub fn record_claim(&self, block_number: u64) -> Self {
+        let mut next = self.clone();
+        next.claims.push(block_number);
+        if next.claims.len() < self.window as usize {
+            return next;
+        }
+        let span = block_number - next.claims[0];
+        let expected = self
+            .target_interval
+            .saturating_mul(next.claims.len() as u64 - 1);
+        if span.saturating_mul(2) < expected {
+            next.difficulty = next.difficulty.saturating_add(1);
+        } else if span > expected.saturating_mul(2) {
+            next.difficulty = next.difficulty.saturating_sub(1).max(self.min_difficulty);
+        }
+        next.claims = alloc::vec![block_number];
+        next
+    }
+}
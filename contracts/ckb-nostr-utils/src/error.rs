@@ -15,4 +15,10 @@ pub enum Error {
     UnknownKey(String),
     /// Error serializing or deserializing JSON data
     Json(String),
+    /// Malformed NIP-26 delegation tag
+    InvalidDelegation,
+    /// Malformed nostr lock script args
+    InvalidLockArgs,
+    /// Malformed PoW difficulty config cell data
+    InvalidDifficultyConfig,
 }
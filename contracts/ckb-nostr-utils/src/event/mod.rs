@@ -10,8 +10,6 @@ use core::fmt;
 use core::hash::{Hash, Hasher};
 use core::ops::Deref;
 use core::str::FromStr;
-use k256::ecdsa::signature::hazmat::PrehashVerifier;
-use k256::schnorr::{Signature, VerifyingKey};
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
@@ -242,15 +240,9 @@ impl Event {
 
     /// Verify only event [`Signature`]
     pub fn verify_signature(&self) -> Result<(), Error> {
-        let sig_bytes = hex::decode(&self.inner.sig).map_err(|_| Error::InvalidSignatureFormat)?;
-        let signature =
-            Signature::try_from(sig_bytes.as_slice()).map_err(|_| Error::InvalidSignatureFormat)?;
-        let pubkey = &self.inner.pubkey;
-        let pk =
-            VerifyingKey::from_bytes(pubkey.as_slice()).map_err(|_| Error::InvalidPublicKey)?;
-        let message = self.id();
-        pk.verify_prehash(message.as_slice(), &signature)
-            .map_err(|_| Error::ValidationFail)
+        self.inner
+            .pubkey
+            .verify_prehash(self.id().as_slice(), &self.inner.sig)
     }
 
     /// Check POW
@@ -259,6 +251,12 @@ impl Event {
     pub fn check_pow(&self, difficulty: u8) -> bool {
         self.inner.id.check_pow(difficulty)
     }
+
+    /// Serialize [`Event`] into JSON with keys in NIP-01 order and no whitespace,
+    /// regardless of the key order it was deserialized from
+    pub fn as_canonical_json(&self) -> Vec<u8> {
+        serde_json::to_vec(&self.inner).unwrap()
+    }
     /// Deserialize [`Event`] from JSON
     ///
     /// **This method NOT verify the signature!**
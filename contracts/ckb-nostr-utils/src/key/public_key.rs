@@ -2,6 +2,8 @@ use crate::error::Error;
 use alloc::string::String;
 use core::str::FromStr;
 use core::{fmt, ops::Deref};
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::schnorr::{Signature, VerifyingKey};
 use serde::{Deserialize, Deserializer, Serialize};
 
 pub const PUBKEY_SIZE: usize = 32;
@@ -72,6 +74,16 @@ impl PublicKey {
     pub fn as_slice(&self) -> &[u8; PUBKEY_SIZE] {
         &self.inner
     }
+
+    /// Verify schnorr signature `sig` in `hex` of a 32 bytes `message`
+    pub fn verify_prehash(&self, message: &[u8], sig: &str) -> Result<(), Error> {
+        let sig_bytes = hex::decode(sig).map_err(|_| Error::InvalidSignatureFormat)?;
+        let signature =
+            Signature::try_from(sig_bytes.as_slice()).map_err(|_| Error::InvalidSignatureFormat)?;
+        let pk = VerifyingKey::from_bytes(self.as_slice()).map_err(|_| Error::InvalidPublicKey)?;
+        pk.verify_prehash(message, &signature)
+            .map_err(|_| Error::ValidationFail)
+    }
 }
 
 impl FromStr for PublicKey {
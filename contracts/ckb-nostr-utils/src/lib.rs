@@ -1,6 +1,12 @@
 #![no_std]
 extern crate alloc;
 
+pub mod binding;
+pub mod blake2b;
+pub mod delegation;
+pub mod difficulty;
 pub mod error;
 pub mod event;
 pub mod key;
+pub mod lock_args;
+pub mod since;
@@ -0,0 +1,240 @@
+//! Nostr lock script args, parsed by the lock script and shared with host
+//! side tools to encode and decode them.
+
+use alloc::vec::Vec;
+
+use crate::error::Error;
+use crate::since::SINCE_RELATIVE;
+
+pub const SCRIPT_ARGS_LEN: usize = 21;
+// `content` is a summary of the transaction instead of the fixed string
+pub const FLAG_SUMMARY_CONTENT: u8 = 0x01;
+// `created_at` of event must be close to the proven timestamp, with a field of
+// the window in seconds, 4 bytes little endian
+pub const FLAG_CREATED_AT_WINDOW: u8 = 0x02;
+// a recovery key can unlock after a relative delay, with a field of the
+// recovery pubkey hash(20 bytes) and the relative `since`(8 bytes little endian)
+pub const FLAG_RECOVERY: u8 = 0x04;
+// cells can be spent without signature when paid into matching outputs
+pub const FLAG_ANYONE_CAN_PAY: u8 = 0x08;
+// PoW difficulty is read from a difficulty config cell, with a field of the
+// type hash(32 bytes) of the config cell
+pub const FLAG_DYNAMIC_POW: u8 = 0x10;
+// the event must be signed by the pubkey hash and meet the PoW difficulty
+pub const FLAG_KEY_AND_POW: u8 = 0x20;
+// kind and content of event are declared by a content config cell, with a
+// field of the type hash(32 bytes) of the config cell. Can't be used with
+// `FLAG_SUMMARY_CONTENT`.
+pub const FLAG_CONTENT_CONFIG: u8 = 0x40;
+pub const FLAGS_ALL: u8 = FLAG_SUMMARY_CONTENT
+    | FLAG_CREATED_AT_WINDOW
+    | FLAG_RECOVERY
+    | FLAG_ANYONE_CAN_PAY
+    | FLAG_DYNAMIC_POW
+    | FLAG_KEY_AND_POW
+    | FLAG_CONTENT_CONFIG;
+// flags which give no one but the owner a say in unlocking. Recovery, anyone
+// can pay, dynamic PoW and content config let another key or a cell of another
+// owner unlock or block the cell.
+pub const FLAGS_OWNER_ONLY: u8 = FLAG_SUMMARY_CONTENT | FLAG_CREATED_AT_WINDOW | FLAG_KEY_AND_POW;
+// flags carrying a field
+const FIELD_FLAGS: [u8; 4] = [
+    FLAG_CREATED_AT_WINDOW,
+    FLAG_RECOVERY,
+    FLAG_DYNAMIC_POW,
+    FLAG_CONTENT_CONFIG,
+];
+// args longer than the legacy ones have `VERSION_MARK | version` after the
+// pubkey hash
+pub const VERSION_MARK: u8 = 0x80;
+pub const VERSION_1: u8 = 1;
+// fields of version 1 with this bit set can be ignored by parsers not knowing them
+pub const FIELD_IGNORABLE: u8 = 0x80;
+
+/// Layout of the args
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArgsFormat {
+    /// `<PoW difficulty, 1 byte><pubkey hash, 20 bytes>`
+    #[default]
+    Legacy,
+    /// `<PoW difficulty><pubkey hash><VERSION_MARK | 1><flags, 1 byte>{<field id, 1 byte><length, 1 byte><value>}`.
+    /// Field ids are ascending. A flag carrying a field has the field id of its
+    /// flag bit, present only when the flag is set.
+    V1,
+}
+
+/// Nostr lock script args in one of the formats in [`ArgsFormat`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LockArgs {
+    pub format: ArgsFormat,
+    pub pow_difficulty: u8,
+    pub pubkey_hash: [u8; 20],
+    pub flags: u8,
+    /// `created_at` of event must be within this many seconds of the proven
+    /// timestamp
+    pub created_at_window: Option<u32>,
+    pub recovery: Option<Recovery>,
+    /// type hash of the difficulty config cell
+    pub difficulty_config: Option<[u8; 32]>,
+    /// type hash of the content config cell
+    pub content_config: Option<[u8; 32]>,
+    /// ignorable fields of version 1 unknown to this parser, as (id, value)
+    pub extra_fields: Vec<(u8, Vec<u8>)>,
+}
+
+/// A recovery key which can only unlock when inputs have been idle for `delay`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recovery {
+    pub pubkey_hash: [u8; 20],
+    /// relative `since`
+    pub delay: u64,
+}
+
+impl LockArgs {
+    pub fn parse(args: &[u8]) -> Result<Self, Error> {
+        if args.len() < SCRIPT_ARGS_LEN {
+            return Err(Error::InvalidLockArgs);
+        }
+        let mut lock_args = Self {
+            pow_difficulty: args[0],
+            pubkey_hash: args[1..21].try_into().unwrap(),
+            ..Default::default()
+        };
+        match args.get(SCRIPT_ARGS_LEN) {
+            None => {}
+            Some(&version) if version == VERSION_MARK | VERSION_1 => {
+                lock_args.format = ArgsFormat::V1;
+                lock_args.parse_v1(&args[SCRIPT_ARGS_LEN + 1..])?;
+            }
+            Some(_) => return Err(Error::InvalidLockArgs),
+        }
+        if lock_args.flags & FLAG_SUMMARY_CONTENT != 0 && lock_args.flags & FLAG_CONTENT_CONFIG != 0
+        {
+            return Err(Error::InvalidLockArgs);
+        }
+        if let Some(recovery) = &lock_args.recovery {
+            if recovery.delay & SINCE_RELATIVE == 0 {
+                return Err(Error::InvalidLockArgs);
+            }
+        }
+        Ok(lock_args)
+    }
+
+    fn parse_v1(&mut self, rest: &[u8]) -> Result<(), Error> {
+        let (flags, mut rest) = split(rest, 1)?;
+        let flags = flags[0];
+        if flags & !FLAGS_ALL != 0 {
+            return Err(Error::InvalidLockArgs);
+        }
+        self.flags = flags;
+        let mut last_id = None;
+        let mut found = 0u8;
+        while !rest.is_empty() {
+            let (header, remaining) = split(rest, 2)?;
+            let (id, len) = (header[0], header[1] as usize);
+            let (value, remaining) = split(remaining, len)?;
+            rest = remaining;
+            if last_id.map_or(false, |last| id <= last) {
+                return Err(Error::InvalidLockArgs);
+            }
+            last_id = Some(id);
+            if id & FIELD_IGNORABLE != 0 {
+                self.extra_fields.push((id, value.to_vec()));
+                continue;
+            }
+            if flags & id == 0 || field_len(id) == 0 || field_len(id) != len {
+                return Err(Error::InvalidLockArgs);
+            }
+            found |= id;
+            self.set_field(id, value);
+        }
+        let fields = FIELD_FLAGS.iter().fold(0, |all, flag| all | flag);
+        if found != flags & fields {
+            return Err(Error::InvalidLockArgs);
+        }
+        Ok(())
+    }
+
+    fn set_field(&mut self, flag: u8, field: &[u8]) {
+        match flag {
+            FLAG_CREATED_AT_WINDOW => {
+                self.created_at_window = Some(u32::from_le_bytes(field.try_into().unwrap()))
+            }
+            FLAG_RECOVERY => {
+                self.recovery = Some(Recovery {
+                    pubkey_hash: field[0..20].try_into().unwrap(),
+                    delay: u64::from_le_bytes(field[20..28].try_into().unwrap()),
+                })
+            }
+            FLAG_DYNAMIC_POW => self.difficulty_config = Some(field.try_into().unwrap()),
+            FLAG_CONTENT_CONFIG => self.content_config = Some(field.try_into().unwrap()),
+            _ => unreachable!(),
+        }
+    }
+
+    fn field(&self, flag: u8) -> Option<Vec<u8>> {
+        match flag {
+            FLAG_CREATED_AT_WINDOW => self.created_at_window.map(|w| w.to_le_bytes().to_vec()),
+            FLAG_RECOVERY => self.recovery.map(|r| {
+                let mut field = r.pubkey_hash.to_vec();
+                field.extend(r.delay.to_le_bytes());
+                field
+            }),
+            FLAG_DYNAMIC_POW => self.difficulty_config.map(|hash| hash.to_vec()),
+            FLAG_CONTENT_CONFIG => self.content_config.map(|hash| hash.to_vec()),
+            _ => None,
+        }
+    }
+
+    /// Whether the cell is unlocked by the key of `pubkey_hash` only, with
+    /// flags in `FLAGS_OWNER_ONLY` and no unknown fields
+    pub fn is_owned_by(&self, pubkey_hash: &[u8; 20]) -> bool {
+        self.pubkey_hash == *pubkey_hash
+            && self.flags & !FLAGS_OWNER_ONLY == 0
+            && (self.pow_difficulty == 0 || self.flags & FLAG_KEY_AND_POW != 0)
+            && self.extra_fields.is_empty()
+    }
+
+    /// Encode in `format`. A legacy format with flags set is encoded in
+    /// version 1. Fields are written for the flags set, and must be present.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut args = alloc::vec![self.pow_difficulty];
+        args.extend(self.pubkey_hash);
+        let fields = FIELD_FLAGS
+            .into_iter()
+            .filter(|flag| self.flags & flag != 0)
+            .map(|flag| (flag, self.field(flag).expect("field of flag")));
+        match self.format {
+            ArgsFormat::Legacy if self.flags == 0 => {}
+            ArgsFormat::Legacy | ArgsFormat::V1 => {
+                args.push(VERSION_MARK | VERSION_1);
+                args.push(self.flags);
+                let mut fields: Vec<(u8, Vec<u8>)> = fields.collect();
+                fields.extend(self.extra_fields.iter().cloned());
+                fields.sort_by_key(|(id, _)| *id);
+                for (id, field) in fields {
+                    args.push(id);
+                    args.push(field.len() as u8);
+                    args.extend(field);
+                }
+            }
+        }
+        args
+    }
+}
+
+fn field_len(flag: u8) -> usize {
+    match flag {
+        FLAG_CREATED_AT_WINDOW => 4,
+        FLAG_RECOVERY => 28,
+        FLAG_DYNAMIC_POW | FLAG_CONTENT_CONFIG => 32,
+        _ => 0,
+    }
+}
+
+fn split(data: &[u8], len: usize) -> Result<(&[u8], &[u8]), Error> {
+    if data.len() < len {
+        return Err(Error::InvalidLockArgs);
+    }
+    Ok(data.split_at(len))
+}
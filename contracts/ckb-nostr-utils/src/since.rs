@@ -0,0 +1,81 @@
+//! `since` of inputs, read by the scripts to tell the time a transaction
+//! proves and whether a relative delay has passed, and shared with host side
+//! tools checking the same rules.
+
+#[cfg(feature = "ckb-std")]
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::prelude::Unpack,
+    high_level::{load_header, load_input_since, QueryIter},
+};
+
+pub const SINCE_RELATIVE: u64 = 0x8000_0000_0000_0000;
+// absolute since with timestamp metric
+pub const SINCE_ABSOLUTE_TIMESTAMP: u64 = 0x4000_0000_0000_0000;
+pub const SINCE_METRIC_MASK: u64 = 0x6000_0000_0000_0000;
+pub const SINCE_METRIC_EPOCH: u64 = 0x2000_0000_0000_0000;
+// absolute `since` with block number metric has all flags unset
+pub const SINCE_FLAGS_MASK: u64 = 0xff00_0000_0000_0000;
+pub const SINCE_VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+
+/// The latest timestamp(in seconds) a transaction proves to be committed
+/// after, from the timestamps(in milliseconds) of its header deps and the
+/// absolute `since` of its inputs. Return 0 when there is no such proof.
+pub fn proven_timestamp(
+    header_timestamps: impl IntoIterator<Item = u64>,
+    input_sinces: impl IntoIterator<Item = u64>,
+) -> u64 {
+    let from_headers = header_timestamps
+        .into_iter()
+        .map(|timestamp| timestamp / 1000)
+        .max()
+        .unwrap_or(0);
+    let from_since = input_sinces
+        .into_iter()
+        .filter(|since| since & SINCE_FLAGS_MASK == SINCE_ABSOLUTE_TIMESTAMP)
+        .map(|since| since & SINCE_VALUE_MASK)
+        .max()
+        .unwrap_or(0);
+    from_headers.max(from_since)
+}
+
+/// [`proven_timestamp`] of the current transaction
+#[cfg(feature = "ckb-std")]
+pub fn load_proven_timestamp() -> u64 {
+    proven_timestamp(
+        QueryIter::new(load_header, Source::HeaderDep)
+            .map(|header| header.raw().timestamp().unpack()),
+        QueryIter::new(load_input_since, Source::Input),
+    )
+}
+
+/// Whether `since` satisfies the relative `delay`: same flags and no less
+/// value.
+pub fn since_satisfied(since: u64, delay: u64) -> bool {
+    if since & SINCE_FLAGS_MASK != delay & SINCE_FLAGS_MASK {
+        return false;
+    }
+    let (value, delay_value) = (since & SINCE_VALUE_MASK, delay & SINCE_VALUE_MASK);
+    if delay & SINCE_METRIC_MASK == SINCE_METRIC_EPOCH {
+        epoch_ge(value, delay_value)
+    } else {
+        value >= delay_value
+    }
+}
+
+// compare epochs with fraction: `<number, 24 bits><index, 16 bits><length, 16 bits>`
+fn epoch_ge(a: u64, b: u64) -> bool {
+    let split = |epoch: u64| {
+        let number = (epoch & 0xff_ffff) as u128;
+        let index = ((epoch >> 24) & 0xffff) as u128;
+        let length = ((epoch >> 40) & 0xffff) as u128;
+        if length == 0 {
+            (number, 0, 1)
+        } else {
+            (number, index, length)
+        }
+    };
+    let (n1, i1, l1) = split(a);
+    let (n2, i2, l2) = split(b);
+    (n1 * l1 + i1) * l2 >= (n2 * l2 + i2) * l1
+}
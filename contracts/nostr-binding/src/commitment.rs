@@ -0,0 +1,44 @@
+use alloc::vec::Vec;
+use ckb_nostr_utils::binding::{FLAG_DATA_CONTENT, FLAG_DATA_CONTENT_HASH, FLAG_DATA_FILE_HASH};
+use ckb_nostr_utils::event::Event;
+use sha2::{Digest, Sha256};
+
+use crate::config::{FILE_HASH_TAG_NAME, FILE_METADATA_KIND};
+use crate::error::Error;
+
+/// Check the binding cell data against the bound `event`, according to the
+/// data commitment flag in script args.
+pub fn verify_data_commitment(event: &Event, flags: u8, data: &[u8]) -> Result<(), Error> {
+    let expected: Vec<u8> = if flags & FLAG_DATA_CONTENT != 0 {
+        event.content().as_bytes().to_vec()
+    } else if flags & FLAG_DATA_CONTENT_HASH != 0 {
+        Sha256::digest(event.content().as_bytes()).to_vec()
+    } else if flags & FLAG_DATA_FILE_HASH != 0 {
+        file_hash(event)?.to_vec()
+    } else {
+        return Ok(());
+    };
+    if data != expected.as_slice() {
+        return Err(Error::DataCommitmentMismatched);
+    }
+    Ok(())
+}
+
+/// The sha256 of the file described by a NIP-94 file metadata event, taken
+/// from its `x` tag.
+fn file_hash(event: &Event) -> Result<[u8; 32], Error> {
+    if event.kind() != FILE_METADATA_KIND {
+        return Err(Error::FileHashNotFound);
+    }
+    let tag = event
+        .tags()
+        .iter()
+        .find(|t| {
+            let t = t.as_vec();
+            t.len() >= 2 && t[0] == FILE_HASH_TAG_NAME
+        })
+        .ok_or(Error::FileHashNotFound)?;
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(&tag.as_vec()[1], &mut hash).map_err(|_| Error::FileHashNotFound)?;
+    Ok(hash)
+}
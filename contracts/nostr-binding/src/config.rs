@@ -1 +1,15 @@
 pub const GLOBAL_UNIQUE_ID_TAG_NAME: &str = "ckb_global_unique_id";
+pub const FILE_HASH_TAG_NAME: &str = "x";
+// NIP-40 expiration timestamp
+pub const EXPIRATION_TAG_NAME: &str = "expiration";
+// ["royalty", <recipient lock hash>, <rate in basis points>, <optional UDT type hash>]
+pub const ROYALTY_TAG_NAME: &str = "royalty";
+// ["ckb_mint_to", <lock hash>]
+pub const MINT_TO_TAG_NAME: &str = "ckb_mint_to";
+// ["ckb_mint_to_author", <nostr lock code hash>]
+pub const MINT_TO_AUTHOR_TAG_NAME: &str = "ckb_mint_to_author";
+// NIP-94 file metadata
+pub const FILE_METADATA_KIND: u16 = 1063;
+
+pub const SCRIPT_ARGS_LEN: usize = 64;
+pub const SCRIPT_ARGS_WITH_FLAGS_LEN: usize = 65;
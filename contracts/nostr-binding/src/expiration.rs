@@ -0,0 +1,24 @@
+use ckb_nostr_utils::event::Event;
+
+use crate::config::EXPIRATION_TAG_NAME;
+use crate::error::Error;
+
+/// The expiration stored in cell data must be equal to the `expiration` tag of
+/// bound event.
+pub fn verify_expiration(event: &Event, expiration: u64) -> Result<(), Error> {
+    let tag = event
+        .tags()
+        .iter()
+        .find(|t| {
+            let t = t.as_vec();
+            t.len() >= 2 && t[0] == EXPIRATION_TAG_NAME
+        })
+        .ok_or(Error::ExpirationNotFound)?;
+    let value: u64 = tag.as_vec()[1]
+        .parse()
+        .map_err(|_| Error::ExpirationNotFound)?;
+    if value != expiration {
+        return Err(Error::ExpirationMismatched);
+    }
+    Ok(())
+}
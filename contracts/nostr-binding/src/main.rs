@@ -1,11 +1,17 @@
 #![no_std]
 #![no_main]
 
+mod commitment;
 mod config;
 mod error;
+mod expiration;
+mod mint_to;
+mod royalty;
 mod type_id;
 
+use ckb_nostr_utils::binding::{BindingData, FLAGS_ALL, FLAGS_IMMUTABLE_DATA, FLAG_ARCHIVE};
 use ckb_nostr_utils::event::Event;
+use ckb_nostr_utils::since::load_proven_timestamp;
 use ckb_std::default_alloc;
 ckb_std::entry!(program_entry);
 default_alloc!(4 * 1024, 1400 * 1024, 64);
@@ -14,11 +20,15 @@ use ckb_std::syscalls::current_cycles;
 use ckb_std::{
     ckb_constants::Source,
     debug,
-    high_level::{load_script, load_witness_args},
+    high_level::{load_cell_data, load_script, load_witness_args},
 };
 
-use config::GLOBAL_UNIQUE_ID_TAG_NAME;
+use commitment::verify_data_commitment;
+use config::{GLOBAL_UNIQUE_ID_TAG_NAME, SCRIPT_ARGS_LEN, SCRIPT_ARGS_WITH_FLAGS_LEN};
 use error::Error;
+use expiration::verify_expiration;
+use mint_to::verify_mint_to;
+use royalty::{verify_royalty, verify_royalty_paid};
 use type_id::{has_type_id_cell, validate_type_id};
 
 pub fn program_entry() -> i8 {
@@ -31,25 +41,40 @@ pub fn program_entry() -> i8 {
 fn entry() -> Result<(), Error> {
     let script = load_script()?;
     let args = script.as_reader().args();
-    if args.len() != 64 {
-        return Err(Error::WrongArgsLength);
-    }
+    let args = args.raw_data();
+    let flags = match args.len() {
+        SCRIPT_ARGS_LEN => 0,
+        SCRIPT_ARGS_WITH_FLAGS_LEN => args[SCRIPT_ARGS_LEN],
+        _ => return Err(Error::WrongArgsLength),
+    };
+    validate_flags(flags)?;
     let mut event_id = [0; 32];
-    event_id.copy_from_slice(&args.raw_data()[0..32]);
+    event_id.copy_from_slice(&args[0..32]);
     let mut global_unique_id = [0; 32];
-    global_unique_id.copy_from_slice(&args.raw_data()[32..]);
+    global_unique_id.copy_from_slice(&args[32..64]);
 
     validate_type_id(global_unique_id)?;
 
     if !has_type_id_cell(0, Source::GroupInput) {
         // mint a new binding cell
-        let witness_args = load_witness_args(0, Source::GroupOutput)?;
-        let witness = witness_args
-            .output_type()
-            .to_opt()
-            .ok_or(Error::WitnessNotExisting)?
-            .raw_data();
-        let event = Event::from_json(witness.as_ref())?;
+        let data = load_cell_data(0, Source::GroupOutput)?;
+        let binding_data = BindingData::parse(flags, &data).ok_or(Error::InvalidCellData)?;
+        let event = if flags & FLAG_ARCHIVE != 0 {
+            // the bound event is archived in cell data, no witness is needed
+            let event = Event::from_json(binding_data.payload)?;
+            if event.as_canonical_json() != binding_data.payload {
+                return Err(Error::NonCanonicalEvent);
+            }
+            event
+        } else {
+            let witness_args = load_witness_args(0, Source::GroupOutput)?;
+            let witness = witness_args
+                .output_type()
+                .to_opt()
+                .ok_or(Error::WitnessNotExisting)?
+                .raw_data();
+            Event::from_json(witness.as_ref())?
+        };
         event.verify_id()?;
         if &event_id != event.id().as_bytes() {
             return Err(Error::InvalidEventId);
@@ -68,7 +93,50 @@ fn entry() -> Result<(), Error> {
             "verify_signature costs {} k cycles",
             (current_cycles() - start) / 1024
         );
+        verify_mint_to(&event)?;
+        verify_data_commitment(&event, flags, binding_data.payload)?;
+        if let Some(expiration) = binding_data.expiration {
+            verify_expiration(&event, expiration)?;
+        }
+        if let Some(royalty) = &binding_data.royalty {
+            verify_royalty(&event, royalty)?;
+        }
+    } else if flags != 0 {
+        // transfer or burn a binding cell
+        let input_data = load_cell_data(0, Source::GroupInput)?;
+        let input = BindingData::parse(flags, &input_data).ok_or(Error::InvalidCellData)?;
+        if let Some(royalty) = &input.royalty {
+            verify_royalty_paid(royalty)?;
+        }
+        if has_type_id_cell(0, Source::GroupOutput) {
+            // transfer a binding cell
+            let output_data = load_cell_data(0, Source::GroupOutput)?;
+            // the committed or archived data can't be changed
+            if flags & FLAGS_IMMUTABLE_DATA != 0 && input_data != output_data {
+                return Err(Error::DataChanged);
+            }
+            let output = BindingData::parse(flags, &output_data).ok_or(Error::InvalidCellData)?;
+            if input.expiration != output.expiration || input.royalty != output.royalty {
+                return Err(Error::DataChanged);
+            }
+            if let Some(expiration) = input.expiration {
+                // expired binding can only be burned
+                if load_proven_timestamp() >= expiration {
+                    return Err(Error::BindingExpired);
+                }
+            }
+        }
     }
 
     Ok(())
 }
+
+fn validate_flags(flags: u8) -> Result<(), Error> {
+    if flags & !FLAGS_ALL != 0 {
+        return Err(Error::InvalidFlags);
+    }
+    if (flags & FLAGS_IMMUTABLE_DATA).count_ones() > 1 {
+        return Err(Error::InvalidFlags);
+    }
+    Ok(())
+}
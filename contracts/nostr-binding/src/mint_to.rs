@@ -0,0 +1,57 @@
+use ckb_nostr_utils::blake2b::blake160;
+use ckb_nostr_utils::event::Event;
+use ckb_nostr_utils::lock_args::LockArgs;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{core::ScriptHashType, packed::Script, prelude::*},
+    high_level::{load_cell_lock, load_cell_lock_hash},
+};
+
+use crate::config::{MINT_TO_AUTHOR_TAG_NAME, MINT_TO_TAG_NAME};
+use crate::error::Error;
+
+/// When bound event carries any `ckb_mint_to` or `ckb_mint_to_author` tag, the
+/// minted binding cell must be locked by one of the locks named by them.
+pub fn verify_mint_to(event: &Event) -> Result<(), Error> {
+    let lock = load_cell_lock(0, Source::GroupOutput)?;
+    let lock_hash_hex = hex::encode(load_cell_lock_hash(0, Source::GroupOutput)?);
+    let mut restricted = false;
+    for tag in event.tags() {
+        let tag = tag.as_vec();
+        if tag.len() != 2 {
+            continue;
+        }
+        let matched = if tag[0] == MINT_TO_TAG_NAME {
+            tag[1] == lock_hash_hex
+        } else if tag[0] == MINT_TO_AUTHOR_TAG_NAME {
+            is_author_lock(event, &lock, &tag[1])
+        } else {
+            continue;
+        };
+        if matched {
+            return Ok(());
+        }
+        restricted = true;
+    }
+    if restricted {
+        Err(Error::MintLockMismatched)
+    } else {
+        Ok(())
+    }
+}
+
+/// The author's nostr lock: `code_hash` named by the tag, `hash_type` type, and
+/// args in any format owned by the blake160 of author, see
+/// `LockArgs::is_owned_by`
+fn is_author_lock(event: &Event, lock: &Script, code_hash_hex: &str) -> bool {
+    let hash_type: u8 = lock.hash_type().into();
+    if hash_type != ScriptHashType::Type as u8
+        || hex::encode(lock.code_hash().as_slice()) != code_hash_hex
+    {
+        return false;
+    }
+    match LockArgs::parse(&lock.args().raw_data()) {
+        Ok(args) => args.is_owned_by(&blake160(event.author().as_slice())),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,112 @@
+use ckb_nostr_utils::binding::{Royalty, ROYALTY_RATE_BASE};
+use ckb_nostr_utils::event::Event;
+use ckb_std::{
+    ckb_constants::Source,
+    error::SysError,
+    high_level::{
+        load_cell_capacity, load_cell_data, load_cell_lock_hash, load_cell_type_hash, QueryIter,
+    },
+};
+
+use crate::config::ROYALTY_TAG_NAME;
+use crate::error::Error;
+
+#[derive(Clone, Copy)]
+enum Asset<'a> {
+    Capacity,
+    Udt(&'a [u8; 32]),
+}
+
+/// The royalty stored in cell data must be equal to the `royalty` tag of bound
+/// event.
+pub fn verify_royalty(event: &Event, royalty: &Royalty) -> Result<(), Error> {
+    let tag = event
+        .tags()
+        .iter()
+        .map(|t| t.as_vec())
+        .find(|t| t.len() >= 3 && t[0] == ROYALTY_TAG_NAME)
+        .ok_or(Error::RoyaltyNotFound)?;
+    let mut recipient = [0u8; 32];
+    hex::decode_to_slice(&tag[1], &mut recipient).map_err(|_| Error::RoyaltyMismatched)?;
+    let rate: u16 = tag[2].parse().map_err(|_| Error::InvalidRoyaltyRate)?;
+    let udt_type_hash = match tag.get(3) {
+        Some(hash) => {
+            let mut udt_type_hash = [0u8; 32];
+            hex::decode_to_slice(hash, &mut udt_type_hash).map_err(|_| Error::RoyaltyMismatched)?;
+            Some(udt_type_hash)
+        }
+        None => None,
+    };
+    if rate > ROYALTY_RATE_BASE {
+        return Err(Error::InvalidRoyaltyRate);
+    }
+    let declared = Royalty {
+        recipient,
+        rate,
+        udt_type_hash,
+    };
+    if &declared != royalty {
+        return Err(Error::RoyaltyMismatched);
+    }
+    Ok(())
+}
+
+/// When the binding cell is spent, the amount of capacity(or UDT) flowing to the
+/// previous owner is treated as the price of a sale. The royalty recipient
+/// should receive no less than `price * rate / 10000` in the same transaction.
+pub fn verify_royalty_paid(royalty: &Royalty) -> Result<(), Error> {
+    let owner = load_cell_lock_hash(0, Source::GroupInput)?;
+    if owner == royalty.recipient {
+        return Ok(());
+    }
+    verify_asset_paid(&owner, royalty, Asset::Capacity)?;
+    if let Some(udt_type_hash) = &royalty.udt_type_hash {
+        verify_asset_paid(&owner, royalty, Asset::Udt(udt_type_hash))?;
+    }
+    Ok(())
+}
+
+fn verify_asset_paid(owner: &[u8; 32], royalty: &Royalty, asset: Asset) -> Result<(), Error> {
+    let price = received(owner, asset)?;
+    if price == 0 {
+        return Ok(());
+    }
+    let rate = royalty.rate as u128;
+    let base = ROYALTY_RATE_BASE as u128;
+    let due = price / base * rate + price % base * rate / base;
+    if received(&royalty.recipient, asset)? < due {
+        return Err(Error::RoyaltyNotPaid);
+    }
+    Ok(())
+}
+
+/// Net amount of `asset` received by cells locked by `lock_hash`
+fn received(lock_hash: &[u8; 32], asset: Asset) -> Result<u128, Error> {
+    let output = total(Source::Output, lock_hash, asset)?;
+    let input = total(Source::Input, lock_hash, asset)?;
+    Ok(output.saturating_sub(input))
+}
+
+fn total(source: Source, lock_hash: &[u8; 32], asset: Asset) -> Result<u128, Error> {
+    let mut sum = 0u128;
+    for (index, cell_lock_hash) in QueryIter::new(load_cell_lock_hash, source).enumerate() {
+        if &cell_lock_hash != lock_hash {
+            continue;
+        }
+        let amount = match asset {
+            Asset::Capacity => load_cell_capacity(index, source)? as u128,
+            Asset::Udt(type_hash) => {
+                if load_cell_type_hash(index, source)?.as_ref() != Some(type_hash) {
+                    continue;
+                }
+                let data = load_cell_data(index, source)?;
+                if data.len() < 16 {
+                    return Err(SysError::Encoding.into());
+                }
+                u128::from_le_bytes(data[0..16].try_into().unwrap())
+            }
+        };
+        sum = sum.saturating_add(amount);
+    }
+    Ok(sum)
+}
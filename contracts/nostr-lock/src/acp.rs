@@ -0,0 +1,76 @@
+use alloc::vec::Vec;
+use ckb_std::{
+    ckb_constants::Source,
+    error::SysError,
+    high_level::{
+        load_cell_capacity, load_cell_data, load_cell_lock_hash, load_cell_type_hash,
+        load_script_hash, load_witness, load_witness_args, QueryIter,
+    },
+};
+
+use crate::error::Error;
+
+/// Whether the first witness of script group carries an unlock event
+pub fn has_signature() -> Result<bool, Error> {
+    match load_witness(0, Source::GroupInput) {
+        Ok(witness) if witness.is_empty() => Ok(false),
+        Ok(_) => {
+            let witness_args = load_witness_args(0, Source::GroupInput)?;
+            Ok(witness_args.lock().to_opt().is_some())
+        }
+        Err(SysError::IndexOutOfBound) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Anyone can spend cells of this lock without signature, as long as every
+/// input is paid into its matching output: the only output with the same lock
+/// and type. Capacity can't decrease, and data is unchanged or only increases
+/// UDT amount.
+pub fn verify_anyone_can_pay() -> Result<(), Error> {
+    let lock_hash = load_script_hash()?;
+    let outputs: Vec<(usize, Option<[u8; 32]>)> =
+        QueryIter::new(load_cell_lock_hash, Source::Output)
+            .enumerate()
+            .filter(|(_, output_lock_hash)| *output_lock_hash == lock_hash)
+            .map(|(index, _)| load_cell_type_hash(index, Source::Output).map(|t| (index, t)))
+            .collect::<Result<_, _>>()?;
+    let inputs: Vec<Option<[u8; 32]>> =
+        QueryIter::new(load_cell_type_hash, Source::GroupInput).collect();
+
+    for (input_index, type_hash) in inputs.iter().enumerate() {
+        // inputs of the same type can't share a matching output
+        if inputs.iter().filter(|t| *t == type_hash).count() > 1 {
+            return Err(Error::AcpOutputMismatched);
+        }
+        let mut matched = outputs.iter().filter(|(_, t)| t == type_hash);
+        let output_index = match (matched.next(), matched.next()) {
+            (Some((index, _)), None) => *index,
+            _ => return Err(Error::AcpOutputMismatched),
+        };
+
+        let input_capacity = load_cell_capacity(input_index, Source::GroupInput)?;
+        let output_capacity = load_cell_capacity(output_index, Source::Output)?;
+        if output_capacity < input_capacity {
+            return Err(Error::AcpAmountDecreased);
+        }
+        let input_data = load_cell_data(input_index, Source::GroupInput)?;
+        let output_data = load_cell_data(output_index, Source::Output)?;
+        if input_data != output_data
+            && !(type_hash.is_some() && udt_increased(&input_data, &output_data))
+        {
+            return Err(Error::AcpAmountDecreased);
+        }
+    }
+    Ok(())
+}
+
+// UDT amount is the first 16 bytes of data, the rest can't change
+fn udt_increased(input: &[u8], output: &[u8]) -> bool {
+    if input.len() < 16 || input.len() != output.len() || input[16..] != output[16..] {
+        return false;
+    }
+    let input_amount = u128::from_le_bytes(input[0..16].try_into().unwrap());
+    let output_amount = u128::from_le_bytes(output[0..16].try_into().unwrap());
+    output_amount >= input_amount
+}
@@ -0,0 +1,76 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use ckb_std::ckb_types::{packed::Script, prelude::*};
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32M_CONST: u32 = 0x2bc830a3;
+// full payload format of CKB address
+const FULL_FORMAT: u8 = 0x00;
+
+/// Encode `script` as a full format CKB address with bech32m, e.g. `ckb1qz...`
+pub fn encode_address(hrp: &str, script: &Script) -> String {
+    let mut payload = Vec::with_capacity(34 + script.args().len());
+    payload.push(FULL_FORMAT);
+    payload.extend_from_slice(script.code_hash().as_slice());
+    payload.extend_from_slice(script.hash_type().as_slice());
+    payload.extend_from_slice(&script.args().raw_data());
+
+    let data = convert_bits(&payload);
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(&data);
+    values.extend_from_slice(&[0u8; 6]);
+    let polymod = polymod(&values) ^ BECH32M_CONST;
+
+    let mut address = String::with_capacity(hrp.len() + 1 + data.len() + 6);
+    address.push_str(hrp);
+    address.push('1');
+    for d in data {
+        address.push(CHARSET[d as usize] as char);
+    }
+    for i in 0..6 {
+        let d = (polymod >> (5 * (5 - i))) & 0x1f;
+        address.push(CHARSET[d as usize] as char);
+    }
+    address
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut values: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|c| c & 0x1f));
+    values
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut checksum = 1u32;
+    for v in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ *v as u32;
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= g;
+            }
+        }
+    }
+    checksum
+}
+
+// regroup 8-bit bytes into 5-bit values, with padding
+fn convert_bits(data: &[u8]) -> Vec<u8> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let mut ret = Vec::with_capacity((data.len() * 8 + 4) / 5);
+    for b in data {
+        acc = (acc << 8) | *b as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            ret.push(((acc >> bits) & 0x1f) as u8);
+        }
+    }
+    if bits > 0 {
+        ret.push(((acc << (5 - bits)) & 0x1f) as u8);
+    }
+    ret
+}
@@ -1,5 +1,19 @@
 pub const SIGHASH_ALL_TAG_NAME: &str = "ckb_sighash_all";
 pub const NOSTR_LOCK_KIND: u16 = 23334;
-pub const SCRIPT_ARGS_LEN: usize = 21;
 pub const NOSTR_LOCK_CONTENT: &str = "Signing a CKB transaction\n\nIMPORTANT: Please verify the integrity and authenticity of connected Nostr client before signing this message\n";
 pub const NONCE: &str = "nonce";
+// <kind, 2 bytes little endian><sha256 of content, 32 bytes>
+pub const CONTENT_CONFIG_LEN: usize = 34;
+pub const CHAIN_TAG_NAME: &str = "ckb_chain";
+pub const NOSTR_LOCK_WARNING: &str = "IMPORTANT: Please verify the integrity and authenticity of connected Nostr client before signing this message";
+pub const SUMMARY_HEADER: &str = "Signing a CKB transaction on ";
+pub const MAINNET_PREFIX: &str = "ckb";
+pub const TESTNET_PREFIX: &str = "ckt";
+// ["ckb_session", <authorization event JSON>]
+pub const SESSION_TAG_NAME: &str = "ckb_session";
+// the authorization event granting a session key
+pub const SESSION_KIND: u16 = 23335;
+pub const SESSION_PUBKEY_TAG_NAME: &str = "ckb_session_pubkey";
+pub const EXPIRATION_TAG_NAME: &str = "expiration";
+pub const MAX_CAPACITY_TAG_NAME: &str = "ckb_max_capacity";
+pub const ALLOWED_LOCK_TAG_NAME: &str = "ckb_allowed_lock";
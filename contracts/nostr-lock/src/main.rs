@@ -1,27 +1,42 @@
 #![no_std]
 #![no_main]
 
-mod blake2b;
+mod acp;
+mod address;
 mod config;
 mod error;
+mod session;
+mod summary;
 mod util;
 
+use acp::{has_signature, verify_anyone_can_pay};
 use alloc::format;
 use alloc::string::String;
-use blake2b::blake160;
+use ckb_nostr_utils::blake2b::blake160;
+use ckb_nostr_utils::delegation::{Condition, Delegation};
+use ckb_nostr_utils::difficulty::DifficultyConfig;
 use ckb_nostr_utils::event::Event;
+use ckb_nostr_utils::lock_args::{
+    LockArgs, FLAG_ANYONE_CAN_PAY, FLAG_KEY_AND_POW, FLAG_SUMMARY_CONTENT,
+};
+use ckb_nostr_utils::since::load_proven_timestamp;
 use ckb_std::ckb_constants::Source;
 use ckb_std::ckb_types::bytes::Bytes;
-use ckb_std::ckb_types::prelude::Unpack;
-use ckb_std::high_level::{load_script, load_witness_args};
+use ckb_std::ckb_types::prelude::{Entity, Unpack};
+use ckb_std::high_level::{
+    load_cell_data, load_cell_type_hash, load_header, load_script, load_witness_args, QueryIter,
+};
 use ckb_std::syscalls::current_cycles;
 use ckb_std::{debug, default_alloc};
-use config::NONCE;
 use config::NOSTR_LOCK_CONTENT;
 use config::NOSTR_LOCK_KIND;
-use config::{SCRIPT_ARGS_LEN, SIGHASH_ALL_TAG_NAME};
+use config::{CHAIN_TAG_NAME, SIGHASH_ALL_TAG_NAME};
+use config::{CONTENT_CONFIG_LEN, NONCE};
 use error::Error;
-use util::generate_sighash_all;
+use session::{has_session, verify_session};
+use sha2::{Digest, Sha256};
+use summary::verify_summary;
+use util::{generate_sighash_all, inputs_since_satisfied};
 
 ckb_std::entry!(program_entry);
 default_alloc!(4 * 1024, 1400 * 1024, 64);
@@ -36,8 +51,9 @@ pub fn program_entry() -> i8 {
 pub fn entry() -> Result<(), Error> {
     let script = load_script()?;
     let args: Bytes = script.args().unpack();
-    if args.len() != SCRIPT_ARGS_LEN {
-        return Err(Error::InvalidScriptArgs);
+    let args = LockArgs::parse(&args)?;
+    if args.flags & FLAG_ANYONE_CAN_PAY != 0 && !has_signature()? {
+        return verify_anyone_can_pay();
     }
 
     let sighash_all = generate_sighash_all()?;
@@ -61,20 +77,105 @@ pub fn entry() -> Result<(), Error> {
     event.verify_id()?;
 
     // rule 3
-    if event.kind() != NOSTR_LOCK_KIND {
+    if args.content_config.is_none() && event.kind() != NOSTR_LOCK_KIND {
         return Err(Error::KindMismatched);
     }
-    if event.content() != NOSTR_LOCK_CONTENT {
+    if let Some(type_hash) = args.content_config {
+        verify_content_config(&event, type_hash)?;
+    } else if args.flags & FLAG_SUMMARY_CONTENT != 0 {
+        verify_summary(event.content())?;
+    } else if event.content() != NOSTR_LOCK_CONTENT {
         return Err(Error::ContentMismatched);
     }
+    verify_chain(&event)?;
+    if let Some(window) = args.created_at_window {
+        verify_created_at(&event, window)?;
+    }
 
-    let pow_difficulty = args[0];
-    let schnorr_pubkey_hash: [u8; 20] = args[1..21].try_into().unwrap();
-    if pow_difficulty == 0 {
-        verify_key(&event, schnorr_pubkey_hash)
+    if let Some(recovery) = &args.recovery {
+        // unlock by recovery key, only after inputs have been idle for the delay
+        if blake160(event.author().as_slice()) == recovery.pubkey_hash {
+            if !inputs_since_satisfied(recovery.delay) {
+                return Err(Error::RecoveryDelayNotReached);
+            }
+            return verify_key(&event, recovery.pubkey_hash);
+        }
+    }
+    // the difficulty in args is a floor of the one in config cell
+    let pow_difficulty = match args.difficulty_config {
+        Some(type_hash) => load_difficulty(type_hash)?.max(args.pow_difficulty),
+        None => args.pow_difficulty,
+    };
+    if args.flags & FLAG_KEY_AND_POW != 0 {
+        verify_key_and_pow(&event, pow_difficulty, args.pubkey_hash)
+    } else if pow_difficulty == 0 {
+        verify_key(&event, args.pubkey_hash)
     } else {
-        verify_pow(&event, pow_difficulty, schnorr_pubkey_hash)
+        verify_pow(&event, pow_difficulty, args.pubkey_hash)
+    }
+}
+
+// current difficulty of the config cell in inputs. Every claim records itself
+// by updating the config cell, so that the difficulty retargets from the pace
+// of all claims
+fn load_difficulty(type_hash: [u8; 32]) -> Result<u8, Error> {
+    let index = QueryIter::new(load_cell_type_hash, Source::Input)
+        .position(|hash| hash == Some(type_hash))
+        .ok_or(Error::DifficultyConfigNotFound)?;
+    let data = load_cell_data(index, Source::Input)?;
+    Ok(DifficultyConfig::parse(&data)?.difficulty)
+}
+
+// kind and sha256 of content are declared by the config cell in cell deps
+fn verify_content_config(event: &Event, type_hash: [u8; 32]) -> Result<(), Error> {
+    let index = QueryIter::new(load_cell_type_hash, Source::CellDep)
+        .position(|hash| hash == Some(type_hash))
+        .ok_or(Error::ContentConfigNotFound)?;
+    let data = load_cell_data(index, Source::CellDep)?;
+    if data.len() != CONTENT_CONFIG_LEN {
+        return Err(Error::InvalidContentConfig);
+    }
+    if event.kind() != u16::from_le_bytes([data[0], data[1]]) {
+        return Err(Error::KindMismatched);
     }
+    if Sha256::digest(event.content().as_bytes())[..] != data[2..] {
+        return Err(Error::ContentMismatched);
+    }
+    Ok(())
+}
+
+// the genesis header named by each `ckb_chain` tag must be in header deps
+fn verify_chain(event: &Event) -> Result<(), Error> {
+    for tag in event.tags() {
+        let entries = tag.as_vec();
+        if entries.len() != 2 || entries[0] != CHAIN_TAG_NAME {
+            continue;
+        }
+        let found = QueryIter::new(load_header, Source::HeaderDep).any(|header| {
+            let number: u64 = header.raw().number().unpack();
+            number == 0 && hex::encode(header.calc_header_hash().as_slice()) == entries[1]
+        });
+        if !found {
+            return Err(Error::ChainMismatched);
+        }
+    }
+    Ok(())
+}
+
+// `created_at` of event must be within `window` seconds of the proven timestamp
+fn verify_created_at(event: &Event, window: u32) -> Result<(), Error> {
+    let timestamp = load_proven_timestamp();
+    if timestamp == 0 {
+        return Err(Error::TimestampNotProven);
+    }
+    let window = window as u64;
+    let created_at = event.created_at();
+    if created_at.saturating_add(window) < timestamp
+        || created_at > timestamp.saturating_add(window)
+    {
+        return Err(Error::CreatedAtOutOfWindow);
+    }
+    Ok(())
 }
 
 fn verify_pow(
@@ -82,6 +183,40 @@ fn verify_pow(
     pow_difficulty: u8,
     schnorr_pubkey_hash: [u8; 20],
 ) -> Result<(), Error> {
+    let validated = check_pow(event, pow_difficulty)?;
+    // rule 7
+    if schnorr_pubkey_hash != [0u8; 20] {
+        return Err(Error::PubkeyNotEmpty);
+    }
+    if validated {
+        Ok(())
+    } else {
+        Err(Error::NonceNotFound)
+    }
+}
+
+// both rules 4 to 6 and rules 8 to 9 apply, rule 7 doesn't
+fn verify_key_and_pow(
+    event: &Event,
+    pow_difficulty: u8,
+    schnorr_pubkey_hash: [u8; 20],
+) -> Result<(), Error> {
+    if pow_difficulty == 0 {
+        return Err(Error::KeyAndPowDifficultyZero);
+    }
+    let validated = check_pow(event, pow_difficulty).map_err(|err| match err {
+        Error::WrongTargetDifficulty => Error::KeyAndPowWrongTargetDifficulty,
+        Error::PoWDifficulty => Error::KeyAndPowDifficulty,
+        err => err,
+    })?;
+    if !validated {
+        return Err(Error::KeyAndPowNonceNotFound);
+    }
+    verify_key(event, schnorr_pubkey_hash)
+}
+
+// rules 4 to 6, return whether a `nonce` tag is found
+fn check_pow(event: &Event, pow_difficulty: u8) -> Result<bool, Error> {
     let pow_difficulty_str = format!("{}", pow_difficulty);
 
     let mut validated = false;
@@ -102,21 +237,48 @@ fn verify_pow(
             }
         }
     }
-    // rule 7
-    if schnorr_pubkey_hash != [0u8; 20] {
-        return Err(Error::PubkeyNotEmpty);
+    Ok(validated)
+}
+
+// the author is delegated by the key of `schnorr_pubkey_hash` via NIP-26
+fn verify_delegation(event: &Event, schnorr_pubkey_hash: [u8; 20]) -> Result<(), Error> {
+    let delegation = Delegation::from_event(event).ok_or(Error::PubkeyNotFound)??;
+    if blake160(delegation.delegator().as_slice()) != schnorr_pubkey_hash {
+        return Err(Error::PubkeyNotFound);
     }
-    if validated {
-        Ok(())
+    delegation.verify_token(event.author_ref())?;
+    let conditions = delegation.conditions()?;
+    // the delegation must be explicitly granted for unlock events, whose kind
+    // is checked by rule 3
+    if !conditions.contains(&Condition::Kind(event.kind())) {
+        return Err(Error::DelegationConditionsNotMet);
+    }
+    // `created_at` is picked by the delegatee, so time conditions must also
+    // hold for the time the transaction proves
+    let timestamp = if conditions.iter().any(|c| !matches!(c, Condition::Kind(_))) {
+        match load_proven_timestamp() {
+            0 => return Err(Error::TimestampNotProven),
+            timestamp => timestamp,
+        }
     } else {
-        Err(Error::NonceNotFound)
+        0
+    };
+    if !conditions.iter().all(|c| {
+        c.evaluate(event.kind(), event.created_at()) && c.evaluate(event.kind(), timestamp)
+    }) {
+        return Err(Error::DelegationConditionsNotMet);
     }
+    Ok(())
 }
 
 fn verify_key(event: &Event, schnorr_pubkey_hash: [u8; 20]) -> Result<(), Error> {
     // rule 8
     if blake160(event.author().as_slice()) != schnorr_pubkey_hash {
-        return Err(Error::PubkeyNotFound);
+        if has_session(event) {
+            verify_session(event, schnorr_pubkey_hash)?;
+        } else {
+            verify_delegation(event, schnorr_pubkey_hash)?;
+        }
     }
     // rule 9
     let start = current_cycles();
@@ -0,0 +1,81 @@
+use alloc::string::ToString;
+use ckb_nostr_utils::event::Event;
+use ckb_std::{
+    ckb_constants::Source,
+    high_level::{load_cell_capacity, load_cell_lock_hash, load_script_hash, QueryIter},
+};
+
+use crate::config::{
+    ALLOWED_LOCK_TAG_NAME, EXPIRATION_TAG_NAME, MAX_CAPACITY_TAG_NAME, SESSION_KIND,
+    SESSION_PUBKEY_TAG_NAME, SESSION_TAG_NAME,
+};
+use crate::error::Error;
+use ckb_nostr_utils::blake2b::blake160;
+use ckb_nostr_utils::since::load_proven_timestamp;
+
+pub fn has_session(event: &Event) -> bool {
+    event
+        .get_tag_content(SESSION_TAG_NAME.to_string())
+        .is_some()
+}
+
+/// The `event` is signed by a session key, granted by an authorization event
+/// of the owner key in `ckb_session` tag. The transaction must stay within the
+/// limits of the authorization.
+pub fn verify_session(event: &Event, owner_pubkey_hash: [u8; 20]) -> Result<(), Error> {
+    let json = event
+        .get_tag_content(SESSION_TAG_NAME.to_string())
+        .ok_or(Error::InvalidSession)?;
+    let auth = Event::from_json(json.as_bytes())?;
+    auth.verify_id()?;
+    if auth.kind() != SESSION_KIND {
+        return Err(Error::InvalidSession);
+    }
+    if blake160(auth.author().as_slice()) != owner_pubkey_hash {
+        return Err(Error::PubkeyNotFound);
+    }
+    auth.verify_signature()?;
+
+    let session_pubkey = auth
+        .get_tag_content(SESSION_PUBKEY_TAG_NAME.to_string())
+        .ok_or(Error::InvalidSession)?;
+    if session_pubkey != event.author().to_hex() {
+        return Err(Error::InvalidSession);
+    }
+    let expiration: u64 = parse_tag(&auth, EXPIRATION_TAG_NAME)?;
+    // `created_at` is picked by the session key, so the transaction must also
+    // prove a time before the expiration
+    let timestamp = match load_proven_timestamp() {
+        0 => return Err(Error::TimestampNotProven),
+        timestamp => timestamp,
+    };
+    if event.created_at() >= expiration || timestamp >= expiration {
+        return Err(Error::SessionExpired);
+    }
+    let max_capacity: u64 = parse_tag(&auth, MAX_CAPACITY_TAG_NAME)?;
+    let allowed_locks = auth.get_tags_content(ALLOWED_LOCK_TAG_NAME.to_string());
+
+    // outputs can only go back to current lock or to allowed locks
+    let lock_hash = load_script_hash()?;
+    let mut returned = 0u64;
+    for (index, output_lock_hash) in QueryIter::new(load_cell_lock_hash, Source::Output).enumerate()
+    {
+        if output_lock_hash == lock_hash {
+            returned = returned.saturating_add(load_cell_capacity(index, Source::Output)?);
+        } else if !allowed_locks.contains(&hex::encode(output_lock_hash).as_str()) {
+            return Err(Error::SessionLockNotAllowed);
+        }
+    }
+    let spent = QueryIter::new(load_cell_capacity, Source::GroupInput)
+        .fold(0u64, |sum, capacity| sum.saturating_add(capacity));
+    if spent.saturating_sub(returned) > max_capacity {
+        return Err(Error::SessionCapacityExceeded);
+    }
+    Ok(())
+}
+
+fn parse_tag(auth: &Event, name: &str) -> Result<u64, Error> {
+    auth.get_tag_content(name.to_string())
+        .and_then(|value| value.parse().ok())
+        .ok_or(Error::InvalidSession)
+}
@@ -0,0 +1,74 @@
+use alloc::format;
+use alloc::string::String;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{packed::Script, prelude::*},
+    high_level::{load_cell, load_cell_capacity, load_cell_data, QueryIter},
+};
+
+use crate::address::encode_address;
+use crate::config::{MAINNET_PREFIX, NOSTR_LOCK_WARNING, SUMMARY_HEADER, TESTNET_PREFIX};
+use crate::error::Error;
+
+/// In summary mode, `content` must be equal to the summary of current
+/// transaction rebuilt on chain. The address prefix is taken from the first
+/// line of `content`.
+pub fn verify_summary(content: &str) -> Result<(), Error> {
+    let hrp = content
+        .strip_prefix(SUMMARY_HEADER)
+        .and_then(|rest| rest.get(0..3))
+        .filter(|hrp| *hrp == MAINNET_PREFIX || *hrp == TESTNET_PREFIX)
+        .ok_or(Error::SummaryMismatched)?;
+    if build_summary(hrp)? != content {
+        return Err(Error::SummaryMismatched);
+    }
+    Ok(())
+}
+
+/// Deterministic summary of current transaction: every output with its
+/// capacity, lock address, type script and data length, followed by the fee.
+pub fn build_summary(hrp: &str) -> Result<String, Error> {
+    let mut summary = format!("{}{}\n\n{}\n", SUMMARY_HEADER, hrp, NOSTR_LOCK_WARNING);
+    let mut outputs_capacity = 0u64;
+    for (index, output) in QueryIter::new(load_cell, Source::Output).enumerate() {
+        let capacity: u64 = output.capacity().unpack();
+        outputs_capacity = outputs_capacity.saturating_add(capacity);
+        summary.push_str(&format!(
+            "\nOutput {}: {} CKB\nTo: {}\n",
+            index,
+            format_capacity(capacity),
+            encode_address(hrp, &output.lock())
+        ));
+        if let Some(type_script) = output.type_().to_opt() {
+            summary.push_str(&format!("Type: {}\n", format_script(&type_script)));
+        }
+        let data = load_cell_data(index, Source::Output)?;
+        summary.push_str(&format!("Data: {} bytes\n", data.len()));
+    }
+    let inputs_capacity = QueryIter::new(load_cell_capacity, Source::Input)
+        .fold(0u64, |sum, capacity| sum.saturating_add(capacity));
+    let fee = inputs_capacity.saturating_sub(outputs_capacity);
+    summary.push_str(&format!("\nFee: {} CKB\n", format_capacity(fee)));
+    Ok(summary)
+}
+
+// shannons in CKB with 8 decimals
+fn format_capacity(shannons: u64) -> String {
+    format!("{}.{:08}", shannons / 100_000_000, shannons % 100_000_000)
+}
+
+fn format_script(script: &Script) -> String {
+    let hash_type = match script.hash_type().as_slice()[0] {
+        0 => "data",
+        1 => "type",
+        2 => "data1",
+        4 => "data2",
+        _ => "unknown",
+    };
+    format!(
+        "0x{} {} 0x{}",
+        hex::encode(script.code_hash().as_slice()),
+        hash_type,
+        hex::encode(&script.args().raw_data()[..])
+    )
+}
@@ -1,11 +1,14 @@
-use crate::blake2b::new_blake2b_stat;
 use crate::error::Error;
 use alloc::vec;
+use ckb_nostr_utils::blake2b::new_blake2b_stat;
+use ckb_nostr_utils::since::since_satisfied;
 use ckb_std::ckb_constants::{InputField, Source};
 use ckb_std::ckb_types::bytes::Bytes;
 use ckb_std::ckb_types::prelude::*;
 use ckb_std::debug;
-use ckb_std::high_level::{load_tx_hash, load_witness, load_witness_args};
+use ckb_std::high_level::{
+    load_input_since, load_tx_hash, load_witness, load_witness_args, QueryIter,
+};
 use ckb_std::syscalls::{load_input_by_field, SysError};
 
 pub fn generate_sighash_all() -> Result<[u8; 32], Error> {
@@ -69,3 +72,9 @@ fn calculate_inputs_len() -> Result<usize, Error> {
     }
     Ok(i)
 }
+
+/// Whether `since` of every input in current script group satisfies the
+/// relative `delay`: same flags and no less value.
+pub fn inputs_since_satisfied(delay: u64) -> bool {
+    QueryIter::new(load_input_since, Source::GroupInput).all(|since| since_satisfied(since, delay))
+}
@@ -0,0 +1,2 @@
+// <type id, 32 bytes><nostr lock code hash, 32 bytes>
+pub const SCRIPT_ARGS_LEN: usize = 64;
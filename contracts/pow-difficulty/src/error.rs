@@ -0,0 +1 @@
+pub use ckb_nostr_error::DifficultyError as Error;
@@ -0,0 +1,105 @@
+#![no_std]
+#![no_main]
+
+mod config;
+mod error;
+mod type_id;
+
+use ckb_nostr_utils::difficulty::DifficultyConfig;
+use ckb_nostr_utils::lock_args::LockArgs;
+use ckb_nostr_utils::since::{SINCE_FLAGS_MASK, SINCE_VALUE_MASK};
+use ckb_std::default_alloc;
+ckb_std::entry!(program_entry);
+default_alloc!(4 * 1024, 64 * 1024, 64);
+
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, prelude::*},
+    debug,
+    high_level::{
+        load_cell_data, load_cell_lock, load_header, load_input_since, load_script,
+        load_script_hash, QueryIter,
+    },
+};
+
+use config::SCRIPT_ARGS_LEN;
+use error::Error;
+use type_id::{has_type_id_cell, validate_type_id};
+
+pub fn program_entry() -> i8 {
+    match entry() {
+        Ok(_) => 0,
+        Err(err) => err as i8,
+    }
+}
+
+fn entry() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    if args.len() != SCRIPT_ARGS_LEN {
+        return Err(Error::WrongArgsLength);
+    }
+    validate_type_id(args[0..32].try_into().unwrap())?;
+    if !has_type_id_cell(0, Source::GroupOutput) {
+        return Err(Error::ConfigDestroyed);
+    }
+    let output = DifficultyConfig::parse(&load_cell_data(0, Source::GroupOutput)?)?;
+    if !has_type_id_cell(0, Source::GroupInput) {
+        // creation, no claim recorded yet
+        if !output.claims.is_empty() {
+            return Err(Error::InvalidConfigData);
+        }
+        return Ok(());
+    }
+    let input = DifficultyConfig::parse(&load_cell_data(0, Source::GroupInput)?)?;
+    if output.min_difficulty != input.min_difficulty
+        || output.target_interval != input.target_interval
+        || output.window != input.window
+    {
+        return Err(Error::ConfigChanged);
+    }
+    verify_claim_input(&args[32..64])?;
+    let block_number = claim_block_number()?;
+    debug!("claim recorded at block {}", block_number);
+    if input.record_claim(block_number) != output {
+        return Err(Error::RetargetMismatched);
+    }
+    Ok(())
+}
+
+// every update records a claim: an input locked by nostr lock in dynamic PoW
+// mode reading difficulty from this config cell
+fn verify_claim_input(lock_code_hash: &[u8]) -> Result<(), Error> {
+    let script_hash = load_script_hash()?;
+    let found = QueryIter::new(load_cell_lock, Source::Input).any(|lock| {
+        if lock.code_hash().as_slice() != lock_code_hash {
+            return false;
+        }
+        let args: Bytes = lock.args().unpack();
+        matches!(
+            LockArgs::parse(&args),
+            Ok(LockArgs { difficulty_config: Some(hash), .. }) if hash == script_hash
+        )
+    });
+    if !found {
+        return Err(Error::ClaimNotFound);
+    }
+    Ok(())
+}
+
+// The block number of the claim is the absolute `since` of the config input,
+// which the transaction can't be committed before. It can't be earlier than
+// the block creating the config input, which must be in header deps.
+fn claim_block_number() -> Result<u64, Error> {
+    let since = load_input_since(0, Source::GroupInput)?;
+    if since & SINCE_FLAGS_MASK != 0 {
+        return Err(Error::InvalidClaimSince);
+    }
+    let block_number = since & SINCE_VALUE_MASK;
+    let header = load_header(0, Source::GroupInput)?;
+    let created: u64 = header.raw().number().unpack();
+    if block_number < created || block_number == 0 {
+        return Err(Error::InvalidClaimSince);
+    }
+    Ok(block_number)
+}
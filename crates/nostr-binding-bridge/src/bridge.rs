@@ -0,0 +1,318 @@
+use std::{collections::HashMap, fmt, time::Duration};
+
+use ckb_nostr_utils::event::Event;
+use ckb_types::{
+    packed::{Script, WitnessArgs},
+    prelude::*,
+    H256,
+};
+use nostr::JsonUtil;
+use nostr_binding_relay::{Filter, RelayClient};
+use nostr_binding_sdk::{fee, tag, NostrBindingSdk, NostrLock, ResolvedTx, Rule, ScriptError};
+
+use crate::{error::Error, submit::Submit};
+
+/// Id of the relay subscription of unlock events
+pub const SUBSCRIPTION_ID: &str = "unlock-events";
+
+/// What an unlock event did to the pending transactions
+#[derive(Debug)]
+pub enum Outcome {
+    /// The event signs a nostr lock group of the transaction, other groups
+    /// still wait for their unlock events
+    Signed { tx_hash: H256, witness_index: usize },
+    /// All groups are signed and the node accepted the transaction
+    Submitted { tx_hash: H256 },
+    /// The event matches the transaction but doesn't unlock it, the group
+    /// keeps waiting
+    Rejected {
+        tx_hash: H256,
+        witness_index: usize,
+        error: Error,
+    },
+    /// The node rejected the signed transaction for a rule of the
+    /// transaction rather than its unlock events, it's dropped
+    Failed {
+        tx_hash: H256,
+        error: Error,
+        /// the exit code of a nostr lock or binding script in the rejection
+        script_error: Option<ScriptError>,
+    },
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Signed {
+                tx_hash,
+                witness_index,
+            } => write!(f, "{:#x}: witness {} signed", tx_hash, witness_index),
+            Outcome::Submitted { tx_hash } => write!(f, "{:#x}: submitted", tx_hash),
+            Outcome::Rejected {
+                tx_hash,
+                witness_index,
+                error,
+            } => write!(
+                f,
+                "{:#x}: unlock event of witness {} rejected, {}",
+                tx_hash, witness_index, error
+            ),
+            Outcome::Failed {
+                tx_hash,
+                error,
+                script_error,
+            } => {
+                write!(f, "{:#x}: failed, {}", tx_hash, error)?;
+                match script_error {
+                    Some(script_error) => write!(f, " ({})", script_error),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+struct Pending {
+    rtx: ResolvedTx,
+    groups: Vec<Group>,
+}
+
+struct Group {
+    lock_indexes: Vec<usize>,
+    sighash_all: [u8; 32],
+    signed: bool,
+}
+
+/// Collects the unlock events of pending transactions from a relay and
+/// submits the transactions once every nostr lock group is signed. The fee is
+/// paid from the capacity the transaction reserves, which is checked against
+/// the minimal fee rate when it is added.
+///
+/// The bridge checks the rules of the unlock event, rules 1 to 9 and the tags
+/// they depend on, before taking an event, so an event with a wrong key,
+/// signature or PoW can't get the transaction rejected by the node. The rules
+/// of the transaction, e.g. the recovery delay and binding scripts, are left
+/// to the node.
+///
+/// Unlock events are ephemeral, relays don't store them, so a transaction
+/// must be added before its unlock events are published.
+pub struct Bridge<S> {
+    sdk: NostrBindingSdk,
+    submitter: S,
+    min_fee_rate: u64,
+    pending: HashMap<H256, Pending>,
+    by_sighash: HashMap<[u8; 32], H256>,
+}
+
+impl<S: Submit> Bridge<S> {
+    /// `min_fee_rate` is in shannons per 1000 bytes
+    pub fn new(sdk: NostrBindingSdk, submitter: S, min_fee_rate: u64) -> Self {
+        Self {
+            sdk,
+            submitter,
+            min_fee_rate,
+            pending: HashMap::new(),
+            by_sighash: HashMap::new(),
+        }
+    }
+
+    pub fn submitter(&self) -> &S {
+        &self.submitter
+    }
+
+    /// Hashes of the transactions waiting for unlock events
+    pub fn pending_txs(&self) -> Vec<H256> {
+        self.pending.keys().cloned().collect()
+    }
+
+    /// Wait for the unlock events of a transaction prepared with dummy locks,
+    /// see `NostrLock::prepare_tx_with_fee`. Groups which are signed already
+    /// are left as they are.
+    pub fn add_pending(&mut self, rtx: ResolvedTx) -> Result<H256, Error> {
+        let tx = &rtx.tx;
+        if rtx.inputs.len() != tx.inputs().len() {
+            return Err(Error::InputCellsMismatched {
+                expected: tx.inputs().len(),
+                actual: rtx.inputs.len(),
+            });
+        }
+        let tx_hash: H256 = tx.hash().unpack();
+        if self.pending.contains_key(&tx_hash) {
+            return Err(Error::AlreadyPending(tx_hash));
+        }
+
+        // the witnesses have the length of the signed ones, so does the size
+        let inputs: u64 = rtx
+            .inputs
+            .iter()
+            .map(|(cell, _)| Unpack::<u64>::unpack(&cell.capacity()))
+            .sum();
+        let outputs: u64 = tx
+            .outputs()
+            .into_iter()
+            .map(|cell| Unpack::<u64>::unpack(&cell.capacity()))
+            .sum();
+        let actual = inputs
+            .checked_sub(outputs)
+            .ok_or(Error::OutputsExceedInputs)?;
+        let required = fee::calculate_fee(fee::tx_size(tx), self.min_fee_rate);
+        if actual < required {
+            return Err(Error::FeeTooLow { required, actual });
+        }
+
+        let mut lock_groups: Vec<(Script, Vec<usize>)> = vec![];
+        for (index, (cell, _)) in rtx.inputs.iter().enumerate() {
+            let lock = cell.lock();
+            if !self.sdk.lock.is_nostr_lock(&lock) {
+                continue;
+            }
+            match lock_groups.iter_mut().find(|(script, _)| *script == lock) {
+                Some((_, indexes)) => indexes.push(index),
+                None => lock_groups.push((lock, vec![index])),
+            }
+        }
+        let mut groups = vec![];
+        for (_, lock_indexes) in lock_groups {
+            if !has_dummy_lock(&rtx, lock_indexes[0]) {
+                continue;
+            }
+            let sighash_all = NostrLock::build_sighash_all(tx, &lock_indexes)?;
+            groups.push(Group {
+                lock_indexes,
+                sighash_all,
+                signed: false,
+            });
+        }
+        if groups.is_empty() {
+            return Err(Error::NothingToSign);
+        }
+
+        for group in &groups {
+            self.by_sighash.insert(group.sighash_all, tx_hash.clone());
+        }
+        self.pending
+            .insert(tx_hash.clone(), Pending { rtx, groups });
+        Ok(tx_hash)
+    }
+
+    /// Subscribe to the unlock events of the lock kind
+    pub fn subscribe(&self, client: &mut RelayClient) -> Result<(), Error> {
+        let filter = Filter::new().kind(self.sdk.lock.kind);
+        client.subscribe(SUBSCRIPTION_ID, vec![filter])?;
+        Ok(())
+    }
+
+    /// Handle the next unlock event of the subscription, `None` when no event
+    /// comes in `timeout` or the event is not for a pending transaction
+    pub fn poll(
+        &mut self,
+        client: &mut RelayClient,
+        timeout: Duration,
+    ) -> Result<Option<Outcome>, Error> {
+        match client.next_event(SUBSCRIPTION_ID, timeout) {
+            Ok(event) => Ok(self.handle_event(&event)),
+            Err(nostr_binding_relay::Error::Timeout) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Put the unlock event into the pending transaction whose sighash_all it
+    /// signs, and submit the transaction once all groups are signed. `None`
+    /// when the event is not for a pending transaction.
+    pub fn handle_event(&mut self, event: &Event) -> Option<Outcome> {
+        let mut sighash_all = [0u8; 32];
+        let tag = event.get_tag_content(tag::SIGHASH_ALL.into())?;
+        hex::decode_to_slice(tag, &mut sighash_all).ok()?;
+        let tx_hash = self.by_sighash.get(&sighash_all)?.clone();
+        let pending = self.pending.get_mut(&tx_hash)?;
+        let group = pending
+            .groups
+            .iter_mut()
+            .find(|group| group.sighash_all == sighash_all)?;
+        let witness_index = group.lock_indexes[0];
+        let rejected = |error: Error| {
+            Some(Outcome::Rejected {
+                tx_hash: tx_hash.clone(),
+                witness_index,
+                error,
+            })
+        };
+
+        let filled = serde_json::to_string(event)
+            .map_err(Error::from)
+            .and_then(|json| {
+                nostr::Event::from_json(json).map_err(|err| Error::InvalidEvent(err.to_string()))
+            })
+            .and_then(|event| {
+                NostrLock::fill_in_signed_event(pending.rtx.tx.clone(), witness_index, &event)
+                    .map_err(Error::from)
+            });
+        let tx = match filled {
+            Ok(tx) => tx,
+            Err(err) => return rejected(err),
+        };
+        let rtx = ResolvedTx {
+            tx,
+            ..pending.rtx.clone()
+        };
+        if let Some(diagnostic) = self.sdk.verify_tx(&rtx).into_iter().find(|diagnostic| {
+            diagnostic.witness_index == witness_index && is_checked(diagnostic.rule)
+        }) {
+            return rejected(Error::Rule(diagnostic));
+        }
+        pending.rtx = rtx;
+        group.signed = true;
+        self.by_sighash.remove(&sighash_all);
+        if pending.groups.iter().any(|group| !group.signed) {
+            return Some(Outcome::Signed {
+                tx_hash,
+                witness_index,
+            });
+        }
+
+        let pending = self.pending.remove(&tx_hash).unwrap();
+        Some(match self.submitter.send_transaction(&pending.rtx.tx) {
+            Ok(_) => Outcome::Submitted { tx_hash },
+            Err(error) => Outcome::Failed {
+                script_error: self.sdk.decode_failure(&error.to_string()),
+                tx_hash,
+                error,
+            },
+        })
+    }
+}
+
+// the rules another unlock event may pass, and what they depend on
+fn is_checked(rule: Rule) -> bool {
+    matches!(
+        rule,
+        Rule::LockArgs
+            | Rule::LockWitness
+            | Rule::SighashAll
+            | Rule::UnlockEventId
+            | Rule::KindAndContent
+            | Rule::Chain
+            | Rule::CreatedAtWindow
+            | Rule::Nonce
+            | Rule::TargetDifficulty
+            | Rule::PowDifficulty
+            | Rule::Pubkey
+            | Rule::Delegation
+            | Rule::Session
+            | Rule::Signature
+    )
+}
+
+// the lock of the group witness is zeros of the length of the unlock event
+fn has_dummy_lock(rtx: &ResolvedTx, witness_index: usize) -> bool {
+    let Some(witness) = rtx.tx.witnesses().get(witness_index) else {
+        return false;
+    };
+    let Ok(witness_args) = WitnessArgs::from_slice(&witness.raw_data()) else {
+        return false;
+    };
+    witness_args.lock().to_opt().map_or(false, |lock| {
+        let lock = lock.raw_data();
+        !lock.is_empty() && lock.iter().all(|byte| *byte == 0)
+    })
+}
@@ -0,0 +1,90 @@
+use core::fmt;
+
+use ckb_types::H256;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Error from the relay connection
+    Relay(nostr_binding_relay::Error),
+    /// Error from the SDK, e.g. a malformed witness
+    Sdk(nostr_binding_sdk::Error),
+    /// The node rejected the transaction, or the RPC failed
+    Rpc(String),
+    /// Error from reading or writing pending transaction files
+    Io(std::io::Error),
+    /// Malformed pending transaction file
+    Json(serde_json::Error),
+    /// The cells of the inputs don't match the inputs of the transaction
+    InputCellsMismatched { expected: usize, actual: usize },
+    /// No nostr lock group of the transaction waits for an unlock event
+    NothingToSign,
+    /// The transaction is pending already
+    AlreadyPending(H256),
+    /// The outputs hold more capacity than the inputs
+    OutputsExceedInputs,
+    /// The capacity reserved for the fee doesn't meet the minimal fee rate
+    FeeTooLow { required: u64, actual: u64 },
+    /// The unlock event is not a valid nostr event
+    InvalidEvent(String),
+    /// The unlock event fails a rule of the nostr lock
+    Rule(nostr_binding_sdk::Diagnostic),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Relay(err) => write!(f, "relay: {}", err),
+            Error::Sdk(err) => write!(f, "{}", err),
+            Error::Rpc(reason) => write!(f, "rpc: {}", reason),
+            Error::Io(err) => write!(f, "io: {}", err),
+            Error::Json(err) => write!(f, "json: {}", err),
+            Error::InputCellsMismatched { expected, actual } => write!(
+                f,
+                "{} input cells for a transaction of {} inputs",
+                actual, expected
+            ),
+            Error::NothingToSign => write!(f, "no nostr lock input waits for an unlock event"),
+            Error::AlreadyPending(tx_hash) => write!(f, "transaction {:#x} is pending", tx_hash),
+            Error::OutputsExceedInputs => write!(f, "outputs exceed the capacity of inputs"),
+            Error::FeeTooLow { required, actual } => write!(
+                f,
+                "fee of {} shannons is below the required {} shannons",
+                actual, required
+            ),
+            Error::InvalidEvent(reason) => write!(f, "invalid unlock event: {}", reason),
+            Error::Rule(diagnostic) => write!(f, "{}", diagnostic),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<nostr_binding_relay::Error> for Error {
+    fn from(err: nostr_binding_relay::Error) -> Self {
+        Error::Relay(err)
+    }
+}
+
+impl From<nostr_binding_sdk::Error> for Error {
+    fn from(err: nostr_binding_sdk::Error) -> Self {
+        Error::Sdk(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Self {
+        Error::Rpc(err.to_string())
+    }
+}
@@ -0,0 +1,15 @@
+//! The special relay of the lightpaper: a bridge from nostr to CKB. It is
+//! handed transactions prepared with dummy locks and the fee paid from their
+//! reserved capacity, waits on a relay for the unlock events signing them,
+//! puts the events into the witnesses once they pass the rules of unlock
+//! events, and submits the unlocked transactions to a CKB node.
+
+pub mod bridge;
+pub mod error;
+pub mod pending;
+pub mod submit;
+
+pub use bridge::{Bridge, Outcome};
+pub use error::Error;
+pub use pending::{CellWithData, PendingTx};
+pub use submit::{RpcSubmitter, Submit};
@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use ckb_types::{core::ScriptHashType, H256};
+use clap::{Parser, ValueEnum};
+use nostr_binding_bridge::{Bridge, Error, Outcome, PendingTx, RpcSubmitter};
+use nostr_binding_relay::RelayClient;
+use nostr_binding_sdk::{NostrBindingSdk, SdkConfig};
+
+/// Submit pending transactions once their unlock events show up on a relay
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// URL of the nostr relay the unlock events are published to
+    #[arg(long)]
+    relay: String,
+    /// URL of the CKB node RPC the transactions are submitted to
+    #[arg(long)]
+    rpc: String,
+    /// Directory of pending transaction files, `*.json` files are picked up
+    /// and renamed to `*.submitted` or `*.failed` when done
+    #[arg(long)]
+    pending: PathBuf,
+    /// Minimal fee rate in shannons per 1000 bytes the reserved fee must meet
+    #[arg(long, default_value_t = 1000)]
+    min_fee_rate: u64,
+    #[arg(long, value_enum, default_value_t = Network::Testnet)]
+    network: Network,
+    /// Code hash of the nostr lock script, overriding the one of the network
+    #[arg(long, value_parser = parse_h256)]
+    lock_code_hash: Option<H256>,
+    /// Hash type of the nostr lock script, overriding the one of the network
+    #[arg(long, value_enum)]
+    lock_hash_type: Option<HashType>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Network {
+    Testnet,
+    Mainnet,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HashType {
+    Data,
+    Type,
+    Data1,
+    Data2,
+}
+
+impl From<HashType> for ScriptHashType {
+    fn from(hash_type: HashType) -> Self {
+        match hash_type {
+            HashType::Data => ScriptHashType::Data,
+            HashType::Type => ScriptHashType::Type,
+            HashType::Data1 => ScriptHashType::Data1,
+            HashType::Data2 => ScriptHashType::Data2,
+        }
+    }
+}
+
+fn parse_h256(s: &str) -> Result<H256, String> {
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(s.strip_prefix("0x").unwrap_or(s), &mut hash)
+        .map_err(|err| err.to_string())?;
+    Ok(H256(hash))
+}
+
+const POLL_TIMEOUT: Duration = Duration::from_secs(1);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+fn main() {
+    let cli = Cli::parse();
+    let mut config = match cli.network {
+        Network::Testnet => SdkConfig::testnet(),
+        Network::Mainnet => SdkConfig::mainnet(),
+    };
+    if let Some(code_hash) = cli.lock_code_hash.clone() {
+        config.nostr_lock.code_hash = code_hash;
+    }
+    if let Some(hash_type) = cli.lock_hash_type {
+        config.nostr_lock.hash_type = hash_type.into();
+    }
+    let mut bridge = Bridge::new(
+        NostrBindingSdk::new(config),
+        RpcSubmitter::new(&cli.rpc),
+        cli.min_fee_rate,
+    );
+
+    // pending files by the hash of their transactions
+    let mut files = HashMap::new();
+    loop {
+        if let Err(err) = run(&cli, &mut bridge, &mut files) {
+            eprintln!("error: {}", err);
+        }
+        thread::sleep(RECONNECT_INTERVAL);
+    }
+}
+
+/// Serve until the relay connection fails
+fn run(
+    cli: &Cli,
+    bridge: &mut Bridge<RpcSubmitter>,
+    files: &mut HashMap<H256, PathBuf>,
+) -> Result<(), Error> {
+    let mut client = RelayClient::connect(&cli.relay)?;
+    bridge.subscribe(&mut client)?;
+    println!("subscribed to {}", cli.relay);
+    loop {
+        load_pending(&cli.pending, bridge, files)?;
+        let Some(outcome) = bridge.poll(&mut client, POLL_TIMEOUT)? else {
+            continue;
+        };
+        println!("{}", outcome);
+        let (tx_hash, extension) = match &outcome {
+            Outcome::Submitted { tx_hash } => (tx_hash, "submitted"),
+            Outcome::Failed { tx_hash, .. } => (tx_hash, "failed"),
+            _ => continue,
+        };
+        if let Some(path) = files.remove(tx_hash) {
+            fs::rename(&path, path.with_extension(extension))?;
+        }
+    }
+}
+
+/// Add the transactions of new files in `dir` to the bridge
+fn load_pending(
+    dir: &Path,
+    bridge: &mut Bridge<RpcSubmitter>,
+    files: &mut HashMap<H256, PathBuf>,
+) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "json")
+            || files.values().any(|file| *file == path)
+        {
+            continue;
+        }
+        match PendingTx::load(&path).and_then(|pending| bridge.add_pending(pending.into())) {
+            Ok(tx_hash) => {
+                println!("{:#x}: waiting for unlock events", tx_hash);
+                files.insert(tx_hash, path);
+            }
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                fs::rename(&path, path.with_extension("failed"))?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::{fs, path::Path};
+
+use ckb_jsonrpc_types as json_types;
+use ckb_types::{bytes::Bytes, core::HeaderView, packed, prelude::*};
+use nostr_binding_sdk::ResolvedTx;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// A prepared transaction with everything its scripts load, the file format
+/// pending transactions are handed to the bridge in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTx {
+    pub transaction: json_types::Transaction,
+    /// cells spent by the inputs, in the same order
+    pub inputs: Vec<CellWithData>,
+    /// cells of the cell deps, dep groups expanded
+    #[serde(default)]
+    pub cell_deps: Vec<CellWithData>,
+    #[serde(default)]
+    pub header_deps: Vec<json_types::HeaderView>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellWithData {
+    pub output: json_types::CellOutput,
+    pub data: json_types::JsonBytes,
+}
+
+impl PendingTx {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl From<&ResolvedTx> for PendingTx {
+    fn from(rtx: &ResolvedTx) -> Self {
+        let cells = |cells: &[(packed::CellOutput, Bytes)]| {
+            cells
+                .iter()
+                .map(|(output, data)| CellWithData {
+                    output: output.clone().into(),
+                    data: json_types::JsonBytes::from_bytes(data.clone()),
+                })
+                .collect()
+        };
+        Self {
+            transaction: rtx.tx.data().into(),
+            inputs: cells(&rtx.inputs),
+            cell_deps: cells(&rtx.cell_deps),
+            header_deps: rtx.header_deps.iter().cloned().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<PendingTx> for ResolvedTx {
+    fn from(pending: PendingTx) -> Self {
+        let cells = |cells: Vec<CellWithData>| {
+            cells
+                .into_iter()
+                .map(|cell| (cell.output.into(), cell.data.into_bytes()))
+                .collect()
+        };
+        Self {
+            tx: packed::Transaction::from(pending.transaction).into_view(),
+            inputs: cells(pending.inputs),
+            cell_deps: cells(pending.cell_deps),
+            header_deps: pending
+                .header_deps
+                .into_iter()
+                .map(HeaderView::from)
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use ckb_jsonrpc_types as json_types;
+use ckb_types::{core::TransactionView, H256};
+use serde_json::{json, Value};
+
+use crate::error::Error;
+
+/// Where unlocked transactions are submitted to
+pub trait Submit {
+    /// Submit the transaction, returning its hash. A rejection is an
+    /// `Error::Rpc` with the message of the node.
+    fn send_transaction(&self, tx: &TransactionView) -> Result<H256, Error>;
+}
+
+/// The `send_transaction` RPC of a CKB node
+pub struct RpcSubmitter {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl RpcSubmitter {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+}
+
+impl Submit for RpcSubmitter {
+    fn send_transaction(&self, tx: &TransactionView) -> Result<H256, Error> {
+        let tx: json_types::Transaction = tx.data().into();
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({
+                "id": 1,
+                "jsonrpc": "2.0",
+                "method": "send_transaction",
+                "params": [tx, "passthrough"],
+            }))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            // the message carries the exit code and the script of a rejection
+            let message = error["message"].as_str().unwrap_or_default();
+            return Err(Error::Rpc(format!("{} {}", message, error["data"])));
+        }
+        serde_json::from_value(response["result"].clone())
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+}
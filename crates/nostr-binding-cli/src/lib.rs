@@ -0,0 +1,655 @@
+//! Offline command line tool building and signing nostr binding transactions
+//! in [`TxFile`] JSON files. It never connects to a node.
+
+pub mod tx_file;
+
+use std::{path::PathBuf, str::FromStr};
+
+use ckb_jsonrpc_types as json_types;
+use ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionView},
+    packed,
+    prelude::*,
+    H256,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use nostr::{
+    nips::nip49::EncryptedSecretKey, Event, EventBuilder, FromBech32, JsonUtil, Keys, Kind,
+    PublicKey, Tag, Timestamp,
+};
+use nostr_binding_sdk::{
+    fee,
+    lock_args::{LockArgs, FLAG_KEY_AND_POW},
+    tag, NostrBinding, NostrBindingSdk, NostrLock, SdkConfig,
+};
+use serde_json::json;
+
+pub use tx_file::TxFile;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Parser)]
+#[command(name = "nostr-binding", version, about)]
+pub struct Cli {
+    #[arg(long, value_enum, default_value_t = Network::Testnet, global = true)]
+    pub network: Network,
+    /// Code hash of the nostr lock script, overriding the one of the network
+    #[arg(long, value_parser = parse_h256, global = true)]
+    pub lock_code_hash: Option<H256>,
+    /// Hash type of the nostr lock script, overriding the one of the network
+    #[arg(long, value_enum, global = true)]
+    pub lock_hash_type: Option<HashType>,
+    /// Code hash of the nostr binding script, overriding the one of the network
+    #[arg(long, value_parser = parse_h256, global = true)]
+    pub binding_code_hash: Option<H256>,
+    /// Hash type of the nostr binding script, overriding the one of the network
+    #[arg(long, value_enum, global = true)]
+    pub binding_hash_type: Option<HashType>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Network {
+    Testnet,
+    Mainnet,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum HashType {
+    Data,
+    Type,
+    Data1,
+    Data2,
+}
+
+impl From<HashType> for ScriptHashType {
+    fn from(hash_type: HashType) -> Self {
+        match hash_type {
+            HashType::Data => ScriptHashType::Data,
+            HashType::Type => ScriptHashType::Type,
+            HashType::Data1 => ScriptHashType::Data1,
+            HashType::Data2 => ScriptHashType::Data2,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Mint a binding cell, whose global unique id is derived from the chosen input
+    Mint(MintArgs),
+    /// Fill in the dummy lock of nostr lock inputs
+    Prepare(PrepareArgs),
+    /// Print sighash_all of a prepared transaction
+    Sighash(TxArgs),
+    /// Sign nostr lock inputs with an nsec or ncryptsec
+    Sign(SignArgs),
+    /// Mine a PoW unlock event for nostr lock inputs
+    Mine(MineArgs),
+    /// Print unlock and binding events of the transaction
+    Inspect(TxArgs),
+    /// Check unlock events and binding events of the transaction
+    Verify(TxArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct TxArgs {
+    /// Transaction JSON file
+    #[arg(long)]
+    pub tx: PathBuf,
+    /// Index of a nostr lock input, found from the input cells in the file
+    /// when not set
+    #[arg(long = "lock-index")]
+    pub lock_indexes: Vec<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct KeyArgs {
+    /// nsec, ncryptsec or hex secret key
+    #[arg(long, env = "NOSTR_SECRET_KEY", hide_env_values = true)]
+    pub key: Option<String>,
+    /// Password of the ncryptsec
+    #[arg(long, env = "NOSTR_KEY_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ContentArgs {
+    /// Content of the unlock event, the default one of nostr lock when not set
+    #[arg(long, conflicts_with = "summary")]
+    pub content: Option<String>,
+    /// Kind of the unlock event, for locks with a content config
+    #[arg(long)]
+    pub kind: Option<u16>,
+    /// Use the summary of the transaction as content, for locks with the
+    /// summary flag
+    #[arg(long)]
+    pub summary: bool,
+    /// Genesis hash of the chain the unlock event is bound to
+    #[arg(long, value_parser = parse_h256)]
+    pub chain: Option<H256>,
+}
+
+#[derive(Debug, Args)]
+pub struct PrepareArgs {
+    #[command(flatten)]
+    pub tx: TxArgs,
+    #[command(flatten)]
+    pub content: ContentArgs,
+    /// PoW difficulty of the unlock event to be mined
+    #[arg(long)]
+    pub pow: Option<u8>,
+    /// Pay the fee of this rate, in shannons per 1000 bytes
+    #[arg(long, requires = "change")]
+    pub fee_rate: Option<u64>,
+    /// Index of the output paying the fee, which holds all the capacity left
+    /// for the fee
+    #[arg(long, requires = "fee_rate")]
+    pub change: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct SignArgs {
+    #[command(flatten)]
+    pub tx: TxArgs,
+    #[command(flatten)]
+    pub content: ContentArgs,
+    #[command(flatten)]
+    pub key: KeyArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct MineArgs {
+    #[command(flatten)]
+    pub tx: TxArgs,
+    #[command(flatten)]
+    pub content: ContentArgs,
+    /// Key signing the mined event, required by locks with the key and PoW
+    /// flag. A random key is used when not set.
+    #[command(flatten)]
+    pub key: KeyArgs,
+    /// PoW difficulty of the unlock event
+    #[arg(long)]
+    pub pow: u8,
+}
+
+#[derive(Debug, Args)]
+pub struct MintArgs {
+    /// Transaction JSON file, created when it doesn't exist
+    #[arg(long)]
+    pub tx: PathBuf,
+    /// Out point `<tx hash>:<index>` of the input to derive the global unique
+    /// id from, put as the first input
+    #[arg(long, value_parser = parse_out_point)]
+    pub input: packed::OutPoint,
+    /// The cell spent by the input as JSON, required when input cells are
+    /// listed in the file
+    #[arg(long)]
+    pub input_cell: Option<String>,
+    #[command(flatten)]
+    pub key: KeyArgs,
+    /// Kind of the bound event
+    #[arg(long, default_value_t = 1)]
+    pub kind: u16,
+    /// Content of the bound event
+    #[arg(long, default_value = "")]
+    pub content: String,
+    /// Tag of the bound event as comma separated values, e.g. `t,nostr`
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// npub or hex pubkey whose nostr lock owns the binding cell, the signer
+    /// by default
+    #[arg(long, conflicts_with = "lock")]
+    pub owner: Option<String>,
+    /// Lock script of the binding cell as JSON
+    #[arg(long)]
+    pub lock: Option<String>,
+    /// Flags byte of the binding script args
+    #[arg(long)]
+    pub flags: Option<u8>,
+    /// Data of the binding cell in hex
+    #[arg(long, value_parser = parse_hex)]
+    pub data: Option<Bytes>,
+}
+
+/// Run the command, returning what to print
+pub fn run(cli: Cli) -> Result<String> {
+    let mut sdk = NostrBindingSdk::new(match cli.network {
+        Network::Testnet => SdkConfig::testnet(),
+        Network::Mainnet => SdkConfig::mainnet(),
+    });
+    if let Some(code_hash) = cli.lock_code_hash {
+        sdk.lock.script_config.code_hash = code_hash;
+    }
+    if let Some(hash_type) = cli.lock_hash_type {
+        sdk.lock.script_config.hash_type = hash_type.into();
+    }
+    if let Some(code_hash) = cli.binding_code_hash {
+        sdk.binding.script_config.code_hash = code_hash;
+    }
+    if let Some(hash_type) = cli.binding_hash_type {
+        sdk.binding.script_config.hash_type = hash_type.into();
+    }
+    match cli.command {
+        Command::Mint(args) => mint(&sdk, args),
+        Command::Prepare(args) => prepare(&mut sdk.lock, args),
+        Command::Sighash(args) => sighash(&sdk.lock, args),
+        Command::Sign(args) => sign(&mut sdk.lock, args),
+        Command::Mine(args) => mine(&mut sdk.lock, args),
+        Command::Inspect(args) => inspect(&sdk, args),
+        Command::Verify(args) => verify(&sdk, args),
+    }
+}
+
+fn mint(sdk: &NostrBindingSdk, args: MintArgs) -> Result<String> {
+    let mut file = TxFile::load_or_default(&args.tx)?;
+    let keys = parse_keys(&args.key)?.ok_or("--key is required")?;
+
+    // the global unique id is derived from the first input
+    let input = packed::CellInput::new(args.input.clone(), 0);
+    let mut tx = file.tx();
+    if tx.inputs().get(0).map(|first| first.previous_output()) != Some(args.input) {
+        let mut inputs: Vec<packed::CellInput> = tx.inputs().into_iter().collect();
+        inputs.insert(0, input.clone());
+        tx = tx.as_advanced_builder().set_inputs(inputs).build();
+        if !file.input_cells.is_empty() {
+            let cell = args
+                .input_cell
+                .ok_or("input cells are listed in the file, --input-cell is required")?;
+            let cell: json_types::CellOutput = serde_json::from_str(&cell)?;
+            file.input_cells.insert(0, cell);
+        }
+    }
+    let first_input = tx.inputs().get(0).unwrap();
+
+    let output_index = tx.outputs().len();
+    let global_unique_id = NostrBinding::build_global_unique_id(&first_input, output_index as u64);
+    let tags = args
+        .tags
+        .iter()
+        .map(|tag| Tag::parse(&tag.split(',').collect::<Vec<_>>()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let event = sdk
+        .binding
+        .finalize_event_to_bind(
+            global_unique_id,
+            EventBuilder::new(Kind::from(args.kind), &args.content, tags),
+        )
+        .custom_created_at(Timestamp::now())
+        .to_event(&keys)?;
+
+    let lock = match (args.lock, args.owner) {
+        (Some(lock), _) => serde_json::from_str::<json_types::Script>(&lock)?.into(),
+        (None, Some(owner)) => sdk.lock.build_script(&PublicKey::parse(owner)?),
+        (None, None) => sdk.lock.build_script(&keys.public_key()),
+    };
+    let (output, data) = sdk.binding.build_binding_cell(
+        event.id().to_bytes(),
+        global_unique_id,
+        lock,
+        args.data.unwrap_or_default(),
+        args.flags,
+    );
+    let tx = tx
+        .as_advanced_builder()
+        .output(output)
+        .output_data(data.pack())
+        .cell_dep(sdk.binding.script_config.cell_dep())
+        .build();
+    file.set_tx(tx);
+
+    // the bound event is in the witness of the binding cell
+    let witness_args = file
+        .witness_args(output_index)?
+        .as_builder()
+        .output_type(Some(Bytes::from(event.as_json())).pack())
+        .build();
+    file.set_witness_args(output_index, witness_args);
+    file.save(&args.tx)?;
+    Ok(format!(
+        "minted output {}\nevent id: {}\nglobal unique id: {}",
+        output_index,
+        event.id().to_hex(),
+        hex::encode(global_unique_id)
+    ))
+}
+
+fn prepare(lock: &mut NostrLock, args: PrepareArgs) -> Result<String> {
+    let mut file = TxFile::load(&args.tx.tx)?;
+    let lock_indexes = lock_indexes(lock, &file, &args.tx)?;
+    lock.pow_difficulty = args.pow;
+    let content = content(lock, &file, &args.content)?;
+    let mut output = format!(
+        "witness {} is filled with a dummy lock of {} bytes",
+        lock_indexes[0],
+        lock.unlock_event_len(&content)
+    );
+    let tx = match (args.fee_rate, args.change) {
+        (Some(fee_rate), Some(change)) => {
+            let cells = file.input_cells();
+            let (tx, _) =
+                lock.prepare_tx_with_fee(file.tx(), &lock_indexes, change, fee_rate, |tx| {
+                    match &cells {
+                        Some(cells) if args.content.summary => lock.build_tx_summary(tx, cells),
+                        _ => Ok(content.clone()),
+                    }
+                })?;
+            let capacity = |tx: &TransactionView| -> u64 {
+                tx.output(change).map_or(0, |cell| cell.capacity().unpack())
+            };
+            output += &format!(
+                "\npaid a fee of {} shannons by output {} for {} bytes",
+                capacity(&file.tx()) - capacity(&tx),
+                change,
+                fee::tx_size(&tx)
+            );
+            tx
+        }
+        _ => lock.prepare_tx(file.tx(), &lock_indexes, &content)?,
+    };
+    file.set_tx(tx);
+    file.save(&args.tx.tx)?;
+    Ok(output)
+}
+
+fn sighash(lock: &NostrLock, args: TxArgs) -> Result<String> {
+    let file = TxFile::load(&args.tx)?;
+    let lock_indexes = lock_indexes(lock, &file, &args)?;
+    let sighash_all = NostrLock::build_sighash_all(&file.tx(), &lock_indexes)?;
+    Ok(hex::encode(sighash_all))
+}
+
+fn sign(lock: &mut NostrLock, args: SignArgs) -> Result<String> {
+    let mut file = TxFile::load(&args.tx.tx)?;
+    let lock_indexes = lock_indexes(lock, &file, &args.tx)?;
+    let keys = parse_keys(&args.key)?.ok_or("--key is required")?;
+    let content = content(lock, &file, &args.content)?;
+    let tx = lock.sign_tx(file.tx(), &lock_indexes, &content, |event| {
+        Ok(event.to_event(&keys)?)
+    })?;
+    file.set_tx(tx);
+    file.save(&args.tx.tx)?;
+    Ok(format!("signed witness {}", lock_indexes[0]))
+}
+
+fn mine(lock: &mut NostrLock, args: MineArgs) -> Result<String> {
+    let mut file = TxFile::load(&args.tx.tx)?;
+    let lock_indexes = lock_indexes(lock, &file, &args.tx)?;
+    let keys = parse_keys(&args.key)?.unwrap_or_else(Keys::generate);
+    lock.pow_difficulty = Some(args.pow);
+    let content = content(lock, &file, &args.content)?;
+    let tx = lock.mine_tx(file.tx(), &lock_indexes, &content, &keys)?;
+    file.set_tx(tx);
+    file.save(&args.tx.tx)?;
+    Ok(format!(
+        "mined witness {} with PoW difficulty {}",
+        lock_indexes[0], args.pow
+    ))
+}
+
+fn inspect(sdk: &NostrBindingSdk, args: TxArgs) -> Result<String> {
+    let file = TxFile::load(&args.tx)?;
+    let tx = file.tx();
+    let mut unlock_events = vec![];
+    for index in 0..tx.witnesses().len() {
+        let Ok(witness_args) = file.witness_args(index) else {
+            continue;
+        };
+        if let Some(event) = NostrLock::parse_unlock_event_from_witness_args(&witness_args) {
+            unlock_events.push(json!({ "witness": index, "event": event }));
+        }
+    }
+    let mut bindings = vec![];
+    for (index, output) in tx.outputs().into_iter().enumerate() {
+        let Some(type_script) = output.type_().to_opt() else {
+            continue;
+        };
+        if !sdk.binding.is_binding_type(&type_script) {
+            continue;
+        }
+        let type_args = type_script.args().raw_data();
+        let event = file.witness_args(index).ok().and_then(|witness_args| {
+            NostrBinding::parse_binding_event_from_witness_args(&witness_args)
+        });
+        bindings.push(json!({
+            "output": index,
+            "event_id": hex::encode(type_args.get(..32).unwrap_or_default()),
+            "global_unique_id": hex::encode(type_args.get(32..64).unwrap_or_default()),
+            "flags": type_args.get(64),
+            "event": event,
+        }));
+    }
+    let report = json!({
+        "tx_hash": format!("0x{}", hex::encode(tx.hash().raw_data())),
+        "lock_indexes": lock_indexes(&sdk.lock, &file, &args).ok(),
+        "unlock_events": unlock_events,
+        "bindings": bindings,
+    });
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+fn verify(sdk: &NostrBindingSdk, args: TxArgs) -> Result<String> {
+    let file = TxFile::load(&args.tx)?;
+    let tx = file.tx();
+    let mut errors = vec![];
+    let mut report = vec![];
+
+    if let Ok(lock_indexes) = lock_indexes(&sdk.lock, &file, &args) {
+        let index = lock_indexes[0];
+        let witness_args = file.witness_args(index)?;
+        match NostrLock::parse_unlock_event_from_witness_args(&witness_args) {
+            None => errors.push(format!("witness {}: no unlock event", index)),
+            Some(event) => {
+                verify_unlock_event(&file, &lock_indexes, &witness_args, &event, &mut errors)?;
+                report.push(format!("witness {}: unlock event {}", index, event.id()));
+            }
+        }
+    }
+
+    let input_types: Vec<packed::Script> = file
+        .input_cells()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|cell| cell.type_().to_opt())
+        .collect();
+    for (index, output) in tx.outputs().into_iter().enumerate() {
+        let Some(type_script) = output.type_().to_opt() else {
+            continue;
+        };
+        // binding cells transferred from inputs carry no event
+        if !sdk.binding.is_binding_type(&type_script) || input_types.contains(&type_script) {
+            continue;
+        }
+        let type_args = type_script.args().raw_data();
+        let Some(event) = file.witness_args(index).ok().and_then(|witness_args| {
+            NostrBinding::parse_binding_event_from_witness_args(&witness_args)
+        }) else {
+            errors.push(format!("output {}: no bound event in witness", index));
+            continue;
+        };
+        verify_binding_event(&tx, index, &type_args, &event, &mut errors);
+        report.push(format!("output {}: bound event {}", index, event.id()));
+    }
+
+    if errors.is_empty() {
+        report.push("ok".into());
+        Ok(report.join("\n"))
+    } else {
+        Err(errors.join("\n").into())
+    }
+}
+
+fn verify_unlock_event(
+    file: &TxFile,
+    lock_indexes: &[usize],
+    witness_args: &packed::WitnessArgs,
+    event: &Event,
+    errors: &mut Vec<String>,
+) -> Result<()> {
+    let index = lock_indexes[0];
+    if event.verify().is_err() {
+        errors.push(format!("witness {}: invalid event id or signature", index));
+    }
+
+    // sighash_all is signed with the lock of zeros
+    let lock_len = witness_args.lock().to_opt().map_or(0, |lock| lock.len());
+    let dummy_witness = witness_args
+        .clone()
+        .as_builder()
+        .lock(Some(Bytes::from(vec![0u8; lock_len])).pack())
+        .build();
+    let mut witnesses: Vec<packed::Bytes> = file.tx().witnesses().into_iter().collect();
+    witnesses[index] = dummy_witness.as_bytes().pack();
+    let dummy_tx = file
+        .tx()
+        .as_advanced_builder()
+        .set_witnesses(witnesses)
+        .build();
+    let sighash_all = hex::encode(NostrLock::build_sighash_all(&dummy_tx, lock_indexes)?);
+    if tag_value(event, tag::SIGHASH_ALL) != Some(sighash_all.clone()) {
+        errors.push(format!(
+            "witness {}: ckb_sighash_all should be {}",
+            index, sighash_all
+        ));
+    }
+
+    // the owner or PoW can only be checked with the lock of the input
+    let Some(cells) = file.input_cells() else {
+        return Ok(());
+    };
+    let args = LockArgs::parse(&cells[index].lock().args().raw_data())
+        .map_err(|_| format!("input {}: invalid nostr lock args", index))?;
+    if args.pow_difficulty > 0 && !event.id().check_pow(args.pow_difficulty) {
+        errors.push(format!(
+            "witness {}: event id doesn't meet PoW difficulty {}",
+            index, args.pow_difficulty
+        ));
+    }
+    let delegated =
+        tag_value(event, tag::DELEGATION).is_some() || tag_value(event, tag::SESSION).is_some();
+    let signed_by_owner = args.pow_difficulty == 0 || args.flags & FLAG_KEY_AND_POW != 0;
+    if signed_by_owner
+        && !delegated
+        && NostrLock::build_pubkey_hash(&event.author()) != args.pubkey_hash
+    {
+        errors.push(format!(
+            "witness {}: event is not signed by the owner of the lock",
+            index
+        ));
+    }
+    Ok(())
+}
+
+fn verify_binding_event(
+    tx: &ckb_types::core::TransactionView,
+    index: usize,
+    type_args: &[u8],
+    event: &Event,
+    errors: &mut Vec<String>,
+) {
+    if type_args.len() < 64 {
+        errors.push(format!("output {}: invalid binding args", index));
+        return;
+    }
+    if event.verify().is_err() {
+        errors.push(format!("output {}: invalid event id or signature", index));
+    }
+    if event.id().as_bytes() != &type_args[..32] {
+        errors.push(format!(
+            "output {}: event id should be {}",
+            index,
+            hex::encode(&type_args[..32])
+        ));
+    }
+    let first_input = tx.inputs().get(0).unwrap_or_default();
+    let global_unique_id = hex::encode(NostrBinding::build_global_unique_id(
+        &first_input,
+        index as u64,
+    ));
+    if hex::encode(&type_args[32..64]) != global_unique_id {
+        errors.push(format!(
+            "output {}: global unique id should be {}",
+            index, global_unique_id
+        ));
+    }
+    if tag_value(event, tag::GLOBAL_UNIQUE_ID) != Some(global_unique_id.clone()) {
+        errors.push(format!(
+            "output {}: ckb_global_unique_id tag should be {}",
+            index, global_unique_id
+        ));
+    }
+}
+
+fn lock_indexes(lock: &NostrLock, file: &TxFile, args: &TxArgs) -> Result<Vec<usize>> {
+    let lock_indexes = if args.lock_indexes.is_empty() {
+        let cells = file
+            .input_cells()
+            .ok_or("input cells are not listed in the file, pass --lock-index")?;
+        lock.get_lock_indexes(&cells)
+    } else {
+        args.lock_indexes.clone()
+    };
+    if lock_indexes.is_empty() {
+        return Err("there is no nostr lock input".into());
+    }
+    Ok(lock_indexes)
+}
+
+fn content(lock: &mut NostrLock, file: &TxFile, args: &ContentArgs) -> Result<String> {
+    if let Some(kind) = args.kind {
+        lock.kind = kind;
+    }
+    if let Some(chain) = &args.chain {
+        lock.bind_chain(chain.0);
+    }
+    if args.summary {
+        let cells = file
+            .input_cells()
+            .ok_or("input cells are required to build the summary")?;
+        return Ok(lock.build_tx_summary(&file.tx(), &cells)?);
+    }
+    Ok(args.content.clone().unwrap_or_else(|| lock.content.clone()))
+}
+
+fn tag_value(event: &Event, name: &str) -> Option<String> {
+    event
+        .iter_tags()
+        .map(|tag| tag.as_vec())
+        .find(|tag| tag.len() > 1 && tag[0] == name)
+        .map(|tag| tag[1].clone())
+}
+
+/// Keys from an nsec, ncryptsec or hex secret key
+pub fn parse_keys(args: &KeyArgs) -> Result<Option<Keys>> {
+    let Some(key) = &args.key else {
+        return Ok(None);
+    };
+    if key.starts_with("ncryptsec") {
+        let password = args
+            .password
+            .as_ref()
+            .ok_or("--password is required to decrypt the ncryptsec")?;
+        let secret_key = EncryptedSecretKey::from_bech32(key)?.to_secret_key(password)?;
+        return Ok(Some(Keys::new(secret_key)));
+    }
+    Ok(Some(Keys::parse(key)?))
+}
+
+fn parse_h256(s: &str) -> std::result::Result<H256, String> {
+    H256::from_str(s.trim_start_matches("0x")).map_err(|e| e.to_string())
+}
+
+fn parse_hex(s: &str) -> std::result::Result<Bytes, String> {
+    hex::decode(s.trim_start_matches("0x"))
+        .map(Bytes::from)
+        .map_err(|e| e.to_string())
+}
+
+fn parse_out_point(s: &str) -> std::result::Result<packed::OutPoint, String> {
+    let (tx_hash, index) = s
+        .split_once(':')
+        .ok_or("out point should be <tx hash>:<index>")?;
+    let index: u32 = index.parse().map_err(|_| "invalid out point index")?;
+    Ok(packed::OutPoint::new(parse_h256(tx_hash)?.pack(), index))
+}
@@ -0,0 +1,12 @@
+use clap::Parser;
+use nostr_binding_cli::{run, Cli};
+
+fn main() {
+    match run(Cli::parse()) {
+        Ok(output) => println!("{}", output),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::{fs, path::Path};
+
+use ckb_jsonrpc_types as json_types;
+use ckb_types::{core::TransactionView, packed, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// A transaction and the cells spent by its inputs, so it can be signed and
+/// verified without a node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TxFile {
+    pub transaction: json_types::Transaction,
+    /// cells spent by the inputs, in the same order. Optional, required to
+    /// find nostr lock inputs and to build transaction summaries.
+    #[serde(default)]
+    pub input_cells: Vec<json_types::CellOutput>,
+}
+
+impl TxFile {
+    pub fn new(tx: &TransactionView, input_cells: &[packed::CellOutput]) -> Self {
+        Self {
+            transaction: tx.data().into(),
+            input_cells: input_cells.iter().map(|cell| cell.clone().into()).collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Load the file, or start a new transaction when it doesn't exist
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn tx(&self) -> TransactionView {
+        packed::Transaction::from(self.transaction.clone()).into_view()
+    }
+
+    pub fn set_tx(&mut self, tx: TransactionView) {
+        self.transaction = tx.data().into();
+    }
+
+    /// Input cells, when all of them are listed
+    pub fn input_cells(&self) -> Option<Vec<packed::CellOutput>> {
+        if self.input_cells.len() != self.transaction.inputs.len() {
+            return None;
+        }
+        Some(
+            self.input_cells
+                .iter()
+                .map(|cell| cell.clone().into())
+                .collect(),
+        )
+    }
+
+    /// Witness `index` as `WitnessArgs`, empty when it doesn't exist
+    pub fn witness_args(&self, index: usize) -> Result<packed::WitnessArgs> {
+        match self.transaction.witnesses.get(index) {
+            Some(witness) if !witness.is_empty() => {
+                packed::WitnessArgs::from_slice(witness.as_bytes())
+                    .map_err(|_| format!("witness {} is not a WitnessArgs", index).into())
+            }
+            _ => Ok(packed::WitnessArgs::default()),
+        }
+    }
+
+    pub fn set_witness_args(&mut self, index: usize, witness_args: packed::WitnessArgs) {
+        let witnesses = &mut self.transaction.witnesses;
+        if index >= witnesses.len() {
+            witnesses.resize(index + 1, Default::default());
+        }
+        witnesses[index] = json_types::JsonBytes::from_bytes(witness_args.as_bytes());
+    }
+}
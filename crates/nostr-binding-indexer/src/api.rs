@@ -0,0 +1,103 @@
+//! A small read-only JSON API over HTTP:
+//!
+//! - `GET /tip`: `{"block_number", "block_hash"}` of the latest indexed block
+//! - `GET /events/<event id>`: the [`Binding`](crate::store::Binding) of the
+//!   event
+//! - `GET /locks/<lock hash>/events`: the bindings of the live cells of the
+//!   lock
+//!
+//! Unknown events are `404`, malformed ids and hashes are `400`.
+
+use std::{net::SocketAddr, sync::Mutex};
+
+use ckb_types::H256;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::{error::Error, indexer::Indexer, source::BlockSource};
+
+pub struct Api {
+    server: Server,
+}
+
+impl Api {
+    /// Listen on `addr`, e.g. `127.0.0.1:0` for a free port
+    pub fn bind(addr: &str) -> Result<Self, Error> {
+        let server = Server::http(addr)
+            .map_err(|err| Error::Io(std::io::Error::new(std::io::ErrorKind::Other, err)))?;
+        Ok(Self { server })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Serve requests until the server fails, reading the indexer which is
+    /// synced elsewhere
+    pub fn serve<S: BlockSource>(&self, indexer: &Mutex<Indexer<S>>) {
+        for request in self.server.incoming_requests() {
+            let (status, body) = if *request.method() == Method::Get {
+                handle(&indexer.lock().unwrap(), request.url())
+            } else {
+                (405, json!({ "error": "only GET is supported" }))
+            };
+            let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+            let response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(content_type);
+            // the client may have gone away
+            let _ = request.respond(response);
+        }
+    }
+}
+
+/// Status code and JSON body of a GET of `path`
+pub fn handle<S: BlockSource>(indexer: &Indexer<S>, path: &str) -> (u16, Value) {
+    let segments: Vec<&str> = path
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let store = indexer.store();
+    match segments.as_slice() {
+        ["tip"] => match indexer.tip() {
+            Some((number, hash)) => (200, json!({ "block_number": number, "block_hash": hash })),
+            None => (200, Value::Null),
+        },
+        ["events", event_id] => {
+            let Some(event_id) = parse_hash(event_id) else {
+                return bad_request("invalid event id");
+            };
+            match store.binding(&hex::encode(event_id)) {
+                Some(binding) => (200, to_json(&binding)),
+                None => (404, json!({ "error": "event not found" })),
+            }
+        }
+        ["locks", lock_hash, "events"] => {
+            let Some(lock_hash) = parse_hash(lock_hash) else {
+                return bad_request("invalid lock hash");
+            };
+            let bindings = store.bindings_by_lock(&H256(lock_hash));
+            (200, to_json(&bindings))
+        }
+        _ => (404, json!({ "error": "not found" })),
+    }
+}
+
+fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> Value {
+    serde_json::to_value(value).expect("bindings serialize")
+}
+
+fn bad_request(message: &str) -> (u16, Value) {
+    (400, json!({ "error": message }))
+}
+
+// 32 bytes in hex, with or without 0x
+fn parse_hash(hash: &str) -> Option<[u8; 32]> {
+    let hash = hash.strip_prefix("0x").unwrap_or(hash);
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(hash, &mut bytes).ok()?;
+    Some(bytes)
+}
@@ -0,0 +1,53 @@
+use core::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Error from the CKB RPC, either the transport or the JSON-RPC response
+    Rpc(String),
+    /// Error from reading a block file or serving the API
+    Io(std::io::Error),
+    /// Malformed block file
+    Json(serde_json::Error),
+    /// The source has no block of the number below its tip
+    BlockNotFound(u64),
+    /// The chain forked before the oldest block which can be rolled back
+    ReorgTooDeep(u64),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Rpc(reason) => write!(f, "rpc: {}", reason),
+            Error::Io(err) => write!(f, "io: {}", err),
+            Error::Json(err) => write!(f, "json: {}", err),
+            Error::BlockNotFound(number) => write!(f, "block {} not found", number),
+            Error::ReorgTooDeep(number) => {
+                write!(
+                    f,
+                    "chain forked before block {}, reindex from scratch",
+                    number
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Self {
+        Error::Rpc(err.to_string())
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::VecDeque;
+
+use ckb_nostr_utils::event::Event;
+use ckb_types::{
+    core::{BlockNumber, BlockView, TransactionView},
+    packed::{self, WitnessArgs},
+    prelude::*,
+    H256,
+};
+use nostr_binding_sdk::{BindingArgs, NostrBinding, ScriptConfig};
+
+use crate::{
+    error::Error,
+    source::BlockSource,
+    store::{BindingCell, Store},
+};
+
+/// How many of the latest blocks can be rolled back on a chain fork
+pub const MAX_ROLLBACK_BLOCKS: usize = 100;
+
+/// Follows the chain of a [`BlockSource`] and keeps a [`Store`] of the live
+/// binding cells of the nostr binding script
+pub struct Indexer<S> {
+    source: S,
+    binding: NostrBinding,
+    store: Store,
+    // the latest indexed blocks, with what they changed in the store
+    blocks: VecDeque<IndexedBlock>,
+    start_block: BlockNumber,
+    next_block: BlockNumber,
+}
+
+struct IndexedBlock {
+    number: BlockNumber,
+    hash: packed::Byte32,
+    created: Vec<packed::OutPoint>,
+    consumed: Vec<(packed::OutPoint, BindingCell)>,
+    events: Vec<String>,
+}
+
+impl<S: BlockSource> Indexer<S> {
+    /// Index blocks from `start_block`, binding cells minted before it are
+    /// missed
+    pub fn new(source: S, binding: ScriptConfig, start_block: BlockNumber) -> Self {
+        Self {
+            source,
+            // the address prefix is unused
+            binding: NostrBinding::new(binding, "ckb"),
+            store: Store::default(),
+            blocks: VecDeque::new(),
+            start_block,
+            next_block: start_block,
+        }
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Number and hash of the latest indexed block
+    pub fn tip(&self) -> Option<(BlockNumber, H256)> {
+        self.blocks
+            .back()
+            .map(|block| (block.number, block.hash.unpack()))
+    }
+
+    /// Index the blocks up to the tip of the source, rolling back the blocks
+    /// of a fork which is no longer on the chain. Returns the number of blocks
+    /// indexed.
+    pub fn sync(&mut self) -> Result<u64, Error> {
+        let mut indexed = 0;
+        while self.next_block <= self.source.tip_block_number()? {
+            let block = self
+                .source
+                .get_block(self.next_block)?
+                .ok_or(Error::BlockNotFound(self.next_block))?;
+            if let Some(parent) = self.blocks.back() {
+                if parent.hash != block.parent_hash() {
+                    self.rollback()?;
+                    continue;
+                }
+            }
+            self.index_block(&block);
+            indexed += 1;
+        }
+        Ok(indexed)
+    }
+
+    fn index_block(&mut self, block: &BlockView) {
+        let mut indexed = IndexedBlock {
+            number: block.number(),
+            hash: block.hash(),
+            created: Vec::new(),
+            consumed: Vec::new(),
+            events: Vec::new(),
+        };
+        for tx in block.transactions() {
+            for out_point in tx.input_pts_iter() {
+                if let Some(cell) = self.store.remove_cell(&out_point) {
+                    indexed.consumed.push((out_point, cell));
+                }
+            }
+            for (index, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+                let Some(type_script) = output.type_().to_opt() else {
+                    continue;
+                };
+                if !self.binding.is_binding_type(&type_script) {
+                    continue;
+                }
+                let Some(args) = BindingArgs::parse(&type_script.args().raw_data()) else {
+                    continue;
+                };
+                if let Some(event) = bound_event(&tx, index, &args, &data) {
+                    let event_id = event.id().to_hex();
+                    if self.store.insert_event(event) {
+                        indexed.events.push(event_id);
+                    }
+                }
+                let out_point = packed::OutPoint::new(tx.hash(), index as u32);
+                let cell = BindingCell::new(
+                    args.event_id,
+                    args.global_unique_id,
+                    &out_point,
+                    &output,
+                    &data.pack(),
+                    block.number(),
+                );
+                self.store.insert_cell(out_point.clone(), cell);
+                indexed.created.push(out_point);
+            }
+        }
+
+        self.blocks.push_back(indexed);
+        if self.blocks.len() > MAX_ROLLBACK_BLOCKS {
+            self.blocks.pop_front();
+        }
+        self.next_block = block.number() + 1;
+    }
+
+    fn rollback(&mut self) -> Result<(), Error> {
+        // the parent of the oldest block kept is unknown, unless it is the
+        // first block indexed
+        match self.blocks.front() {
+            Some(block) if self.blocks.len() > 1 || block.number == self.start_block => {}
+            _ => return Err(Error::ReorgTooDeep(self.next_block)),
+        }
+        let block = self.blocks.pop_back().unwrap();
+        for out_point in block.created.iter().rev() {
+            self.store.remove_cell(out_point);
+        }
+        // cells created and consumed in the same block never existed before it
+        for (out_point, cell) in block.consumed.into_iter().rev() {
+            if !block.created.contains(&out_point) {
+                self.store.insert_cell(out_point, cell);
+            }
+        }
+        for event_id in &block.events {
+            self.store.remove_event(event_id);
+        }
+        self.next_block = block.number;
+        Ok(())
+    }
+}
+
+/// The event bound by the output at `index`, in the output type of the
+/// witness at the same index when minted, or in the cell data when archived.
+/// Events which don't match the args are skipped.
+fn bound_event(
+    tx: &TransactionView,
+    index: usize,
+    args: &BindingArgs,
+    data: &[u8],
+) -> Option<Event> {
+    let json = match NostrBinding::parse_archived_event(args.flags, data) {
+        Some(json) => json.to_vec(),
+        None => {
+            let witness = tx.witnesses().get(index)?.raw_data();
+            WitnessArgs::from_slice(&witness)
+                .ok()?
+                .output_type()
+                .to_opt()?
+                .raw_data()
+                .to_vec()
+        }
+    };
+    let event = Event::from_json(json).ok()?;
+    (event.id().as_bytes() == &args.event_id && event.verify().is_ok()).then_some(event)
+}
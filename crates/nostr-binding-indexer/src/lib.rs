@@ -0,0 +1,16 @@
+//! Indexer of the nostr binding protocol, answering which live cell binds an
+//! event and which events a lock owns. It follows the blocks of a CKB node, or
+//! of a [`MockSource`] in tests, recognises the cells of the nostr binding type
+//! script, and extracts the bound events from their mint witnesses.
+
+pub mod api;
+pub mod error;
+pub mod indexer;
+pub mod source;
+pub mod store;
+
+pub use api::Api;
+pub use error::Error;
+pub use indexer::Indexer;
+pub use source::{BlockSource, MockSource, RpcSource};
+pub use store::{Binding, BindingCell, Store};
@@ -0,0 +1,123 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use ckb_types::{core::ScriptHashType, H256};
+use clap::{ArgGroup, Parser, ValueEnum};
+use nostr_binding_indexer::{Api, BlockSource, Indexer, MockSource, RpcSource};
+use nostr_binding_sdk::{ScriptConfig, SdkConfig};
+
+/// Index the live nostr binding cells and serve them as JSON
+#[derive(Parser)]
+#[command(version)]
+#[command(group(ArgGroup::new("source").required(true).args(["rpc", "blocks"])))]
+struct Cli {
+    /// URL of the CKB node RPC
+    #[arg(long)]
+    rpc: Option<String>,
+    /// JSON file of blocks in the JSON-RPC format, from the genesis block
+    #[arg(long)]
+    blocks: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = Network::Testnet)]
+    network: Network,
+    /// Code hash of the nostr binding script, overriding the one of the network
+    #[arg(long, value_parser = parse_h256)]
+    binding_code_hash: Option<H256>,
+    /// Hash type of the nostr binding script, overriding the one of the network
+    #[arg(long, value_enum)]
+    binding_hash_type: Option<HashType>,
+    /// First block to index, e.g. the block the binding script is deployed in
+    #[arg(long, default_value_t = 0)]
+    start_block: u64,
+    /// Address of the JSON API
+    #[arg(long, default_value = "127.0.0.1:8118")]
+    listen: String,
+    /// Seconds between polls of the tip
+    #[arg(long, default_value_t = 3)]
+    poll_interval: u64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Network {
+    Testnet,
+    Mainnet,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HashType {
+    Data,
+    Type,
+    Data1,
+    Data2,
+}
+
+impl From<HashType> for ScriptHashType {
+    fn from(hash_type: HashType) -> Self {
+        match hash_type {
+            HashType::Data => ScriptHashType::Data,
+            HashType::Type => ScriptHashType::Type,
+            HashType::Data1 => ScriptHashType::Data1,
+            HashType::Data2 => ScriptHashType::Data2,
+        }
+    }
+}
+
+fn parse_h256(s: &str) -> Result<H256, String> {
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(s.strip_prefix("0x").unwrap_or(s), &mut hash)
+        .map_err(|err| err.to_string())?;
+    Ok(H256(hash))
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let mut binding = match cli.network {
+        Network::Testnet => SdkConfig::testnet(),
+        Network::Mainnet => SdkConfig::mainnet(),
+    }
+    .nostr_binding;
+    if let Some(code_hash) = cli.binding_code_hash.clone() {
+        binding.code_hash = code_hash;
+    }
+    if let Some(hash_type) = cli.binding_hash_type {
+        binding.hash_type = hash_type.into();
+    }
+
+    let result = match (&cli.rpc, &cli.blocks) {
+        (Some(url), _) => run(RpcSource::new(url), binding, &cli),
+        (_, Some(path)) => MockSource::from_file(path)
+            .map_err(|err| err.to_string())
+            .and_then(|source| run(source, binding, &cli)),
+        _ => unreachable!("clap requires a source"),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run<S>(source: S, binding: ScriptConfig, cli: &Cli) -> Result<(), String>
+where
+    S: BlockSource + Send + 'static,
+{
+    let api = Api::bind(&cli.listen).map_err(|err| err.to_string())?;
+    let indexer = Arc::new(Mutex::new(Indexer::new(source, binding, cli.start_block)));
+
+    let poll_interval = Duration::from_secs(cli.poll_interval);
+    let syncing = indexer.clone();
+    thread::spawn(move || loop {
+        match syncing.lock().unwrap().sync() {
+            Ok(0) => {}
+            Ok(indexed) => println!("indexed {} blocks", indexed),
+            Err(err) => eprintln!("sync: {}", err),
+        }
+        thread::sleep(poll_interval);
+    });
+
+    println!("serving on http://{}", cli.listen);
+    api.serve(&indexer);
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use ckb_jsonrpc_types as json_types;
+use ckb_types::core::{BlockNumber, BlockView};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::error::Error;
+
+/// Where the indexer reads blocks from
+pub trait BlockSource {
+    fn tip_block_number(&self) -> Result<BlockNumber, Error>;
+
+    /// The block of `number` on the current chain, `None` above the tip
+    fn get_block(&self, number: BlockNumber) -> Result<Option<BlockView>, Error>;
+}
+
+/// Blocks of a CKB node over its JSON-RPC
+pub struct RpcSource {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl RpcSource {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, Error> {
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({
+                "id": 1,
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": params,
+            }))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            return Err(Error::Rpc(format!("{}: {}", method, error)));
+        }
+        serde_json::from_value(response["result"].clone())
+            .map_err(|err| Error::Rpc(format!("{}: {}", method, err)))
+    }
+}
+
+impl BlockSource for RpcSource {
+    fn tip_block_number(&self) -> Result<BlockNumber, Error> {
+        let number: json_types::BlockNumber = self.call("get_tip_block_number", json!([]))?;
+        Ok(number.into())
+    }
+
+    fn get_block(&self, number: BlockNumber) -> Result<Option<BlockView>, Error> {
+        let number = json_types::BlockNumber::from(number);
+        let block: Option<json_types::BlockView> =
+            self.call("get_block_by_number", json!([number]))?;
+        Ok(block.map(Into::into))
+    }
+}
+
+/// Blocks kept in memory, for tests and blocks exported to a file. Clones
+/// share the blocks, so a test can keep extending the chain an indexer reads.
+#[derive(Clone, Default)]
+pub struct MockSource {
+    blocks: Arc<Mutex<Vec<BlockView>>>,
+}
+
+impl MockSource {
+    /// `blocks` starts from the genesis block
+    pub fn new(blocks: Vec<BlockView>) -> Self {
+        Self {
+            blocks: Arc::new(Mutex::new(blocks)),
+        }
+    }
+
+    /// A JSON array of blocks in the JSON-RPC format, from the genesis block
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let blocks: Vec<json_types::BlockView> = serde_json::from_slice(&fs::read(path)?)?;
+        Ok(Self::new(blocks.into_iter().map(Into::into).collect()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let blocks: Vec<json_types::BlockView> = self
+            .blocks
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect();
+        fs::write(path, serde_json::to_string_pretty(&blocks)?)?;
+        Ok(())
+    }
+
+    pub fn push(&self, block: BlockView) {
+        self.blocks.lock().unwrap().push(block);
+    }
+
+    /// Drop the blocks above `number`, so that the chain can fork from it
+    pub fn truncate(&self, number: BlockNumber) {
+        self.blocks.lock().unwrap().truncate(number as usize + 1);
+    }
+
+    pub fn tip(&self) -> Option<BlockView> {
+        self.blocks.lock().unwrap().last().cloned()
+    }
+}
+
+impl BlockSource for MockSource {
+    fn tip_block_number(&self) -> Result<BlockNumber, Error> {
+        Ok(self.blocks.lock().unwrap().len().saturating_sub(1) as BlockNumber)
+    }
+
+    fn get_block(&self, number: BlockNumber) -> Result<Option<BlockView>, Error> {
+        Ok(self.blocks.lock().unwrap().get(number as usize).cloned())
+    }
+}
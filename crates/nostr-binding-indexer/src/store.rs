@@ -0,0 +1,137 @@
+use std::collections::{BTreeSet, HashMap};
+
+use ckb_jsonrpc_types as json_types;
+use ckb_nostr_utils::event::Event;
+use ckb_types::{core::BlockNumber, packed, prelude::*, H256};
+use serde::Serialize;
+
+/// A live binding cell
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BindingCell {
+    /// id of the bound event in hex
+    pub event_id: String,
+    /// type id of the binding cell in hex
+    pub global_unique_id: String,
+    pub out_point: json_types::OutPoint,
+    pub output: json_types::CellOutput,
+    pub output_data: json_types::JsonBytes,
+    pub lock_hash: H256,
+    pub block_number: BlockNumber,
+}
+
+/// A bound event and the live cell binding it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Binding {
+    pub event_id: String,
+    /// `None` once the binding cell is burnt
+    pub cell: Option<BindingCell>,
+    /// `None` when the indexer didn't see the mint of the cell
+    pub event: Option<Event>,
+}
+
+/// Live binding cells and the events extracted from their mint witnesses,
+/// indexed by event id and by lock hash
+#[derive(Default)]
+pub struct Store {
+    cells: HashMap<packed::OutPoint, BindingCell>,
+    by_event: HashMap<String, packed::OutPoint>,
+    by_lock: HashMap<H256, BTreeSet<String>>,
+    events: HashMap<String, Event>,
+}
+
+impl Store {
+    /// The live cell binding `event_id`
+    pub fn cell_by_event(&self, event_id: &str) -> Option<&BindingCell> {
+        self.by_event
+            .get(event_id)
+            .and_then(|out_point| self.cells.get(out_point))
+    }
+
+    /// The bound event of `event_id`, which is kept after the cell is burnt
+    pub fn event(&self, event_id: &str) -> Option<&Event> {
+        self.events.get(event_id)
+    }
+
+    pub fn binding(&self, event_id: &str) -> Option<Binding> {
+        let cell = self.cell_by_event(event_id).cloned();
+        let event = self.event(event_id).cloned();
+        (cell.is_some() || event.is_some()).then(|| Binding {
+            event_id: event_id.to_string(),
+            cell,
+            event,
+        })
+    }
+
+    /// Events bound by the live cells of `lock_hash`, ordered by event id
+    pub fn bindings_by_lock(&self, lock_hash: &H256) -> Vec<Binding> {
+        self.by_lock
+            .get(lock_hash)
+            .into_iter()
+            .flatten()
+            .filter_map(|event_id| self.binding(event_id))
+            .collect()
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &BindingCell> {
+        self.cells.values()
+    }
+
+    pub(crate) fn insert_cell(&mut self, out_point: packed::OutPoint, cell: BindingCell) {
+        self.by_event
+            .insert(cell.event_id.clone(), out_point.clone());
+        self.by_lock
+            .entry(cell.lock_hash.clone())
+            .or_default()
+            .insert(cell.event_id.clone());
+        self.cells.insert(out_point, cell);
+    }
+
+    pub(crate) fn remove_cell(&mut self, out_point: &packed::OutPoint) -> Option<BindingCell> {
+        let cell = self.cells.remove(out_point)?;
+        if self.by_event.get(&cell.event_id) == Some(out_point) {
+            self.by_event.remove(&cell.event_id);
+        }
+        if let Some(events) = self.by_lock.get_mut(&cell.lock_hash) {
+            events.remove(&cell.event_id);
+            if events.is_empty() {
+                self.by_lock.remove(&cell.lock_hash);
+            }
+        }
+        Some(cell)
+    }
+
+    /// Returns false when the event is already known
+    pub(crate) fn insert_event(&mut self, event: Event) -> bool {
+        let event_id = event.id().to_hex();
+        if self.events.contains_key(&event_id) {
+            return false;
+        }
+        self.events.insert(event_id, event);
+        true
+    }
+
+    pub(crate) fn remove_event(&mut self, event_id: &str) {
+        self.events.remove(event_id);
+    }
+}
+
+impl BindingCell {
+    pub(crate) fn new(
+        event_id: [u8; 32],
+        global_unique_id: [u8; 32],
+        out_point: &packed::OutPoint,
+        output: &packed::CellOutput,
+        output_data: &packed::Bytes,
+        block_number: BlockNumber,
+    ) -> Self {
+        Self {
+            event_id: hex::encode(event_id),
+            global_unique_id: hex::encode(global_unique_id),
+            out_point: out_point.clone().into(),
+            output: output.clone().into(),
+            output_data: json_types::JsonBytes::from_bytes(output_data.raw_data()),
+            lock_hash: output.lock().calc_script_hash().unpack(),
+            block_number,
+        }
+    }
+}
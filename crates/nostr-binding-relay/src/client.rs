@@ -0,0 +1,173 @@
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+
+use ckb_nostr_utils::event::Event;
+use tungstenite::{stream::MaybeTlsStream, Message, WebSocket};
+
+use crate::{
+    error::Error,
+    filter::Filter,
+    message::{ClientMessage, RelayMessage},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long `publish` and `fetch` wait for the relay
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A blocking NIP-01 client of one relay. Messages which are not waited for
+/// are kept in order for later calls.
+pub struct RelayClient {
+    ws: WebSocket<MaybeTlsStream<TcpStream>>,
+    pending: VecDeque<RelayMessage>,
+    next_subscription: usize,
+}
+
+impl RelayClient {
+    pub fn connect(url: &str) -> Result<Self, Error> {
+        let (ws, _) = tungstenite::connect(url)?;
+        if let MaybeTlsStream::Plain(stream) = ws.get_ref() {
+            stream.set_read_timeout(Some(POLL_INTERVAL))?;
+        }
+        Ok(Self {
+            ws,
+            pending: VecDeque::new(),
+            next_subscription: 0,
+        })
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> Result<(), Error> {
+        self.ws.send(Message::Text(message.as_json()))?;
+        Ok(())
+    }
+
+    /// Publish the event and wait for the relay to accept it
+    pub fn publish(&mut self, event: &Event) -> Result<(), Error> {
+        self.send(&ClientMessage::Event(Box::new(event.clone())))?;
+        let id = event.id().to_hex();
+        let (accepted, message) = self.wait_for(DEFAULT_TIMEOUT, |message| match message {
+            RelayMessage::Ok {
+                event_id,
+                accepted,
+                message,
+            } if *event_id == id => Some((*accepted, message.clone())),
+            _ => None,
+        })?;
+        if accepted {
+            Ok(())
+        } else {
+            Err(Error::Rejected(message))
+        }
+    }
+
+    pub fn subscribe(&mut self, subscription_id: &str, filters: Vec<Filter>) -> Result<(), Error> {
+        self.send(&ClientMessage::Req {
+            subscription_id: subscription_id.into(),
+            filters,
+        })
+    }
+
+    pub fn close(&mut self, subscription_id: &str) -> Result<(), Error> {
+        self.send(&ClientMessage::Close(subscription_id.into()))
+    }
+
+    /// Stored events matching the filters, with a subscription which is closed
+    /// at EOSE
+    pub fn fetch(&mut self, filters: Vec<Filter>) -> Result<Vec<Event>, Error> {
+        let subscription_id = format!("fetch-{}", self.next_subscription);
+        self.next_subscription += 1;
+        self.subscribe(&subscription_id, filters)?;
+
+        let deadline = Instant::now() + DEFAULT_TIMEOUT;
+        let mut events = Vec::new();
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let message = self.wait_for(timeout, |message| match message {
+                RelayMessage::Event {
+                    subscription_id: id,
+                    ..
+                }
+                | RelayMessage::Eose(id)
+                | RelayMessage::Closed {
+                    subscription_id: id,
+                    ..
+                } if *id == subscription_id => Some(message.clone()),
+                _ => None,
+            })?;
+            match message {
+                RelayMessage::Event { event, .. } => events.push(*event),
+                RelayMessage::Closed { message, .. } => return Err(Error::Closed(message)),
+                _ => break,
+            }
+        }
+        self.close(&subscription_id)?;
+        Ok(events)
+    }
+
+    /// Wait for the next event of the subscription, stored events before EOSE
+    /// included
+    pub fn next_event(&mut self, subscription_id: &str, timeout: Duration) -> Result<Event, Error> {
+        let message = self.wait_for(timeout, |message| match message {
+            RelayMessage::Event {
+                subscription_id: id,
+                ..
+            }
+            | RelayMessage::Closed {
+                subscription_id: id,
+                ..
+            } if id == subscription_id => Some(message.clone()),
+            _ => None,
+        })?;
+        match message {
+            RelayMessage::Event { event, .. } => Ok(*event),
+            RelayMessage::Closed { message, .. } => Err(Error::Closed(message)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The next message from the relay
+    pub fn recv(&mut self, timeout: Duration) -> Result<RelayMessage, Error> {
+        self.wait_for(timeout, |message| Some(message.clone()))
+    }
+
+    /// Wait for the first message `select` returns a value for, the other
+    /// messages are kept
+    pub fn wait_for<T, F>(&mut self, timeout: Duration, mut select: F) -> Result<T, Error>
+    where
+        F: FnMut(&RelayMessage) -> Option<T>,
+    {
+        if let Some((index, value)) = self
+            .pending
+            .iter()
+            .enumerate()
+            .find_map(|(index, message)| select(message).map(|value| (index, value)))
+        {
+            self.pending.remove(index);
+            return Ok(value);
+        }
+
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            let text = match self.ws.read() {
+                Ok(Message::Text(text)) => text,
+                Ok(_) => continue,
+                Err(tungstenite::Error::Io(err))
+                    if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+                {
+                    continue
+                }
+                Err(err) => return Err(err.into()),
+            };
+            let message = RelayMessage::from_json(&text)?;
+            match select(&message) {
+                Some(value) => return Ok(value),
+                None => self.pending.push_back(message),
+            }
+        }
+        Err(Error::Timeout)
+    }
+}
@@ -0,0 +1,44 @@
+use core::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Error from the websocket connection
+    WebSocket(tungstenite::Error),
+    /// Error from binding or configuring the socket
+    Io(std::io::Error),
+    /// A message which is not a NIP-01 message
+    InvalidMessage(String),
+    /// The relay didn't accept the event, with its reason
+    Rejected(String),
+    /// The relay closed the subscription, with its reason
+    Closed(String),
+    /// No message in time
+    Timeout,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::WebSocket(err) => write!(f, "websocket: {}", err),
+            Error::Io(err) => write!(f, "io: {}", err),
+            Error::InvalidMessage(reason) => write!(f, "invalid message: {}", reason),
+            Error::Rejected(reason) => write!(f, "event rejected: {}", reason),
+            Error::Closed(reason) => write!(f, "subscription closed: {}", reason),
+            Error::Timeout => write!(f, "timed out"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<tungstenite::Error> for Error {
+    fn from(err: tungstenite::Error) -> Self {
+        Error::WebSocket(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
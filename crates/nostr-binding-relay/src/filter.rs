@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use ckb_nostr_utils::event::Event;
+use serde::{Deserialize, Serialize};
+
+/// NIP-01 subscription filter. An event matches when it matches every field
+/// that is set, tag queries are the `#<letter>` fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Filter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authors: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kinds: Option<Vec<u16>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<u64>,
+    /// maximum number of stored events returned before EOSE
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// `#e`, `#p` and other single letter tag queries, keyed by `#<letter>`
+    #[serde(flatten)]
+    pub tags: BTreeMap<String, Vec<String>>,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.ids.get_or_insert_with(Vec::new).push(id.into());
+        self
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.authors
+            .get_or_insert_with(Vec::new)
+            .push(author.into());
+        self
+    }
+
+    pub fn kind(mut self, kind: u16) -> Self {
+        self.kinds.get_or_insert_with(Vec::new).push(kind);
+        self
+    }
+
+    pub fn since(mut self, since: u64) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn until(mut self, until: u64) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Events with a `name` tag whose first value is `value`, `name` is a
+    /// single letter
+    pub fn tag(mut self, name: char, value: impl Into<String>) -> Self {
+        self.tags
+            .entry(format!("#{}", name))
+            .or_default()
+            .push(value.into());
+        self
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        // ids and authors of old clients can be prefixes
+        let hex_matches = |values: &Option<Vec<String>>, hex: String| {
+            values.as_ref().map_or(true, |values| {
+                values.iter().any(|v| hex.starts_with(v.as_str()))
+            })
+        };
+        hex_matches(&self.ids, event.id().to_hex())
+            && hex_matches(&self.authors, event.author().to_hex())
+            && self
+                .kinds
+                .as_ref()
+                .map_or(true, |kinds| kinds.contains(&event.kind()))
+            && self.since.map_or(true, |since| event.created_at() >= since)
+            && self.until.map_or(true, |until| event.created_at() <= until)
+            && self.tags.iter().all(|(name, values)| {
+                let Some(name) = name.strip_prefix('#') else {
+                    // not a tag query
+                    return true;
+                };
+                event.iter_tags().any(|tag| {
+                    let tag = tag.as_vec();
+                    tag.len() > 1 && tag[0] == name && values.contains(&tag[1])
+                })
+            })
+    }
+}
@@ -0,0 +1,19 @@
+//! A lightweight NIP-01 relay which keeps events in memory, and a blocking
+//! client of it, so that the relay half of the nostr binding protocol, e.g.
+//! publishing asset events and collecting unlock events, can be tested on
+//! localhost without a live relay.
+//!
+//! Events are verified with `ckb_nostr_utils::event::Event`, the same code the
+//! scripts run on chain.
+
+pub mod client;
+pub mod error;
+pub mod filter;
+pub mod message;
+pub mod relay;
+
+pub use client::RelayClient;
+pub use error::Error;
+pub use filter::Filter;
+pub use message::{ClientMessage, RelayMessage};
+pub use relay::Relay;
@@ -0,0 +1,29 @@
+use std::thread;
+
+use clap::Parser;
+use nostr_binding_relay::Relay;
+
+/// In-memory NIP-01 relay for local tests, events are lost on exit
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7000")]
+    listen: String,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    match Relay::bind(cli.listen) {
+        Ok(relay) => {
+            println!("listening on {}", relay.url());
+            loop {
+                thread::park();
+            }
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use ckb_nostr_utils::event::Event;
+use serde_json::{json, value::RawValue, Value};
+
+use crate::{error::Error, filter::Filter};
+
+/// Messages from clients to relays
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientMessage {
+    /// `["EVENT", <event>]`
+    Event(Box<Event>),
+    /// `["REQ", <subscription id>, <filter>...]`
+    Req {
+        subscription_id: String,
+        filters: Vec<Filter>,
+    },
+    /// `["CLOSE", <subscription id>]`
+    Close(String),
+}
+
+/// Messages from relays to clients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayMessage {
+    /// `["EVENT", <subscription id>, <event>]`
+    Event {
+        subscription_id: String,
+        event: Box<Event>,
+    },
+    /// `["OK", <event id>, <accepted>, <message>]`
+    Ok {
+        event_id: String,
+        accepted: bool,
+        message: String,
+    },
+    /// `["EOSE", <subscription id>]`, the end of stored events
+    Eose(String),
+    /// `["CLOSED", <subscription id>, <message>]`
+    Closed {
+        subscription_id: String,
+        message: String,
+    },
+    /// `["NOTICE", <message>]`
+    Notice(String),
+}
+
+impl ClientMessage {
+    pub fn as_json(&self) -> String {
+        match self {
+            ClientMessage::Event(event) => json!(["EVENT", event]),
+            ClientMessage::Req {
+                subscription_id,
+                filters,
+            } => {
+                let mut message = vec![json!("REQ"), json!(subscription_id)];
+                message.extend(filters.iter().map(|filter| json!(filter)));
+                Value::Array(message)
+            }
+            ClientMessage::Close(subscription_id) => json!(["CLOSE", subscription_id]),
+        }
+        .to_string()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let (name, values) = parse_array(json)?;
+        match (name.as_str(), values.as_slice()) {
+            ("EVENT", [event]) => Ok(ClientMessage::Event(Box::new(parse_event(event)?))),
+            ("REQ", [subscription_id, filters @ ..]) => Ok(ClientMessage::Req {
+                subscription_id: parse_string(subscription_id)?,
+                filters: filters
+                    .iter()
+                    .map(|filter| serde_json::from_str(filter.get()))
+                    .collect::<Result<_, _>>()
+                    .map_err(|err| Error::InvalidMessage(format!("filter: {}", err)))?,
+            }),
+            ("CLOSE", [subscription_id]) => {
+                Ok(ClientMessage::Close(parse_string(subscription_id)?))
+            }
+            _ => Err(Error::InvalidMessage(format!("unknown message {}", name))),
+        }
+    }
+}
+
+impl RelayMessage {
+    pub fn as_json(&self) -> String {
+        match self {
+            RelayMessage::Event {
+                subscription_id,
+                event,
+            } => json!(["EVENT", subscription_id, event]),
+            RelayMessage::Ok {
+                event_id,
+                accepted,
+                message,
+            } => json!(["OK", event_id, accepted, message]),
+            RelayMessage::Eose(subscription_id) => json!(["EOSE", subscription_id]),
+            RelayMessage::Closed {
+                subscription_id,
+                message,
+            } => json!(["CLOSED", subscription_id, message]),
+            RelayMessage::Notice(message) => json!(["NOTICE", message]),
+        }
+        .to_string()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let (name, values) = parse_array(json)?;
+        match (name.as_str(), values.as_slice()) {
+            ("EVENT", [subscription_id, event]) => Ok(RelayMessage::Event {
+                subscription_id: parse_string(subscription_id)?,
+                event: Box::new(parse_event(event)?),
+            }),
+            ("OK", [event_id, accepted, message]) => Ok(RelayMessage::Ok {
+                event_id: parse_string(event_id)?,
+                accepted: serde_json::from_str(accepted.get())
+                    .map_err(|_| Error::InvalidMessage(format!("{} is not a bool", accepted)))?,
+                message: parse_string(message)?,
+            }),
+            ("EOSE", [subscription_id]) => Ok(RelayMessage::Eose(parse_string(subscription_id)?)),
+            ("CLOSED", [subscription_id, message]) => Ok(RelayMessage::Closed {
+                subscription_id: parse_string(subscription_id)?,
+                message: parse_string(message)?,
+            }),
+            ("NOTICE", [message]) => Ok(RelayMessage::Notice(parse_string(message)?)),
+            _ => Err(Error::InvalidMessage(format!("unknown message {}", name))),
+        }
+    }
+}
+
+// events are parsed from the raw JSON, so that they are relayed with the key
+// order they are signed with
+fn parse_array(json: &str) -> Result<(String, Vec<&RawValue>), Error> {
+    let mut values: Vec<&RawValue> =
+        serde_json::from_str(json).map_err(|err| Error::InvalidMessage(err.to_string()))?;
+    if values.is_empty() {
+        return Err(Error::InvalidMessage("empty array".into()));
+    }
+    let name = parse_string(values.remove(0))?;
+    Ok((name, values))
+}
+
+fn parse_string(value: &RawValue) -> Result<String, Error> {
+    serde_json::from_str(value.get())
+        .map_err(|_| Error::InvalidMessage(format!("{} is not a string", value)))
+}
+
+fn parse_event(value: &RawValue) -> Result<Event, Error> {
+    Event::from_json(value.get()).map_err(|_| Error::InvalidMessage("invalid event".into()))
+}
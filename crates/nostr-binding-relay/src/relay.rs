@@ -0,0 +1,274 @@
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use ckb_nostr_utils::event::Event;
+use tungstenite::{HandshakeError, Message, WebSocket};
+
+use crate::{
+    error::Error,
+    filter::Filter,
+    message::{ClientMessage, RelayMessage},
+};
+
+// how often connections check for outgoing messages and shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A NIP-01 relay on a local address, which keeps events in memory. The relay
+/// serves every connection on its own thread and stops when dropped.
+pub struct Relay {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct State {
+    events: Vec<Event>,
+    connections: HashMap<usize, Connection>,
+    next_connection: usize,
+}
+
+struct Connection {
+    sender: Sender<RelayMessage>,
+    subscriptions: HashMap<String, Vec<Filter>>,
+}
+
+impl Relay {
+    /// Listen on `addr`, e.g. `127.0.0.1:0` for a free port
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, Error> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let (accept_state, accept_shutdown) = (state.clone(), shutdown.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accept_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let (state, shutdown) = (accept_state.clone(), accept_shutdown.clone());
+                thread::spawn(move || {
+                    // connections end with errors whenever clients go away
+                    let _ = serve(stream, &state, &shutdown);
+                });
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The websocket URL for clients
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Stored events, ephemeral events are never stored
+    pub fn events(&self) -> Vec<Event> {
+        self.state.lock().unwrap().events.clone()
+    }
+}
+
+impl Drop for Relay {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // wake up the accept loop
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn serve(stream: TcpStream, state: &Mutex<State>, shutdown: &AtomicBool) -> Result<(), Error> {
+    let mut ws = tungstenite::accept(stream).map_err(|err| match err {
+        HandshakeError::Failure(err) => Error::WebSocket(err),
+        HandshakeError::Interrupted(_) => Error::Timeout,
+    })?;
+    ws.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+
+    let (sender, receiver) = channel();
+    let id = {
+        let mut state = state.lock().unwrap();
+        let id = state.next_connection;
+        state.next_connection += 1;
+        state.connections.insert(
+            id,
+            Connection {
+                sender,
+                subscriptions: HashMap::new(),
+            },
+        );
+        id
+    };
+    let result = serve_connection(&mut ws, id, &receiver, state, shutdown);
+    state.lock().unwrap().connections.remove(&id);
+    result
+}
+
+fn serve_connection(
+    ws: &mut WebSocket<TcpStream>,
+    id: usize,
+    receiver: &Receiver<RelayMessage>,
+    state: &Mutex<State>,
+    shutdown: &AtomicBool,
+) -> Result<(), Error> {
+    while !shutdown.load(Ordering::SeqCst) {
+        match ws.read() {
+            Ok(Message::Text(text)) => state.lock().unwrap().handle(id, &text),
+            Ok(Message::Binary(_)) => state.lock().unwrap().send(
+                id,
+                RelayMessage::Notice("binary messages are not supported".into()),
+            ),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        }
+        while let Ok(message) = receiver.try_recv() {
+            ws.send(Message::Text(message.as_json()))?;
+        }
+    }
+    Ok(())
+}
+
+impl State {
+    fn send(&self, id: usize, message: RelayMessage) {
+        if let Some(connection) = self.connections.get(&id) {
+            let _ = connection.sender.send(message);
+        }
+    }
+
+    fn handle(&mut self, id: usize, text: &str) {
+        match ClientMessage::from_json(text) {
+            Ok(ClientMessage::Event(event)) => {
+                let (accepted, message) = self.publish(*event.clone());
+                self.send(
+                    id,
+                    RelayMessage::Ok {
+                        event_id: event.id().to_hex(),
+                        accepted,
+                        message,
+                    },
+                );
+            }
+            Ok(ClientMessage::Req {
+                subscription_id,
+                filters,
+            }) => {
+                for event in self.query(&filters) {
+                    self.send(
+                        id,
+                        RelayMessage::Event {
+                            subscription_id: subscription_id.clone(),
+                            event: Box::new(event),
+                        },
+                    );
+                }
+                self.send(id, RelayMessage::Eose(subscription_id.clone()));
+                // a REQ with the id of an open subscription replaces it
+                if let Some(connection) = self.connections.get_mut(&id) {
+                    connection.subscriptions.insert(subscription_id, filters);
+                }
+            }
+            Ok(ClientMessage::Close(subscription_id)) => {
+                if let Some(connection) = self.connections.get_mut(&id) {
+                    connection.subscriptions.remove(&subscription_id);
+                }
+            }
+            Err(err) => self.send(id, RelayMessage::Notice(err.to_string())),
+        }
+    }
+
+    /// Store and broadcast the event, returns the OK status and message
+    fn publish(&mut self, event: Event) -> (bool, String) {
+        if event.verify().is_err() {
+            return (false, "invalid: bad event id or signature".into());
+        }
+        if self.events.contains(&event) {
+            return (true, "duplicate: already have this event".into());
+        }
+
+        let kind = event.kind();
+        let replaceable = kind == 0 || kind == 3 || (10000..20000).contains(&kind);
+        let parameterized = (30000..40000).contains(&kind);
+        let ephemeral = (20000..30000).contains(&kind);
+        if replaceable || parameterized {
+            let d = |event: &Event| event.get_tag_content("d".into()).map(str::to_owned);
+            let replaces = |stored: &Event| {
+                stored.author() == event.author()
+                    && stored.kind() == kind
+                    && (!parameterized || d(stored) == d(&event))
+            };
+            // the newest one is kept, or the lowest id of the same time
+            let newer = |stored: &Event| {
+                (stored.created_at(), std::cmp::Reverse(stored.id()))
+                    > (event.created_at(), std::cmp::Reverse(event.id()))
+            };
+            if self
+                .events
+                .iter()
+                .any(|stored| replaces(stored) && newer(stored))
+            {
+                return (false, "replaced: have a newer event".into());
+            }
+            self.events.retain(|stored| !replaces(stored));
+        }
+        if !ephemeral {
+            self.events.push(event.clone());
+        }
+
+        for connection in self.connections.values() {
+            for (subscription_id, filters) in &connection.subscriptions {
+                if filters.iter().any(|filter| filter.matches(&event)) {
+                    let _ = connection.sender.send(RelayMessage::Event {
+                        subscription_id: subscription_id.clone(),
+                        event: Box::new(event.clone()),
+                    });
+                }
+            }
+        }
+        (true, String::new())
+    }
+
+    /// Stored events matching any of the filters, newest first, the limit of
+    /// a filter applies to the events it matches
+    fn query(&self, filters: &[Filter]) -> Vec<Event> {
+        let mut events: Vec<&Event> = self.events.iter().collect();
+        events.sort_by(|a, b| b.cmp(a));
+        let mut matched: Vec<Event> = Vec::new();
+        for filter in filters {
+            let limit = filter.limit.unwrap_or(usize::MAX);
+            for event in events
+                .iter()
+                .filter(|event| filter.matches(event))
+                .take(limit)
+            {
+                if !matched.contains(event) {
+                    matched.push((*event).clone());
+                }
+            }
+        }
+        matched.sort_by(|a, b| b.cmp(a));
+        matched
+    }
+}
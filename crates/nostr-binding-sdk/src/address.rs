@@ -0,0 +1,44 @@
+//! Full format CKB addresses, `0x00 | code hash | hash type | args` encoded in
+//! bech32m.
+
+use bech32::{Bech32m, Hrp};
+use ckb_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{Byte32, Script},
+    prelude::*,
+};
+
+use crate::error::Error;
+
+const FULL_FORMAT: u8 = 0x00;
+
+pub fn encode_address(prefix: &str, script: &Script) -> Result<String, Error> {
+    let hrp = Hrp::parse(prefix).map_err(|e| Error::InvalidAddress(e.to_string()))?;
+    let mut payload = vec![FULL_FORMAT];
+    payload.extend(script.code_hash().as_slice());
+    payload.extend(script.hash_type().as_slice());
+    payload.extend(script.args().raw_data());
+    bech32::encode::<Bech32m>(hrp, &payload).map_err(|e| Error::InvalidAddress(e.to_string()))
+}
+
+pub fn parse_address(prefix: &str, address: &str) -> Result<Script, Error> {
+    let (hrp, payload) =
+        bech32::decode(address).map_err(|e| Error::InvalidAddress(e.to_string()))?;
+    if hrp.to_string() != prefix {
+        return Err(Error::InvalidAddress(format!(
+            "prefix should be {}",
+            prefix
+        )));
+    }
+    if payload.len() < 34 || payload[0] != FULL_FORMAT {
+        return Err(Error::InvalidAddress("not a full format address".into()));
+    }
+    let hash_type = ScriptHashType::try_from(payload[33])
+        .map_err(|_| Error::InvalidAddress("invalid hash type".into()))?;
+    Ok(Script::new_builder()
+        .code_hash(Byte32::from_slice(&payload[1..33]).unwrap())
+        .hash_type(hash_type.into())
+        .args(Bytes::copy_from_slice(&payload[34..]).pack())
+        .build())
+}
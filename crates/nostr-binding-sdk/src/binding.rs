@@ -0,0 +1,136 @@
+use ckb_hash::new_blake2b;
+use ckb_nostr_utils::binding::BindingData;
+use ckb_types::{
+    bytes::Bytes,
+    core::Capacity,
+    packed::{CellDep, CellInput, CellOutput, Script, WitnessArgs},
+    prelude::*,
+};
+use nostr::{Event, EventBuilder, JsonUtil, Tag, TagKind};
+
+use crate::{config::ScriptConfig, tag};
+
+// flags byte appended to binding script args
+pub use ckb_nostr_utils::binding::{
+    FLAG_ARCHIVE, FLAG_DATA_CONTENT, FLAG_DATA_CONTENT_HASH, FLAG_DATA_FILE_HASH, FLAG_EXPIRATION,
+    FLAG_ROYALTY,
+};
+
+/// Args of a binding type script, `<event id><global unique id>[flags]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BindingArgs {
+    pub event_id: [u8; 32],
+    pub global_unique_id: [u8; 32],
+    /// `FLAG_*`, 0 when args have no flags byte
+    pub flags: u8,
+}
+
+impl BindingArgs {
+    pub fn parse(args: &[u8]) -> Option<Self> {
+        let flags = match args.len() {
+            64 => 0,
+            65 => args[64],
+            _ => return None,
+        };
+        Some(Self {
+            event_id: args[0..32].try_into().unwrap(),
+            global_unique_id: args[32..64].try_into().unwrap(),
+            flags,
+        })
+    }
+}
+
+pub struct NostrBinding {
+    pub script_config: ScriptConfig,
+    pub prefix: &'static str,
+}
+
+impl NostrBinding {
+    pub fn new(script_config: ScriptConfig, prefix: &'static str) -> Self {
+        Self {
+            script_config,
+            prefix,
+        }
+    }
+
+    pub fn is_binding_type(&self, type_script: &Script) -> bool {
+        type_script.code_hash().as_slice() == self.script_config.code_hash.as_bytes()
+            && type_script.hash_type() == self.script_config.hash_type.into()
+    }
+
+    pub fn parse_binding_event_from_witness_args(args: &WitnessArgs) -> Option<Event> {
+        let output_type = args.output_type().to_opt()?.raw_data();
+        Event::from_json(output_type).ok()
+    }
+
+    /// The canonical JSON of the bound event kept in the data of a binding
+    /// cell with `FLAG_ARCHIVE`
+    pub fn parse_archived_event(flags: u8, data: &[u8]) -> Option<&[u8]> {
+        if flags & FLAG_ARCHIVE == 0 {
+            return None;
+        }
+        BindingData::parse(flags, data).map(|data| data.payload)
+    }
+
+    /// `flags` is an optional trailing byte of args, see `FLAG_*`
+    pub fn build_script(
+        &self,
+        event_id: [u8; 32],
+        global_unique_id: [u8; 32],
+        flags: Option<u8>,
+    ) -> Script {
+        let mut args = event_id.to_vec();
+        args.extend(global_unique_id);
+        args.extend(flags);
+        Script::new_builder()
+            .code_hash(self.script_config.code_hash.pack())
+            .hash_type(self.script_config.hash_type.into())
+            .args(Bytes::from(args).pack())
+            .build()
+    }
+
+    /// Add the `ckb_global_unique_id` tag to the event to bind
+    pub fn finalize_event_to_bind(
+        &self,
+        global_unique_id: [u8; 32],
+        event: EventBuilder,
+    ) -> EventBuilder {
+        event.add_tags([Tag::custom(
+            TagKind::from(tag::GLOBAL_UNIQUE_ID),
+            [hex::encode(global_unique_id)],
+        )])
+    }
+
+    /// The binding cell of exact capacity for `data`
+    pub fn build_binding_cell(
+        &self,
+        event_id: [u8; 32],
+        global_unique_id: [u8; 32],
+        lock: Script,
+        data: Bytes,
+        flags: Option<u8>,
+    ) -> (CellOutput, Bytes) {
+        let type_script = self.build_script(event_id, global_unique_id, flags);
+        let output = CellOutput::new_builder()
+            .lock(lock)
+            .type_(Some(type_script).pack())
+            .build_exact_capacity(Capacity::bytes(data.len()).unwrap())
+            .unwrap();
+        (output, data)
+    }
+
+    /// The type id of the binding cell at `output_index`, minted by a
+    /// transaction whose first input is `first_input`
+    pub fn build_global_unique_id(first_input: &CellInput, output_index: u64) -> [u8; 32] {
+        let mut hasher = new_blake2b();
+        hasher.update(first_input.as_slice());
+        hasher.update(&output_index.to_le_bytes());
+        let mut global_unique_id = [0u8; 32];
+        hasher.finalize(&mut global_unique_id);
+        global_unique_id
+    }
+
+    pub fn cell_deps(&self) -> Vec<CellDep> {
+        vec![self.script_config.cell_dep()]
+    }
+}
@@ -0,0 +1,94 @@
+use ckb_types::{
+    core::{DepType, ScriptHashType},
+    h256,
+    packed::{CellDep, OutPoint},
+    prelude::*,
+    H256,
+};
+
+/// Where a script is deployed and how transactions refer to it
+#[derive(Debug, Clone)]
+pub struct ScriptConfig {
+    pub code_hash: H256,
+    pub hash_type: ScriptHashType,
+    pub tx_hash: H256,
+    pub index: u32,
+    pub dep_type: DepType,
+}
+
+impl ScriptConfig {
+    /// Cell dep of the deployed cell. Scripts referred by type hash may have
+    /// been upgraded in a newer cell, which is up to the caller to look up.
+    pub fn cell_dep(&self) -> CellDep {
+        CellDep::new_builder()
+            .out_point(OutPoint::new(self.tx_hash.pack(), self.index))
+            .dep_type(self.dep_type.into())
+            .build()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SdkConfig {
+    /// address prefix, `ckt` for testnet and `ckb` for mainnet
+    pub prefix: &'static str,
+    pub nostr_binding: ScriptConfig,
+    pub nostr_lock: ScriptConfig,
+}
+
+impl SdkConfig {
+    pub fn testnet() -> Self {
+        Self {
+            prefix: "ckt",
+            nostr_lock: ScriptConfig {
+                code_hash: h256!(
+                    "0x6ae5ee0cb887b2df5a9a18137315b9bdc55be8d52637b2de0624092d5f0c91d5"
+                ),
+                hash_type: ScriptHashType::Type,
+                tx_hash: h256!(
+                    "0xa2a434dcdbe280b9ed75bb7d6c7d68186a842456aba0fc506657dc5ed7c01d68"
+                ),
+                index: 0,
+                dep_type: DepType::Code,
+            },
+            nostr_binding: ScriptConfig {
+                code_hash: h256!(
+                    "0x4105801324b70b3a1508ded8958aba66a6faf68cab26f863b4902b50dfb8b9ab"
+                ),
+                hash_type: ScriptHashType::Type,
+                tx_hash: h256!(
+                    "0x0e3949fa8afbbdf6d4abdda0d12ac1206c8d05dd51ec490b7341586291db85a6"
+                ),
+                index: 0,
+                dep_type: DepType::Code,
+            },
+        }
+    }
+
+    pub fn mainnet() -> Self {
+        Self {
+            prefix: "ckb",
+            nostr_lock: ScriptConfig {
+                code_hash: h256!(
+                    "0x641a89ad2f77721b803cd50d01351c1f308444072d5fa20088567196c0574c68"
+                ),
+                hash_type: ScriptHashType::Type,
+                tx_hash: h256!(
+                    "0x1911208b136957d5f7c1708a8835edfe8ae1d02700d5cb2c3a6aacf4d5906306"
+                ),
+                index: 0,
+                dep_type: DepType::Code,
+            },
+            nostr_binding: ScriptConfig {
+                code_hash: h256!(
+                    "0xb56ea08c4b10b454ed3389bb0e504ecfc57dcfe3089a5030654525a2def2108e"
+                ),
+                hash_type: ScriptHashType::Type,
+                tx_hash: h256!(
+                    "0xa3a63292ce23600faa508f2b2762d1a41d520eb4dd582a2dc544424ce06c3870"
+                ),
+                index: 0,
+                dep_type: DepType::Code,
+            },
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use core::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// There is no nostr lock input in the transaction
+    NoLockInput,
+    /// No witness at the index
+    WitnessNotFound(usize),
+    /// The witness at the index is not a `WitnessArgs`
+    InvalidWitness(usize),
+    /// The lock of the first witness in the group is not the dummy lock
+    InvalidDummyLock,
+    /// The signed unlock event doesn't have the length of the dummy lock
+    UnlockEventLengthMismatched { expected: usize, actual: usize },
+    /// No output at the index
+    OutputNotFound(usize),
+    /// The output at the index can't pay the fee
+    InsufficientCapacity(usize),
+    /// The fee keeps changing the content of the unlock event
+    FeeNotConverged,
+    /// Lock args which the nostr lock script would reject
+    InvalidLockArgs,
+    /// Data of a PoW difficulty config cell which the type script would reject
+    InvalidDifficultyConfig,
+    /// Malformed NIP-26 delegation tag
+    InvalidDelegation,
+    /// An event with a malformed key, id or signature, or which fails to
+    /// verify
+    InvalidEvent(String),
+    /// PoW difficulty out of range
+    InvalidPowDifficulty(u8),
+    /// Malformed or unsupported CKB address
+    InvalidAddress(String),
+    /// The script is not the nostr lock or binding of the config
+    ScriptNotMatch,
+    /// Error from building or signing a nostr event
+    Nostr(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoLockInput => write!(f, "there is no nostr lock input"),
+            Error::WitnessNotFound(index) => write!(f, "witness {} not found", index),
+            Error::InvalidWitness(index) => write!(f, "witness {} is not a WitnessArgs", index),
+            Error::InvalidDummyLock => {
+                write!(f, "lock field in first witness is an invalid dummy lock")
+            }
+            Error::UnlockEventLengthMismatched { expected, actual } => write!(
+                f,
+                "unlock event of {} bytes doesn't match the dummy lock of {} bytes",
+                actual, expected
+            ),
+            Error::OutputNotFound(index) => write!(f, "output {} not found", index),
+            Error::InsufficientCapacity(index) => {
+                write!(f, "output {} has not enough capacity to pay the fee", index)
+            }
+            Error::FeeNotConverged => write!(f, "fee doesn't converge"),
+            Error::InvalidLockArgs => write!(f, "invalid nostr lock args"),
+            Error::InvalidDifficultyConfig => write!(f, "invalid PoW difficulty config"),
+            Error::InvalidDelegation => write!(f, "invalid delegation tag"),
+            Error::InvalidEvent(reason) => write!(f, "invalid event: {}", reason),
+            Error::InvalidPowDifficulty(pow) => write!(f, "invalid PoW difficulty {}", pow),
+            Error::InvalidAddress(reason) => write!(f, "invalid address: {}", reason),
+            Error::ScriptNotMatch => write!(f, "nostr-lock contract script info not match"),
+            Error::Nostr(reason) => write!(f, "nostr: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ckb_nostr_utils::error::Error> for Error {
+    fn from(err: ckb_nostr_utils::error::Error) -> Self {
+        use ckb_nostr_utils::error::Error as UtilsError;
+        match err {
+            UtilsError::InvalidLockArgs => Error::InvalidLockArgs,
+            UtilsError::InvalidDifficultyConfig => Error::InvalidDifficultyConfig,
+            UtilsError::InvalidDelegation => Error::InvalidDelegation,
+            UtilsError::InvalidPublicKey => Error::InvalidEvent("invalid public key".into()),
+            UtilsError::InvalidEventId => Error::InvalidEvent("invalid event id".into()),
+            UtilsError::ValidationFail => Error::InvalidEvent("invalid signature".into()),
+            UtilsError::InvalidSignatureFormat => {
+                Error::InvalidEvent("invalid signature format".into())
+            }
+            UtilsError::UnknownKey(key) => Error::InvalidEvent(format!("unknown key {}", key)),
+            UtilsError::Json(reason) => Error::InvalidEvent(format!("json: {}", reason)),
+        }
+    }
+}
+
+impl From<nostr::event::builder::Error> for Error {
+    fn from(err: nostr::event::builder::Error) -> Self {
+        Error::Nostr(err.to_string())
+    }
+}
+
+impl From<nostr::event::Error> for Error {
+    fn from(err: nostr::event::Error) -> Self {
+        Error::Nostr(err.to_string())
+    }
+}
+
+impl From<nostr::event::unsigned::Error> for Error {
+    fn from(err: nostr::event::unsigned::Error) -> Self {
+        Error::Nostr(err.to_string())
+    }
+}
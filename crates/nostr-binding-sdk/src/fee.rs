@@ -0,0 +1,98 @@
+//! Size and fee of transactions unlocked by nostr lock. The witness lock of an
+//! unlock is the signed event JSON, whose exact length is known before
+//! signing, so the fee can be paid before sighash_all is generated.
+
+use ckb_types::{
+    core::{Capacity, TransactionView},
+    packed,
+    prelude::*,
+};
+
+use crate::{error::Error, NostrLock};
+
+// the summary content depends on the fee, which rarely changes its length
+const MAX_FEE_ROUNDS: usize = 8;
+
+/// Size of the transaction in a block, which the fee rate applies to
+pub fn tx_size(tx: &TransactionView) -> usize {
+    tx.data().serialized_size_in_block()
+}
+
+/// Fee of `size` bytes at `fee_rate` shannons per 1000 bytes. CKB nodes round
+/// the minimal fee down, this rounds up, so the fee is a safe upper bound
+/// never below the one a node requires.
+pub fn calculate_fee(size: usize, fee_rate: u64) -> u64 {
+    let fee = (size as u64).saturating_mul(fee_rate);
+    fee / 1000 + u64::from(fee % 1000 != 0)
+}
+
+impl NostrLock {
+    /// Fee of the transaction once the nostr lock group at `lock_indexes` is
+    /// signed with an unlock event of `content`
+    pub fn estimate_fee(
+        &self,
+        tx: TransactionView,
+        lock_indexes: &[usize],
+        content: &str,
+        fee_rate: u64,
+    ) -> Result<u64, Error> {
+        let tx = self.prepare_tx(tx, lock_indexes, content)?;
+        Ok(calculate_fee(tx_size(&tx), fee_rate))
+    }
+
+    /// Pay the fee of `fee_rate` from the output at `change_index` and fill in
+    /// the dummy lock, so both the fee and sighash_all are final before
+    /// signing. The change output holds all the capacity left for the fee.
+    /// `content` builds the unlock event content of the transaction, e.g.
+    /// `build_tx_summary` for locks with `FLAG_SUMMARY_CONTENT`, whose fee line
+    /// changes with the fee.
+    ///
+    /// Returns the prepared transaction and the content to call
+    /// `sign_prepared_tx` or `mine_tx` with.
+    pub fn prepare_tx_with_fee<F>(
+        &self,
+        tx: TransactionView,
+        lock_indexes: &[usize],
+        change_index: usize,
+        fee_rate: u64,
+        content: F,
+    ) -> Result<(TransactionView, String), Error>
+    where
+        F: Fn(&TransactionView) -> Result<String, Error>,
+    {
+        let (change, data) = tx
+            .output_with_data(change_index)
+            .ok_or(Error::OutputNotFound(change_index))?;
+        let capacity: u64 = change.capacity().unpack();
+        let occupied = Capacity::bytes(data.len())
+            .and_then(|data| change.occupied_capacity(data))
+            .map_err(|_| Error::InsufficientCapacity(change_index))?
+            .as_u64();
+        let pay = |fee: u64| -> Result<TransactionView, Error> {
+            let capacity = capacity
+                .checked_sub(fee)
+                .filter(|capacity| *capacity >= occupied)
+                .ok_or(Error::InsufficientCapacity(change_index))?;
+            let mut outputs: Vec<packed::CellOutput> = tx.outputs().into_iter().collect();
+            outputs[change_index] = change
+                .clone()
+                .as_builder()
+                .capacity(capacity.pack())
+                .build();
+            Ok(tx.as_advanced_builder().set_outputs(outputs).build())
+        };
+
+        let mut fee = 0;
+        for _ in 0..MAX_FEE_ROUNDS {
+            let tx = pay(fee)?;
+            let content = content(&tx)?;
+            let prepared = self.prepare_tx(tx, lock_indexes, &content)?;
+            let required = calculate_fee(tx_size(&prepared), fee_rate);
+            if required <= fee {
+                return Ok((prepared, content));
+            }
+            fee = required;
+        }
+        Err(Error::FeeNotConverged)
+    }
+}
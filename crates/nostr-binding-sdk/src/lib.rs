@@ -0,0 +1,41 @@
+//! Rust SDK for the nostr binding protocol on CKB, the counterpart of the
+//! JavaScript SDK in `packages/sdk`. It builds scripts, cells, witnesses and
+//! events for transactions, and leaves fetching cells and sending
+//! transactions to the caller.
+
+pub mod address;
+pub mod binding;
+pub mod config;
+pub mod error;
+pub mod fee;
+pub mod lock;
+pub mod tag;
+pub mod verify;
+
+pub use binding::{BindingArgs, NostrBinding};
+pub use ckb_nostr_error::{ScriptError, ScriptFailure, ScriptKind};
+pub use ckb_nostr_utils::lock_args;
+pub use config::{ScriptConfig, SdkConfig};
+pub use error::Error;
+pub use lock::NostrLock;
+pub use verify::{Diagnostic, ResolvedTx, Rule};
+
+pub struct NostrBindingSdk {
+    pub binding: NostrBinding,
+    pub lock: NostrLock,
+}
+
+impl NostrBindingSdk {
+    pub fn new(config: SdkConfig) -> Self {
+        Self {
+            binding: NostrBinding::new(config.nostr_binding, config.prefix),
+            lock: NostrLock::new(config.nostr_lock, config.prefix),
+        }
+    }
+}
+
+impl Default for NostrBindingSdk {
+    fn default() -> Self {
+        Self::new(SdkConfig::testnet())
+    }
+}
@@ -0,0 +1,568 @@
+use ckb_hash::{blake2b_256, new_blake2b};
+use ckb_nostr_utils::lock_args::{
+    ArgsFormat, LockArgs, Recovery, FLAG_DYNAMIC_POW, FLAG_KEY_AND_POW,
+};
+use ckb_nostr_utils::since::SINCE_RELATIVE;
+use ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionView},
+    packed::{self, CellDep, CellOutput, Script, WitnessArgs},
+    prelude::*,
+};
+use nostr::{
+    Event, EventBuilder, EventId, JsonUtil, Keys, Kind, PublicKey, Tag, TagKind, Timestamp,
+    UnsignedEvent,
+};
+use serde_json::json;
+
+use crate::{
+    address::{encode_address, parse_address},
+    config::ScriptConfig,
+    error::Error,
+    tag,
+};
+
+pub const NOSTR_LOCK_KIND: u16 = 23334;
+pub const SESSION_KIND: u16 = 23335;
+pub const WARNING: &str = "IMPORTANT: Please verify the integrity and authenticity of connected Nostr client before signing this message";
+// nonce of mined unlock events is padded to a fixed length, so the dummy lock
+// has the length of the signed event
+const NONCE_LEN: usize = 10;
+
+pub struct NostrLock {
+    pub script_config: ScriptConfig,
+    pub prefix: &'static str,
+    /// kind and content of unlock events, can be replaced by
+    /// `use_content_config`
+    pub kind: u16,
+    pub content: String,
+    /// when set, unlock events carry a `ckb_chain` tag and transactions depend
+    /// on the genesis header, see `bind_chain`
+    pub genesis_hash: Option<[u8; 32]>,
+    /// NIP-26 delegation tag granted by the lock key to the signer, added to
+    /// unlock events when set. Its conditions must include kind=23334.
+    pub delegation_tag: Option<Tag>,
+    /// authorization event signed by the owner key, added to unlock events
+    /// signed by a session key when set
+    pub session_authorization: Option<Event>,
+    /// when set, unlock events carry a `nonce` tag and are mined to this PoW
+    /// difficulty, see `mine_tx`
+    pub pow_difficulty: Option<u8>,
+}
+
+impl NostrLock {
+    pub fn new(script_config: ScriptConfig, prefix: &'static str) -> Self {
+        Self {
+            script_config,
+            prefix,
+            kind: NOSTR_LOCK_KIND,
+            content: format!("Signing a CKB transaction\n\n{}\n", WARNING),
+            genesis_hash: None,
+            delegation_tag: None,
+            session_authorization: None,
+            pow_difficulty: None,
+        }
+    }
+
+    /// Sign unlock events of `kind` and `content`, for locks with
+    /// `FLAG_CONTENT_CONFIG`. The content config cell must be in cell deps of
+    /// the transaction.
+    pub fn use_content_config(&mut self, kind: u16, content: &str) {
+        self.kind = kind;
+        self.content = content.to_string();
+    }
+
+    /// Data of a content config cell: kind, 2 bytes little endian, followed by
+    /// sha256 of content
+    pub fn build_content_config_data(kind: u16, content: &str) -> Bytes {
+        use nostr::hashes::{sha256, Hash};
+        let mut data = kind.to_le_bytes().to_vec();
+        data.extend(sha256::Hash::hash(content.as_bytes()).as_byte_array());
+        data.into()
+    }
+
+    pub fn is_nostr_lock(&self, lock: &Script) -> bool {
+        lock.code_hash().as_slice() == self.script_config.code_hash.as_bytes()
+            && lock.hash_type() == self.script_config.hash_type.into()
+    }
+
+    pub fn parse_unlock_event_from_witness_args(args: &WitnessArgs) -> Option<Event> {
+        let lock = args.lock().to_opt()?.raw_data();
+        Event::from_json(lock).ok()
+    }
+
+    /// 20 bytes of pubkey hash
+    pub fn build_pubkey_hash(pubkey: &PublicKey) -> [u8; 20] {
+        blake2b_256(pubkey.to_bytes())[..20].try_into().unwrap()
+    }
+
+    /// Args of the lock owned by `owner`, without flags. Set flags and fields
+    /// on it for `build_script_with_args`.
+    pub fn build_lock_args(owner: &PublicKey) -> LockArgs {
+        LockArgs {
+            pubkey_hash: Self::build_pubkey_hash(owner),
+            ..Default::default()
+        }
+    }
+
+    /// Recovery field of the lock args. `delay` must be a relative since.
+    pub fn build_recovery_field(recovery: &PublicKey, delay: u64) -> Result<Recovery, Error> {
+        if delay & SINCE_RELATIVE == 0 {
+            return Err(Error::InvalidLockArgs);
+        }
+        Ok(Recovery {
+            pubkey_hash: Self::build_pubkey_hash(recovery),
+            delay,
+        })
+    }
+
+    pub fn build_script(&self, owner: &PublicKey) -> Script {
+        self.new_script(Self::build_lock_args(owner).to_bytes())
+    }
+
+    /// Args are checked the same way as the lock script does
+    pub fn build_script_with_args(&self, args: &LockArgs) -> Result<Script, Error> {
+        let args = args.to_bytes();
+        LockArgs::parse(&args)?;
+        Ok(self.new_script(args))
+    }
+
+    /// PoW lock script only checks if the witness event is matching specific
+    /// difficulties instead of a pubkey hash
+    pub fn build_pow_script(&self, pow: u8) -> Script {
+        let args = LockArgs {
+            pow_difficulty: pow,
+            ..Default::default()
+        };
+        self.new_script(args.to_bytes())
+    }
+
+    /// PoW difficulty is the one in the config cell of `config_type_hash`, no
+    /// less than `floor`. The unlocking transaction must record the claim by
+    /// updating the config cell, see the pow-difficulty type script.
+    pub fn build_dynamic_pow_script(&self, config_type_hash: [u8; 32], floor: u8) -> Script {
+        let args = LockArgs {
+            format: ArgsFormat::V1,
+            pow_difficulty: floor,
+            flags: FLAG_DYNAMIC_POW,
+            difficulty_config: Some(config_type_hash),
+            ..Default::default()
+        };
+        self.new_script(args.to_bytes())
+    }
+
+    /// Both the owner signature and PoW of `pow` difficulty are required to
+    /// unlock
+    pub fn build_key_and_pow_script(&self, args: &LockArgs, pow: u8) -> Result<Script, Error> {
+        if pow == 0 {
+            return Err(Error::InvalidPowDifficulty(pow));
+        }
+        let args = LockArgs {
+            pow_difficulty: pow,
+            flags: args.flags | FLAG_KEY_AND_POW,
+            ..args.clone()
+        };
+        self.build_script_with_args(&args)
+    }
+
+    fn new_script(&self, args: Vec<u8>) -> Script {
+        Script::new_builder()
+            .code_hash(self.script_config.code_hash.pack())
+            .hash_type(self.script_config.hash_type.into())
+            .args(Bytes::from(args).pack())
+            .build()
+    }
+
+    pub fn encode_to_ckb_address(&self, owner: &PublicKey) -> Result<String, Error> {
+        encode_address(self.prefix, &self.build_script(owner))
+    }
+
+    pub fn parse_ckb_address_to_nostr_pubkey_hash(&self, address: &str) -> Result<[u8; 20], Error> {
+        let script = parse_address(self.prefix, address)?;
+        if !self.is_nostr_lock(&script) {
+            return Err(Error::ScriptNotMatch);
+        }
+        let args = LockArgs::parse(&script.args().raw_data())?;
+        Ok(args.pubkey_hash)
+    }
+
+    pub fn cell_deps(&self) -> Vec<CellDep> {
+        vec![self.script_config.cell_dep()]
+    }
+
+    /// Make unlock events only valid on the chain of `genesis_hash`, so they
+    /// can't be replayed on another network or fork
+    pub fn bind_chain(&mut self, genesis_hash: [u8; 32]) {
+        self.genesis_hash = Some(genesis_hash);
+    }
+
+    /// The genesis header named by `ckb_chain` tag must be in header deps,
+    /// which affects the transaction hash
+    pub fn add_chain_header_dep(&self, tx: TransactionView) -> TransactionView {
+        match self.genesis_hash {
+            Some(genesis_hash) => {
+                let genesis_hash = genesis_hash.pack();
+                if tx.header_deps_iter().any(|hash| hash == genesis_hash) {
+                    tx
+                } else {
+                    tx.as_advanced_builder().header_dep(genesis_hash).build()
+                }
+            }
+            None => tx,
+        }
+    }
+
+    /// Indexes of nostr lock inputs, `input_cells` are the cells spent by the
+    /// inputs of the transaction, in the same order
+    pub fn get_lock_indexes(&self, input_cells: &[CellOutput]) -> Vec<usize> {
+        input_cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| self.is_nostr_lock(&cell.lock()))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Overwrite the witness lock with dummy lock and then generate
+    /// sighash_all, sign it and return signed transaction. The fee must have
+    /// been paid for the length of the dummy lock, see `prepare_tx_with_fee`.
+    /// `content` should be `build_tx_summary` of the transaction when
+    /// `FLAG_SUMMARY_CONTENT` is set.
+    pub fn sign_tx<F>(
+        &self,
+        tx: TransactionView,
+        lock_indexes: &[usize],
+        content: &str,
+        signer: F,
+    ) -> Result<TransactionView, Error>
+    where
+        F: FnOnce(EventBuilder) -> Result<Event, Error>,
+    {
+        let tx = self.prepare_tx(tx, lock_indexes, content)?;
+        self.sign_prepared_tx(tx, lock_indexes, content, signer)
+    }
+
+    /// Check the transaction is placed with the dummy lock of `content`, and
+    /// then directly generate sighash_all, sign it and return signed
+    /// transaction. Call `prepare_tx` before this function.
+    pub fn sign_prepared_tx<F>(
+        &self,
+        tx: TransactionView,
+        lock_indexes: &[usize],
+        content: &str,
+        signer: F,
+    ) -> Result<TransactionView, Error>
+    where
+        F: FnOnce(EventBuilder) -> Result<Event, Error>,
+    {
+        let witness_index = *lock_indexes.first().ok_or(Error::NoLockInput)?;
+        let witness = tx
+            .witnesses()
+            .get(witness_index)
+            .ok_or(Error::WitnessNotFound(witness_index))?;
+        let witness_args = WitnessArgs::from_slice(&witness.raw_data())
+            .map_err(|_| Error::InvalidWitness(witness_index))?;
+        if let Some(lock) = witness_args.lock().to_opt() {
+            if lock.raw_data() != self.build_dummy_lock(content) {
+                return Err(Error::InvalidDummyLock);
+            }
+        }
+
+        let sighash_all = Self::build_sighash_all(&tx, lock_indexes)?;
+        let event = signer(self.build_unlock_event(sighash_all, content))?;
+        Self::fill_in_signed_event(tx, witness_index, &event)
+    }
+
+    /// Fill in the witness of nostr lock with the dummy lock of `content`
+    pub fn prepare_tx(
+        &self,
+        tx: TransactionView,
+        lock_indexes: &[usize],
+        content: &str,
+    ) -> Result<TransactionView, Error> {
+        let witness_index = *lock_indexes.first().ok_or(Error::NoLockInput)?;
+        let tx = self.add_chain_header_dep(tx);
+        let mut witnesses: Vec<packed::Bytes> = tx.witnesses().into_iter().collect();
+        if witness_index >= witnesses.len() {
+            witnesses.resize(witness_index + 1, Bytes::new().pack());
+        }
+        let witness = witnesses[witness_index].raw_data();
+        witnesses[witness_index] = self
+            .fill_in_dummy_lock_witness(&witness, content)
+            .map_err(|_| Error::InvalidWitness(witness_index))?
+            .pack();
+        Ok(tx.as_advanced_builder().set_witnesses(witnesses).build())
+    }
+
+    /// Prepare the transaction, mine an unlock event of `pow_difficulty` and
+    /// sign it with `keys`, which is any key for PoW locks, or the owner key
+    /// for locks with `FLAG_KEY_AND_POW`
+    pub fn mine_tx(
+        &self,
+        tx: TransactionView,
+        lock_indexes: &[usize],
+        content: &str,
+        keys: &Keys,
+    ) -> Result<TransactionView, Error> {
+        let tx = self.prepare_tx(tx, lock_indexes, content)?;
+        let sighash_all = Self::build_sighash_all(&tx, lock_indexes)?;
+        let event = self
+            .mine_unlock_event(sighash_all, content, keys.public_key())?
+            .sign(keys)?;
+        Self::fill_in_signed_event(tx, lock_indexes[0], &event)
+    }
+
+    /// Search the nonce for an unlock event of `pubkey` whose id meets
+    /// `pow_difficulty`
+    pub fn mine_unlock_event(
+        &self,
+        sighash_all: [u8; 32],
+        content: &str,
+        pubkey: PublicKey,
+    ) -> Result<UnsignedEvent, Error> {
+        let difficulty = self.pow_difficulty.ok_or(Error::InvalidPowDifficulty(0))?;
+        let created_at = Timestamp::now();
+        let mut tags = self.build_tags(sighash_all);
+        tags.pop();
+        for nonce in 0..10u64.pow(NONCE_LEN as u32) {
+            let mut tags = tags.clone();
+            tags.push(Self::nonce_tag(nonce, difficulty));
+            let kind = Kind::from(self.kind);
+            if EventId::new(&pubkey, &created_at, &kind, &tags, content).check_pow(difficulty) {
+                return Ok(EventBuilder::new(kind, content, tags)
+                    .custom_created_at(created_at)
+                    .to_unsigned_event(pubkey));
+            }
+        }
+        Err(Error::InvalidPowDifficulty(difficulty))
+    }
+
+    fn nonce_tag(nonce: u64, difficulty: u8) -> Tag {
+        Tag::custom(
+            TagKind::from(tag::NONCE),
+            [
+                format!("{:0width$}", nonce, width = NONCE_LEN),
+                difficulty.to_string(),
+            ],
+        )
+    }
+
+    /// Put the JSON of signed `event` into the lock of the witness
+    pub fn fill_in_signed_event(
+        tx: TransactionView,
+        witness_index: usize,
+        event: &Event,
+    ) -> Result<TransactionView, Error> {
+        let mut witnesses: Vec<packed::Bytes> = tx.witnesses().into_iter().collect();
+        let witness = witnesses
+            .get(witness_index)
+            .ok_or(Error::WitnessNotFound(witness_index))?
+            .raw_data();
+        let witness_args =
+            WitnessArgs::from_slice(&witness).map_err(|_| Error::InvalidWitness(witness_index))?;
+        let lock = Bytes::from(event.as_json());
+        // sighash_all and the fee are computed with the dummy lock in place
+        if let Some(dummy_lock) = witness_args.lock().to_opt() {
+            if dummy_lock.len() != lock.len() {
+                return Err(Error::UnlockEventLengthMismatched {
+                    expected: dummy_lock.len(),
+                    actual: lock.len(),
+                });
+            }
+        }
+        witnesses[witness_index] = witness_args
+            .as_builder()
+            .lock(Some(lock).pack())
+            .build()
+            .as_bytes()
+            .pack();
+        Ok(tx.as_advanced_builder().set_witnesses(witnesses).build())
+    }
+
+    /// sighash_all of the lock group at `lock_indexes`. The witnesses should
+    /// be filled with placeholders before calling.
+    pub fn build_sighash_all(
+        tx: &TransactionView,
+        lock_indexes: &[usize],
+    ) -> Result<[u8; 32], Error> {
+        if lock_indexes.is_empty() {
+            return Err(Error::NoLockInput);
+        }
+        let mut hasher = new_blake2b();
+        hasher.update(tx.hash().as_slice());
+        let extra_witnesses = tx.inputs().len()..tx.witnesses().len();
+        for index in lock_indexes.iter().copied().chain(extra_witnesses) {
+            let witness = tx
+                .witnesses()
+                .get(index)
+                .ok_or(Error::WitnessNotFound(index))?
+                .raw_data();
+            hasher.update(&(witness.len() as u64).to_le_bytes());
+            hasher.update(&witness);
+        }
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        Ok(message)
+    }
+
+    pub fn fill_in_dummy_lock_witness(
+        &self,
+        witness: &[u8],
+        content: &str,
+    ) -> Result<Bytes, Error> {
+        let witness_args = if witness.is_empty() {
+            WitnessArgs::default()
+        } else {
+            WitnessArgs::from_slice(witness).map_err(|_| Error::InvalidWitness(0))?
+        };
+        Ok(witness_args
+            .as_builder()
+            .lock(Some(self.build_dummy_lock(content)).pack())
+            .build()
+            .as_bytes())
+    }
+
+    /// Zeros of the length of the signed unlock event JSON
+    pub fn build_dummy_lock(&self, content: &str) -> Bytes {
+        vec![0u8; self.unlock_event_len(content)].into()
+    }
+
+    /// Exact length of the signed unlock event JSON of `content`, with the
+    /// tags of this lock: chain, delegation, session and the padded nonce of
+    /// mined events. `created_at` is assumed to have the width of now.
+    pub fn unlock_event_len(&self, content: &str) -> usize {
+        self.build_dummy_event(content).len()
+    }
+
+    pub fn build_tags(&self, sighash_all: [u8; 32]) -> Vec<Tag> {
+        let mut tags = vec![Tag::custom(
+            TagKind::from(tag::SIGHASH_ALL),
+            [hex::encode(sighash_all)],
+        )];
+        if let Some(genesis_hash) = self.genesis_hash {
+            tags.push(Tag::custom(
+                TagKind::from(tag::CHAIN),
+                [hex::encode(genesis_hash)],
+            ));
+        }
+        if let Some(delegation_tag) = &self.delegation_tag {
+            tags.push(delegation_tag.clone());
+        }
+        if let Some(session_authorization) = &self.session_authorization {
+            tags.push(Tag::custom(
+                TagKind::from(tag::SESSION),
+                [session_authorization.as_json()],
+            ));
+        }
+        if let Some(difficulty) = self.pow_difficulty {
+            tags.push(Self::nonce_tag(0, difficulty));
+        }
+        tags
+    }
+
+    /// JSON of an unlock event with id, pubkey, signature and sighash_all of
+    /// zeros
+    pub fn build_dummy_event(&self, content: &str) -> String {
+        let tags: Vec<Vec<String>> = self
+            .build_tags([0u8; 32])
+            .into_iter()
+            .map(Tag::to_vec)
+            .collect();
+        json!({
+            "id": "00".repeat(32),
+            "pubkey": "00".repeat(32),
+            "created_at": Timestamp::now().as_u64(),
+            "kind": self.kind,
+            "tags": tags,
+            "content": content,
+            "sig": "00".repeat(64),
+        })
+        .to_string()
+    }
+
+    pub fn build_unlock_event(&self, sighash_all: [u8; 32], content: &str) -> EventBuilder {
+        EventBuilder::new(Kind::from(self.kind), content, self.build_tags(sighash_all))
+    }
+
+    /// Authorization event to be signed by the owner key, granting
+    /// `session_pubkey` to unlock until `expiration`(unix timestamp in
+    /// seconds). Each transaction can move at most `max_capacity` shannons out
+    /// of the lock, and only to `allowed_lock_hashes`. Each transaction must
+    /// prove a time before `expiration` by a header dep or an absolute `since`.
+    pub fn build_session_authorization_event(
+        &self,
+        session_pubkey: &PublicKey,
+        expiration: u64,
+        max_capacity: u64,
+        allowed_lock_hashes: &[[u8; 32]],
+    ) -> EventBuilder {
+        let mut tags = vec![
+            Tag::custom(
+                TagKind::from(tag::SESSION_PUBKEY),
+                [session_pubkey.to_hex()],
+            ),
+            Tag::custom(TagKind::from(tag::EXPIRATION), [expiration.to_string()]),
+            Tag::custom(TagKind::from(tag::MAX_CAPACITY), [max_capacity.to_string()]),
+        ];
+        for lock_hash in allowed_lock_hashes {
+            tags.push(Tag::custom(
+                TagKind::from(tag::ALLOWED_LOCK),
+                [hex::encode(lock_hash)],
+            ));
+        }
+        EventBuilder::new(Kind::from(SESSION_KIND), "", tags)
+    }
+
+    /// Deterministic summary of the transaction, which is rebuilt and
+    /// compared on chain when `FLAG_SUMMARY_CONTENT` is set. It must be
+    /// generated after outputs and inputs are final. `input_cells` are the
+    /// cells spent by the inputs.
+    pub fn build_tx_summary(
+        &self,
+        tx: &TransactionView,
+        input_cells: &[CellOutput],
+    ) -> Result<String, Error> {
+        let format_capacity =
+            |shannons: u64| format!("{}.{:08}", shannons / 100_000_000, shannons % 100_000_000);
+
+        let mut summary = format!(
+            "Signing a CKB transaction on {}\n\n{}\n",
+            self.prefix, WARNING
+        );
+        let mut outputs_capacity = 0u64;
+        for (index, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+            let capacity: u64 = output.capacity().unpack();
+            outputs_capacity = outputs_capacity.saturating_add(capacity);
+            summary += &format!(
+                "\nOutput {}: {} CKB\nTo: {}\n",
+                index,
+                format_capacity(capacity),
+                encode_address(self.prefix, &output.lock())?
+            );
+            if let Some(type_script) = output.type_().to_opt() {
+                let hash_type = match ScriptHashType::try_from(type_script.hash_type()) {
+                    Ok(ScriptHashType::Data) => "data",
+                    Ok(ScriptHashType::Type) => "type",
+                    Ok(ScriptHashType::Data1) => "data1",
+                    Ok(ScriptHashType::Data2) => "data2",
+                    Err(_) => "unknown",
+                };
+                summary += &format!(
+                    "Type: 0x{} {} 0x{}\n",
+                    hex::encode(type_script.code_hash().as_slice()),
+                    hash_type,
+                    hex::encode(type_script.args().raw_data())
+                );
+            }
+            summary += &format!("Data: {} bytes\n", data.len());
+        }
+        let inputs_capacity = input_cells.iter().fold(0u64, |sum, cell| {
+            sum.saturating_add(Unpack::<u64>::unpack(&cell.capacity()))
+        });
+        summary += &format!(
+            "\nFee: {} CKB\n",
+            format_capacity(inputs_capacity.saturating_sub(outputs_capacity))
+        );
+        Ok(summary)
+    }
+}
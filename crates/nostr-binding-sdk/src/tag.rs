@@ -0,0 +1,27 @@
+//! Names of the tags used by the nostr binding protocol
+
+pub const GLOBAL_UNIQUE_ID: &str = "ckb_global_unique_id";
+pub const SIGHASH_ALL: &str = "ckb_sighash_all";
+/// `[ckb_chain, <genesis hash>]`, the unlock event is only valid on the chain
+/// of the genesis hash
+pub const CHAIN: &str = "ckb_chain";
+/// NIP-26 `[delegation, <delegator pubkey>, <conditions>, <token>]`
+pub const DELEGATION: &str = "delegation";
+/// `[ckb_session, <authorization event JSON>]`, the unlock event is signed by
+/// a session key
+pub const SESSION: &str = "ckb_session";
+pub const SESSION_PUBKEY: &str = "ckb_session_pubkey";
+pub const MAX_CAPACITY: &str = "ckb_max_capacity";
+pub const ALLOWED_LOCK: &str = "ckb_allowed_lock";
+pub const EXPIRATION: &str = "expiration";
+/// `[ckb_mint_to, <lock hash>]`, the binding cell can only be minted to the
+/// named lock
+pub const MINT_TO: &str = "ckb_mint_to";
+/// `[ckb_mint_to_author, <nostr lock code hash>]`, the binding cell can only
+/// be minted to the author's nostr lock
+pub const MINT_TO_AUTHOR: &str = "ckb_mint_to_author";
+pub const NONCE: &str = "nonce";
+/// `[royalty, <recipient lock hash>, <rate in basis points>, <UDT type hash>]`
+pub const ROYALTY: &str = "royalty";
+/// NIP-94 sha256 of the file
+pub const FILE_HASH: &str = "x";
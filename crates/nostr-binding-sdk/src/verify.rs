@@ -0,0 +1,1054 @@
+//! Off-chain preflight of the nostr lock and nostr binding scripts. On chain a
+//! failed script only tells its exit code, here the same rules run against a
+//! resolved transaction and the failed one is reported with the expected and
+//! actual values.
+//!
+//! Cells spent by anyone-can-pay without signature, and transfers and burns of
+//! binding cells are not checked.
+
+use std::fmt;
+
+use ckb_nostr_error::{BindingError, CodeHash, LockError, ScriptError, ScriptFailure};
+use ckb_nostr_utils::{
+    binding::{
+        BindingData, Royalty, FLAGS_ALL, FLAGS_IMMUTABLE_DATA, FLAG_ARCHIVE, FLAG_DATA_CONTENT,
+        FLAG_DATA_CONTENT_HASH, FLAG_DATA_FILE_HASH, ROYALTY_RATE_BASE,
+    },
+    blake2b::blake160,
+    delegation::{Condition, Delegation},
+    difficulty::DifficultyConfig,
+    event::{id::get_leading_zero_bits, Event, EventId},
+    lock_args::{LockArgs, FLAG_ANYONE_CAN_PAY, FLAG_KEY_AND_POW, FLAG_SUMMARY_CONTENT},
+    since::{self, since_satisfied},
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::{HeaderView, ScriptHashType, TransactionView},
+    packed::{CellOutput, Script, WitnessArgs},
+    prelude::*,
+};
+use nostr::hashes::{sha256, Hash};
+
+use crate::{
+    lock::{NOSTR_LOCK_KIND, SESSION_KIND, WARNING},
+    tag, NostrBinding, NostrBindingSdk, NostrLock, ScriptConfig,
+};
+
+const FILE_METADATA_KIND: u16 = 1063;
+
+pub use ckb_nostr_error::ScriptKind;
+
+/// A transaction with everything its scripts load
+#[derive(Debug, Clone)]
+pub struct ResolvedTx {
+    pub tx: TransactionView,
+    /// cells spent by the inputs with their data, in the same order
+    pub inputs: Vec<(CellOutput, Bytes)>,
+    /// cells of the cell deps with their data, dep groups expanded
+    pub cell_deps: Vec<(CellOutput, Bytes)>,
+    pub header_deps: Vec<HeaderView>,
+}
+
+/// What the scripts check, in the order they check it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    LockArgs,
+    LockWitness,
+    /// rule 1
+    SighashAll,
+    /// rule 2
+    UnlockEventId,
+    /// rule 3
+    KindAndContent,
+    Chain,
+    CreatedAtWindow,
+    Recovery,
+    DifficultyConfig,
+    /// rule 4
+    Nonce,
+    /// rule 5
+    TargetDifficulty,
+    /// rule 6
+    PowDifficulty,
+    /// rule 7
+    EmptyPubkey,
+    /// rule 8
+    Pubkey,
+    Delegation,
+    Session,
+    /// rule 9
+    Signature,
+
+    BindingArgs,
+    TypeId,
+    CellData,
+    BoundEvent,
+    BoundEventId,
+    GlobalUniqueId,
+    BoundSignature,
+    MintTo,
+    DataCommitment,
+    Expiration,
+    Royalty,
+}
+
+impl Rule {
+    /// Number of the rule in the nostr lock script specification
+    pub fn number(&self) -> Option<u8> {
+        match self {
+            Rule::SighashAll => Some(1),
+            Rule::UnlockEventId => Some(2),
+            Rule::KindAndContent => Some(3),
+            Rule::Nonce => Some(4),
+            Rule::TargetDifficulty => Some(5),
+            Rule::PowDifficulty => Some(6),
+            Rule::EmptyPubkey => Some(7),
+            Rule::Pubkey => Some(8),
+            Rule::Signature => Some(9),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Rule::LockArgs => "lock args",
+            Rule::LockWitness => "unlock event in witness",
+            Rule::SighashAll => "ckb_sighash_all tag",
+            Rule::UnlockEventId => "event id",
+            Rule::KindAndContent => "kind and content",
+            Rule::Chain => "ckb_chain tag",
+            Rule::CreatedAtWindow => "created_at window",
+            Rule::Recovery => "recovery delay",
+            Rule::DifficultyConfig => "difficulty config",
+            Rule::Nonce => "nonce tag",
+            Rule::TargetDifficulty => "nonce target difficulty",
+            Rule::PowDifficulty => "PoW difficulty",
+            Rule::EmptyPubkey => "empty pubkey hash",
+            Rule::Pubkey => "pubkey hash",
+            Rule::Delegation => "delegation",
+            Rule::Session => "session",
+            Rule::Signature => "signature",
+            Rule::BindingArgs => "binding args",
+            Rule::TypeId => "global unique id",
+            Rule::CellData => "cell data",
+            Rule::BoundEvent => "bound event",
+            Rule::BoundEventId => "bound event id",
+            Rule::GlobalUniqueId => "ckb_global_unique_id tag",
+            Rule::BoundSignature => "bound event signature",
+            Rule::MintTo => "mint lock",
+            Rule::DataCommitment => "data commitment",
+            Rule::Expiration => "expiration",
+            Rule::Royalty => "royalty",
+        };
+        match self.number() {
+            Some(number) => write!(f, "rule {} ({})", number, name),
+            None => write!(f, "{}", name),
+        }
+    }
+}
+
+/// The first rule a script group fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    /// exit code of the script on chain
+    pub error: ScriptError,
+    /// witness of the script group: the first input of a nostr lock group, or
+    /// the output of a minted binding cell
+    pub witness_index: usize,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl Diagnostic {
+    fn new(rule: Rule, error: impl Into<ScriptError>, witness_index: usize) -> Self {
+        Self {
+            rule,
+            error: error.into(),
+            witness_index,
+            expected: None,
+            actual: None,
+        }
+    }
+
+    fn expected(mut self, expected: impl ToString) -> Self {
+        self.expected = Some(expected.to_string());
+        self
+    }
+
+    fn actual(mut self, actual: impl ToString) -> Self {
+        self.actual = Some(actual.to_string());
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of witness {} failed {}, error code {}",
+            self.error.kind(),
+            self.witness_index,
+            self.rule,
+            self.error.code()
+        )?;
+        if let Some(expected) = &self.expected {
+            write!(f, "\n  expected: {}", expected)?;
+        }
+        if let Some(actual) = &self.actual {
+            write!(f, "\n  actual: {}", actual)?;
+        }
+        Ok(())
+    }
+}
+
+type Check = Result<(), Diagnostic>;
+
+impl NostrBindingSdk {
+    /// Run nostr lock and nostr binding mint rules of every script group, and
+    /// report the first failed rule of each group. Empty when all pass.
+    pub fn verify_tx(&self, rtx: &ResolvedTx) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        let mut lock_groups: Vec<(Script, Vec<usize>)> = vec![];
+        for (index, (cell, _)) in rtx.inputs.iter().enumerate() {
+            let lock = cell.lock();
+            if !self.lock.is_nostr_lock(&lock) {
+                continue;
+            }
+            match lock_groups.iter_mut().find(|(script, _)| *script == lock) {
+                Some((_, indexes)) => indexes.push(index),
+                None => lock_groups.push((lock, vec![index])),
+            }
+        }
+        for (script, indexes) in lock_groups {
+            if let Err(diagnostic) = self.verify_lock_group(rtx, &script, &indexes) {
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        let mut binding_groups: Vec<(Script, Vec<usize>, Vec<usize>)> = vec![];
+        let inputs = rtx.inputs.iter().map(|(cell, _)| cell.type_());
+        let outputs = rtx.tx.outputs().into_iter().map(|cell| cell.type_());
+        for (is_input, types) in [
+            (true, inputs.collect::<Vec<_>>()),
+            (false, outputs.collect()),
+        ] {
+            for (index, type_script) in types.into_iter().enumerate() {
+                let Some(type_script) = type_script.to_opt() else {
+                    continue;
+                };
+                if !self.binding.is_binding_type(&type_script) {
+                    continue;
+                }
+                let position = binding_groups
+                    .iter()
+                    .position(|(script, _, _)| *script == type_script)
+                    .unwrap_or_else(|| {
+                        binding_groups.push((type_script, vec![], vec![]));
+                        binding_groups.len() - 1
+                    });
+                let group = &mut binding_groups[position];
+                if is_input {
+                    group.1.push(index);
+                } else {
+                    group.2.push(index);
+                }
+            }
+        }
+        for (script, inputs, outputs) in binding_groups {
+            if let Err(diagnostic) = self.verify_binding_group(rtx, &script, &inputs, &outputs) {
+                diagnostics.push(diagnostic);
+            }
+        }
+        diagnostics
+    }
+
+    /// Decode the exit code in the message of a rejected transaction, e.g. a
+    /// `ckb_error::Error` or a `send_transaction` RPC error. `None` if the
+    /// failed script is neither the nostr lock nor the nostr binding of the
+    /// config.
+    pub fn decode_failure(&self, message: &str) -> Option<ScriptError> {
+        let failure = ScriptFailure::parse(message)?;
+        let code_hash = failure.code_hash?;
+        let kind = if is_script_code(&self.lock.script_config, code_hash) {
+            ScriptKind::NostrLock
+        } else if is_script_code(&self.binding.script_config, code_hash) {
+            ScriptKind::NostrBinding
+        } else {
+            return None;
+        };
+        failure.decode(kind)
+    }
+
+    fn verify_lock_group(&self, rtx: &ResolvedTx, script: &Script, indexes: &[usize]) -> Check {
+        let index = indexes[0];
+        let fail = |rule, error: LockError| Diagnostic::new(rule, error, index);
+
+        let args = LockArgs::parse(&script.args().raw_data())
+            .map_err(|_| fail(Rule::LockArgs, LockError::InvalidScriptArgs))?;
+        let witness = rtx.tx.witnesses().get(index).map(|w| w.raw_data());
+        let witness_args = match &witness {
+            Some(witness) if !witness.is_empty() => Some(
+                WitnessArgs::from_slice(witness)
+                    .map_err(|_| fail(Rule::LockWitness, LockError::Encoding))?,
+            ),
+            _ => None,
+        };
+        let lock = witness_args.as_ref().and_then(|w| w.lock().to_opt());
+        if args.flags & FLAG_ANYONE_CAN_PAY != 0 && lock.is_none() {
+            return Ok(());
+        }
+        let witness_args = witness_args.ok_or_else(|| {
+            let error = if witness.is_none() {
+                LockError::IndexOutOfBound
+            } else {
+                LockError::Encoding
+            };
+            fail(Rule::LockWitness, error)
+        })?;
+        let lock = lock
+            .ok_or_else(|| fail(Rule::LockWitness, LockError::WrongWitnessArgs))?
+            .raw_data();
+
+        // sighash_all is signed with the lock of zeros
+        let dummy_witness = witness_args
+            .as_builder()
+            .lock(Some(Bytes::from(vec![0u8; lock.len()])).pack())
+            .build();
+        let mut witnesses: Vec<_> = rtx.tx.witnesses().into_iter().collect();
+        witnesses[index] = dummy_witness.as_bytes().pack();
+        let dummy_tx = rtx
+            .tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses)
+            .build();
+        let sighash_all = NostrLock::build_sighash_all(&dummy_tx, indexes)
+            .map_err(|_| fail(Rule::LockWitness, LockError::IndexOutOfBound))?;
+        let sighash_all = hex::encode(sighash_all);
+
+        let event = Event::from_json(&lock).map_err(|_| {
+            fail(Rule::LockWitness, LockError::Json).actual(String::from_utf8_lossy(&lock))
+        })?;
+
+        // rule 1
+        let found = event.tags().iter().any(|t| {
+            let t = t.as_vec();
+            t.len() == 2 && t[0] == tag::SIGHASH_ALL && t[1] == sighash_all
+        });
+        if !found {
+            return Err(fail(Rule::SighashAll, LockError::SighashAllMismatched)
+                .expected(&sighash_all)
+                .actual(tag_value(&event, tag::SIGHASH_ALL)));
+        }
+        // rule 2
+        if event.verify_id().is_err() {
+            return Err(fail(Rule::UnlockEventId, LockError::InvalidEventId)
+                .expected(computed_id(&event))
+                .actual(event.id()));
+        }
+        // rule 3
+        if args.content_config.is_none() && event.kind() != NOSTR_LOCK_KIND {
+            return Err(fail(Rule::KindAndContent, LockError::KindMismatched)
+                .expected(NOSTR_LOCK_KIND)
+                .actual(event.kind()));
+        }
+        if let Some(type_hash) = args.content_config {
+            self.verify_content_config(rtx, &event, type_hash, fail)?;
+        } else if args.flags & FLAG_SUMMARY_CONTENT != 0 {
+            self.verify_summary(rtx, &event, fail)?;
+        } else {
+            let content = format!("Signing a CKB transaction\n\n{}\n", WARNING);
+            if event.content() != content {
+                return Err(fail(Rule::KindAndContent, LockError::ContentMismatched)
+                    .expected(content)
+                    .actual(event.content()));
+            }
+        }
+        verify_chain(rtx, &event, fail)?;
+        if let Some(window) = args.created_at_window {
+            let timestamp = proven_timestamp(rtx);
+            if timestamp == 0 {
+                return Err(fail(Rule::CreatedAtWindow, LockError::TimestampNotProven));
+            }
+            let window = window as u64;
+            let created_at = event.created_at();
+            if created_at.saturating_add(window) < timestamp
+                || created_at > timestamp.saturating_add(window)
+            {
+                return Err(fail(Rule::CreatedAtWindow, LockError::CreatedAtOutOfWindow)
+                    .expected(format!(
+                        "{}..={}",
+                        timestamp.saturating_sub(window),
+                        timestamp.saturating_add(window)
+                    ))
+                    .actual(created_at));
+            }
+        }
+
+        if let Some(recovery) = &args.recovery {
+            if blake160(event.author().as_slice()) == recovery.pubkey_hash {
+                for &input in indexes {
+                    let since: u64 = rtx.tx.inputs().get(input).unwrap().since().unpack();
+                    if !since_satisfied(since, recovery.delay) {
+                        return Err(fail(Rule::Recovery, LockError::RecoveryDelayNotReached)
+                            .expected(format!("since {:#x}", recovery.delay))
+                            .actual(format!("since {:#x} of input {}", since, input)));
+                    }
+                }
+                return self.verify_key(rtx, script, indexes, &event, recovery.pubkey_hash);
+            }
+        }
+        let pow_difficulty = match args.difficulty_config {
+            Some(type_hash) => load_difficulty(rtx, type_hash, fail)?.max(args.pow_difficulty),
+            None => args.pow_difficulty,
+        };
+        if args.flags & FLAG_KEY_AND_POW != 0 {
+            if pow_difficulty == 0 {
+                return Err(fail(
+                    Rule::PowDifficulty,
+                    LockError::KeyAndPowDifficultyZero,
+                ));
+            }
+            let validated = check_pow(&event, pow_difficulty, true, fail)?;
+            if !validated {
+                return Err(fail(Rule::Nonce, LockError::KeyAndPowNonceNotFound));
+            }
+            self.verify_key(rtx, script, indexes, &event, args.pubkey_hash)
+        } else if pow_difficulty == 0 {
+            self.verify_key(rtx, script, indexes, &event, args.pubkey_hash)
+        } else {
+            let validated = check_pow(&event, pow_difficulty, false, fail)?;
+            // rule 7
+            if args.pubkey_hash != [0u8; 20] {
+                return Err(fail(Rule::EmptyPubkey, LockError::PubkeyNotEmpty)
+                    .expected(hex::encode([0u8; 20]))
+                    .actual(hex::encode(args.pubkey_hash)));
+            }
+            if validated {
+                Ok(())
+            } else {
+                Err(fail(Rule::Nonce, LockError::NonceNotFound))
+            }
+        }
+    }
+
+    fn verify_content_config(
+        &self,
+        rtx: &ResolvedTx,
+        event: &Event,
+        type_hash: [u8; 32],
+        fail: impl Fn(Rule, LockError) -> Diagnostic,
+    ) -> Check {
+        let (_, data) = find_by_type_hash(&rtx.cell_deps, type_hash).ok_or_else(|| {
+            fail(Rule::KindAndContent, LockError::ContentConfigNotFound)
+                .expected(format!("cell dep of type hash {}", hex::encode(type_hash)))
+        })?;
+        if data.len() != 34 {
+            return Err(fail(Rule::KindAndContent, LockError::InvalidContentConfig)
+                .expected("34 bytes")
+                .actual(format!("{} bytes", data.len())));
+        }
+        let kind = u16::from_le_bytes([data[0], data[1]]);
+        if event.kind() != kind {
+            return Err(fail(Rule::KindAndContent, LockError::KindMismatched)
+                .expected(kind)
+                .actual(event.kind()));
+        }
+        let content_hash = sha256::Hash::hash(event.content().as_bytes());
+        if content_hash.as_byte_array()[..] != data[2..] {
+            return Err(fail(Rule::KindAndContent, LockError::ContentMismatched)
+                .expected(format!("sha256 {}", hex::encode(&data[2..])))
+                .actual(format!("sha256 {}", content_hash)));
+        }
+        Ok(())
+    }
+
+    fn verify_summary(
+        &self,
+        rtx: &ResolvedTx,
+        event: &Event,
+        fail: impl Fn(Rule, LockError) -> Diagnostic,
+    ) -> Check {
+        let fail = || fail(Rule::KindAndContent, LockError::SummaryMismatched);
+        // the address prefix is taken from the first line of content
+        let prefix = match event
+            .content()
+            .strip_prefix("Signing a CKB transaction on ")
+            .and_then(|rest| rest.get(0..3))
+        {
+            Some("ckb") => "ckb",
+            Some("ckt") => "ckt",
+            _ => return Err(fail().actual(event.content())),
+        };
+        let lock = NostrLock::new(self.lock.script_config.clone(), prefix);
+        let input_cells: Vec<CellOutput> = rtx.inputs.iter().map(|(c, _)| c.clone()).collect();
+        let summary = lock
+            .build_tx_summary(&rtx.tx, &input_cells)
+            .map_err(|_| fail())?;
+        if event.content() != summary {
+            return Err(fail().expected(summary).actual(event.content()));
+        }
+        Ok(())
+    }
+
+    // rules 8 and 9
+    fn verify_key(
+        &self,
+        rtx: &ResolvedTx,
+        script: &Script,
+        indexes: &[usize],
+        event: &Event,
+        pubkey_hash: [u8; 20],
+    ) -> Check {
+        let index = indexes[0];
+        let fail = |rule, error: LockError| Diagnostic::new(rule, error, index);
+        let author_hash = blake160(event.author().as_slice());
+        if author_hash != pubkey_hash {
+            if event.get_tag_content(tag::SESSION.to_string()).is_some() {
+                verify_session(rtx, script, indexes, event, pubkey_hash, fail)?;
+            } else {
+                verify_delegation(rtx, event, pubkey_hash, author_hash, fail)?;
+            }
+        }
+        event.verify_signature().map_err(|err| {
+            fail(Rule::Signature, LockError::from(err))
+                .expected(format!("signature of {}", event.author().to_hex()))
+                .actual(event.signature())
+        })
+    }
+
+    fn verify_binding_group(
+        &self,
+        rtx: &ResolvedTx,
+        script: &Script,
+        inputs: &[usize],
+        outputs: &[usize],
+    ) -> Check {
+        let index = outputs
+            .first()
+            .or(inputs.first())
+            .copied()
+            .unwrap_or_default();
+        let fail = |rule, error: BindingError| Diagnostic::new(rule, error, index);
+
+        let args = script.args().raw_data();
+        let flags = match args.len() {
+            64 => 0,
+            65 => args[64],
+            len => {
+                return Err(fail(Rule::BindingArgs, BindingError::WrongArgsLength)
+                    .expected("64 or 65 bytes")
+                    .actual(format!("{} bytes", len)))
+            }
+        };
+        if flags & !FLAGS_ALL != 0 || (flags & FLAGS_IMMUTABLE_DATA).count_ones() > 1 {
+            return Err(fail(Rule::BindingArgs, BindingError::InvalidFlags)
+                .actual(format!("{:#04x}", flags)));
+        }
+        if inputs.len() > 1 || outputs.len() > 1 {
+            return Err(fail(Rule::TypeId, BindingError::TooManyTypeIdCell)
+                .expected("at most 1 input and 1 output")
+                .actual(format!(
+                    "{} inputs and {} outputs",
+                    inputs.len(),
+                    outputs.len()
+                )));
+        }
+        if !inputs.is_empty() {
+            return Ok(());
+        }
+
+        // mint a new binding cell
+        let first_input = rtx
+            .tx
+            .inputs()
+            .get(0)
+            .ok_or_else(|| fail(Rule::TypeId, BindingError::IndexOutOfBound))?;
+        let global_unique_id = hex::encode(NostrBinding::build_global_unique_id(
+            &first_input,
+            index as u64,
+        ));
+        if hex::encode(&args[32..64]) != global_unique_id {
+            return Err(fail(Rule::TypeId, BindingError::TypeIdNotMatch)
+                .expected(&global_unique_id)
+                .actual(hex::encode(&args[32..64])));
+        }
+
+        let data = rtx.tx.outputs_data().get(index).unwrap().raw_data();
+        let binding_data = BindingData::parse(flags, &data)
+            .ok_or_else(|| fail(Rule::CellData, BindingError::InvalidCellData))?;
+        let event = if flags & FLAG_ARCHIVE != 0 {
+            let event = Event::from_json(binding_data.payload).map_err(|_| {
+                fail(Rule::BoundEvent, BindingError::Json)
+                    .actual(String::from_utf8_lossy(binding_data.payload))
+            })?;
+            if event.as_canonical_json() != binding_data.payload {
+                return Err(fail(Rule::BoundEvent, BindingError::NonCanonicalEvent)
+                    .expected(String::from_utf8_lossy(&event.as_canonical_json()))
+                    .actual(String::from_utf8_lossy(binding_data.payload)));
+            }
+            event
+        } else {
+            let witness = rtx
+                .tx
+                .witnesses()
+                .get(index)
+                .ok_or_else(|| fail(Rule::BoundEvent, BindingError::IndexOutOfBound))?
+                .raw_data();
+            let witness_args = WitnessArgs::from_slice(&witness)
+                .map_err(|_| fail(Rule::BoundEvent, BindingError::Encoding))?;
+            let json = witness_args
+                .output_type()
+                .to_opt()
+                .ok_or_else(|| fail(Rule::BoundEvent, BindingError::WitnessNotExisting))?
+                .raw_data();
+            Event::from_json(&json).map_err(|_| {
+                fail(Rule::BoundEvent, BindingError::Json).actual(String::from_utf8_lossy(&json))
+            })?
+        };
+        if event.verify_id().is_err() {
+            return Err(fail(Rule::BoundEventId, BindingError::InvalidEventId)
+                .expected(computed_id(&event))
+                .actual(event.id()));
+        }
+        if event.id().as_bytes() != &args[0..32] {
+            return Err(fail(Rule::BoundEventId, BindingError::InvalidEventId)
+                .expected(hex::encode(&args[0..32]))
+                .actual(event.id()));
+        }
+        let found = event.tags().iter().any(|t| {
+            let t = t.as_vec();
+            t.len() == 2 && t[0] == tag::GLOBAL_UNIQUE_ID && t[1] == global_unique_id
+        });
+        if !found {
+            return Err(
+                fail(Rule::GlobalUniqueId, BindingError::GlobalUniqueIdNotFound)
+                    .expected(&global_unique_id)
+                    .actual(tag_value(&event, tag::GLOBAL_UNIQUE_ID)),
+            );
+        }
+        event.verify_signature().map_err(|err| {
+            fail(Rule::BoundSignature, BindingError::from(err))
+                .expected(format!("signature of {}", event.author().to_hex()))
+                .actual(event.signature())
+        })?;
+
+        let lock = rtx.tx.outputs().get(index).unwrap().lock();
+        verify_mint_to(&event, &lock, fail)?;
+        verify_data_commitment(&event, flags, binding_data.payload, fail)?;
+        if let Some(expiration) = binding_data.expiration {
+            let value = tag_value(&event, tag::EXPIRATION);
+            match value.parse::<u64>() {
+                Err(_) => {
+                    return Err(fail(Rule::Expiration, BindingError::ExpirationNotFound)
+                        .expected(expiration)
+                        .actual(value))
+                }
+                Ok(value) if value != expiration => {
+                    return Err(fail(Rule::Expiration, BindingError::ExpirationMismatched)
+                        .expected(expiration)
+                        .actual(value))
+                }
+                _ => {}
+            }
+        }
+        if let Some(royalty) = binding_data.royalty {
+            verify_royalty(&event, royalty, fail)?;
+        }
+        Ok(())
+    }
+}
+
+fn verify_chain(
+    rtx: &ResolvedTx,
+    event: &Event,
+    fail: impl Fn(Rule, LockError) -> Diagnostic,
+) -> Check {
+    let genesis_hashes: Vec<String> = rtx
+        .header_deps
+        .iter()
+        .filter(|header| header.number() == 0)
+        .map(|header| hex::encode(header.hash().as_slice()))
+        .collect();
+    for tag in event.tags() {
+        let entries = tag.as_vec();
+        if entries.len() != 2 || entries[0] != tag::CHAIN {
+            continue;
+        }
+        if !genesis_hashes.contains(&entries[1]) {
+            return Err(fail(Rule::Chain, LockError::ChainMismatched)
+                .expected(format!("genesis header {} in header deps", entries[1]))
+                .actual(format!("genesis headers [{}]", genesis_hashes.join(", "))));
+        }
+    }
+    Ok(())
+}
+
+fn load_difficulty(
+    rtx: &ResolvedTx,
+    type_hash: [u8; 32],
+    fail: impl Fn(Rule, LockError) -> Diagnostic,
+) -> Result<u8, Diagnostic> {
+    // the config cell in inputs, every claim records itself by updating it
+    let (_, data) = find_by_type_hash(&rtx.inputs, type_hash).ok_or_else(|| {
+        fail(Rule::DifficultyConfig, LockError::DifficultyConfigNotFound)
+            .expected(format!("input of type hash {}", hex::encode(type_hash)))
+    })?;
+    DifficultyConfig::parse(data)
+        .map(|config| config.difficulty)
+        .map_err(|_| fail(Rule::DifficultyConfig, LockError::InvalidDifficultyConfig))
+}
+
+// rules 4 to 6, return whether a `nonce` tag is found
+fn check_pow(
+    event: &Event,
+    pow_difficulty: u8,
+    key_and_pow: bool,
+    fail: impl Fn(Rule, LockError) -> Diagnostic,
+) -> Result<bool, Diagnostic> {
+    let (wrong_target, difficulty) = if key_and_pow {
+        (
+            LockError::KeyAndPowWrongTargetDifficulty,
+            LockError::KeyAndPowDifficulty,
+        )
+    } else {
+        (LockError::WrongTargetDifficulty, LockError::PoWDifficulty)
+    };
+    let mut validated = false;
+    for tag in event.tags() {
+        let entries = tag.as_vec();
+        // rule 4
+        if entries.len() != 3 || entries[0] != tag::NONCE {
+            continue;
+        }
+        // rule 5
+        if entries[2] != pow_difficulty.to_string() {
+            return Err(fail(Rule::TargetDifficulty, wrong_target)
+                .expected(pow_difficulty)
+                .actual(&entries[2]));
+        }
+        // rule 6
+        if !event.check_pow(pow_difficulty) {
+            return Err(fail(Rule::PowDifficulty, difficulty)
+                .expected(format!("{} leading zero bits", pow_difficulty))
+                .actual(format!(
+                    "{} leading zero bits",
+                    get_leading_zero_bits(event.id().as_bytes())
+                )));
+        }
+        validated = true;
+    }
+    Ok(validated)
+}
+
+fn verify_delegation(
+    rtx: &ResolvedTx,
+    event: &Event,
+    pubkey_hash: [u8; 20],
+    author_hash: [u8; 20],
+    fail: impl Fn(Rule, LockError) -> Diagnostic,
+) -> Check {
+    let Some(delegation) = Delegation::from_event(event) else {
+        // rule 8
+        return Err(fail(Rule::Pubkey, LockError::PubkeyNotFound)
+            .expected(hex::encode(pubkey_hash))
+            .actual(hex::encode(author_hash)));
+    };
+    let delegation =
+        delegation.map_err(|_| fail(Rule::Delegation, LockError::InvalidDelegation))?;
+    let delegator_hash = blake160(delegation.delegator().as_slice());
+    if delegator_hash != pubkey_hash {
+        return Err(fail(Rule::Pubkey, LockError::PubkeyNotFound)
+            .expected(hex::encode(pubkey_hash))
+            .actual(format!("delegator {}", hex::encode(delegator_hash))));
+    }
+    delegation
+        .verify_token(event.author_ref())
+        .map_err(|err| fail(Rule::Delegation, LockError::from(err)))?;
+    let conditions = delegation
+        .conditions()
+        .map_err(|err| fail(Rule::Delegation, LockError::from(err)))?;
+    // `created_at` is picked by the delegatee, so time conditions must also
+    // hold for the time the transaction proves
+    let timestamp = if conditions.iter().any(|c| !matches!(c, Condition::Kind(_))) {
+        match proven_timestamp(rtx) {
+            0 => return Err(fail(Rule::Delegation, LockError::TimestampNotProven)),
+            timestamp => timestamp,
+        }
+    } else {
+        0
+    };
+    if !conditions.contains(&Condition::Kind(event.kind()))
+        || !conditions.iter().all(|c| {
+            c.evaluate(event.kind(), event.created_at()) && c.evaluate(event.kind(), timestamp)
+        })
+    {
+        return Err(
+            fail(Rule::Delegation, LockError::DelegationConditionsNotMet)
+                .expected(format!("conditions granting kind={}", event.kind()))
+                .actual(format!("{:?}", conditions)),
+        );
+    }
+    Ok(())
+}
+
+fn verify_session(
+    rtx: &ResolvedTx,
+    script: &Script,
+    indexes: &[usize],
+    event: &Event,
+    pubkey_hash: [u8; 20],
+    fail: impl Fn(Rule, LockError) -> Diagnostic,
+) -> Check {
+    let invalid = || fail(Rule::Session, LockError::InvalidSession);
+    let json = event.get_tag_content(tag::SESSION.to_string()).unwrap();
+    let auth = Event::from_json(json.as_bytes())
+        .map_err(|err| fail(Rule::Session, LockError::from(err)))?;
+    auth.verify_id()
+        .map_err(|err| fail(Rule::Session, LockError::from(err)))?;
+    if auth.kind() != SESSION_KIND {
+        return Err(invalid().expected(SESSION_KIND).actual(auth.kind()));
+    }
+    let owner_hash = blake160(auth.author().as_slice());
+    if owner_hash != pubkey_hash {
+        return Err(fail(Rule::Pubkey, LockError::PubkeyNotFound)
+            .expected(hex::encode(pubkey_hash))
+            .actual(format!("session owner {}", hex::encode(owner_hash))));
+    }
+    auth.verify_signature()
+        .map_err(|err| fail(Rule::Session, LockError::from(err)))?;
+
+    let session_pubkey = auth
+        .get_tag_content(tag::SESSION_PUBKEY.to_string())
+        .ok_or_else(invalid)?;
+    if session_pubkey != event.author().to_hex() {
+        return Err(invalid()
+            .expected(session_pubkey)
+            .actual(event.author().to_hex()));
+    }
+    let parse_tag = |name: &str| {
+        auth.get_tag_content(name.to_string())
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(invalid)
+    };
+    let expiration = parse_tag(tag::EXPIRATION)?;
+    let timestamp = match proven_timestamp(rtx) {
+        0 => return Err(fail(Rule::Session, LockError::TimestampNotProven)),
+        timestamp => event.created_at().max(timestamp),
+    };
+    if timestamp >= expiration {
+        return Err(fail(Rule::Session, LockError::SessionExpired)
+            .expected(format!("before {}", expiration))
+            .actual(timestamp));
+    }
+    let max_capacity = parse_tag(tag::MAX_CAPACITY)?;
+    let allowed_locks = auth.get_tags_content(tag::ALLOWED_LOCK.to_string());
+
+    // outputs can only go back to current lock or to allowed locks
+    let mut returned = 0u64;
+    for (index, output) in rtx.tx.outputs().into_iter().enumerate() {
+        let capacity: u64 = output.capacity().unpack();
+        if output.lock() == *script {
+            returned = returned.saturating_add(capacity);
+            continue;
+        }
+        let lock_hash = hex::encode(output.lock().calc_script_hash().as_slice());
+        if !allowed_locks.contains(&lock_hash.as_str()) {
+            return Err(fail(Rule::Session, LockError::SessionLockNotAllowed)
+                .expected(format!("allowed locks [{}]", allowed_locks.join(", ")))
+                .actual(format!("lock {} of output {}", lock_hash, index)));
+        }
+    }
+    let spent = indexes.iter().fold(0u64, |sum, &index| {
+        let capacity: u64 = rtx.inputs[index].0.capacity().unpack();
+        sum.saturating_add(capacity)
+    });
+    let spent = spent.saturating_sub(returned);
+    if spent > max_capacity {
+        return Err(fail(Rule::Session, LockError::SessionCapacityExceeded)
+            .expected(format!("at most {} shannons", max_capacity))
+            .actual(format!("{} shannons", spent)));
+    }
+    Ok(())
+}
+
+fn verify_mint_to(
+    event: &Event,
+    lock: &Script,
+    fail: impl Fn(Rule, BindingError) -> Diagnostic,
+) -> Check {
+    let lock_hash = hex::encode(lock.calc_script_hash().as_slice());
+    let mut allowed = vec![];
+    for tag in event.tags() {
+        let tag = tag.as_vec();
+        if tag.len() != 2 {
+            continue;
+        }
+        let matched = if tag[0] == tag::MINT_TO {
+            tag[1] == lock_hash
+        } else if tag[0] == tag::MINT_TO_AUTHOR {
+            is_author_lock(event, lock, &tag[1])
+        } else {
+            continue;
+        };
+        if matched {
+            return Ok(());
+        }
+        allowed.push(format!("{} {}", tag[0], tag[1]));
+    }
+    if allowed.is_empty() {
+        Ok(())
+    } else {
+        Err(fail(Rule::MintTo, BindingError::MintLockMismatched)
+            .expected(allowed.join(", "))
+            .actual(format!("lock hash {}", lock_hash)))
+    }
+}
+
+// the author's nostr lock: code hash named by the tag, type hash type, and
+// args in any format owned by the blake160 of author
+fn is_author_lock(event: &Event, lock: &Script, code_hash: &str) -> bool {
+    lock.hash_type() == ScriptHashType::Type.into()
+        && hex::encode(lock.code_hash().as_slice()) == code_hash
+        && LockArgs::parse(&lock.args().raw_data()).map_or(false, |args| {
+            args.is_owned_by(&blake160(event.author().as_slice()))
+        })
+}
+
+fn verify_data_commitment(
+    event: &Event,
+    flags: u8,
+    data: &[u8],
+    fail: impl Fn(Rule, BindingError) -> Diagnostic,
+) -> Check {
+    let expected = if flags & FLAG_DATA_CONTENT != 0 {
+        event.content().as_bytes().to_vec()
+    } else if flags & FLAG_DATA_CONTENT_HASH != 0 {
+        sha256::Hash::hash(event.content().as_bytes())
+            .as_byte_array()
+            .to_vec()
+    } else if flags & FLAG_DATA_FILE_HASH != 0 {
+        let file_hash = event
+            .tags()
+            .iter()
+            .map(|t| t.as_vec())
+            .find(|t| t.len() >= 2 && t[0] == tag::FILE_HASH)
+            .and_then(|t| hex::decode(&t[1]).ok())
+            .filter(|hash| hash.len() == 32 && event.kind() == FILE_METADATA_KIND);
+        file_hash.ok_or_else(|| {
+            fail(Rule::DataCommitment, BindingError::FileHashNotFound)
+                .expected(format!("x tag of a kind {} event", FILE_METADATA_KIND))
+        })?
+    } else {
+        return Ok(());
+    };
+    if data != expected {
+        return Err(
+            fail(Rule::DataCommitment, BindingError::DataCommitmentMismatched)
+                .expected(hex::encode(expected))
+                .actual(hex::encode(data)),
+        );
+    }
+    Ok(())
+}
+
+fn verify_royalty(
+    event: &Event,
+    royalty: Royalty,
+    fail: impl Fn(Rule, BindingError) -> Diagnostic,
+) -> Check {
+    let tag = event
+        .tags()
+        .iter()
+        .map(|t| t.as_vec())
+        .find(|t| t.len() >= 3 && t[0] == tag::ROYALTY)
+        .ok_or_else(|| {
+            fail(Rule::Royalty, BindingError::RoyaltyNotFound).expected(royalty.to_string())
+        })?;
+    let mismatched = || {
+        fail(Rule::Royalty, BindingError::RoyaltyMismatched)
+            .expected(royalty.to_string())
+            .actual(tag[1..].join(" "))
+    };
+    let mut recipient = [0u8; 32];
+    hex::decode_to_slice(&tag[1], &mut recipient).map_err(|_| mismatched())?;
+    let rate: u16 = tag[2]
+        .parse()
+        .map_err(|_| fail(Rule::Royalty, BindingError::InvalidRoyaltyRate).actual(&tag[2]))?;
+    let udt_type_hash = match tag.get(3) {
+        Some(hash) => {
+            let mut udt_type_hash = [0u8; 32];
+            hex::decode_to_slice(hash, &mut udt_type_hash).map_err(|_| mismatched())?;
+            Some(udt_type_hash)
+        }
+        None => None,
+    };
+    if rate > ROYALTY_RATE_BASE {
+        return Err(fail(Rule::Royalty, BindingError::InvalidRoyaltyRate)
+            .expected(format!("at most {}", ROYALTY_RATE_BASE))
+            .actual(rate));
+    }
+    let declared = Royalty {
+        recipient,
+        rate,
+        udt_type_hash,
+    };
+    if declared != royalty {
+        return Err(mismatched());
+    }
+    Ok(())
+}
+
+fn find_by_type_hash(
+    cells: &[(CellOutput, Bytes)],
+    type_hash: [u8; 32],
+) -> Option<&(CellOutput, Bytes)> {
+    cells.iter().find(|(cell, _)| {
+        cell.type_().to_opt().map_or(false, |type_script| {
+            type_script.calc_script_hash().as_slice() == type_hash
+        })
+    })
+}
+
+// the latest timestamp in seconds proven by header deps and absolute since of
+// inputs, 0 when there is no such proof
+fn proven_timestamp(rtx: &ResolvedTx) -> u64 {
+    since::proven_timestamp(
+        rtx.header_deps.iter().map(|header| header.timestamp()),
+        rtx.tx
+            .inputs()
+            .into_iter()
+            .map(|input| input.since().unpack()),
+    )
+}
+
+fn tag_value(event: &Event, name: &str) -> String {
+    event
+        .get_tag_content(name.to_string())
+        .unwrap_or("none")
+        .to_string()
+}
+
+fn computed_id(event: &Event) -> EventId {
+    EventId::new(
+        event.author_ref(),
+        &event.created_at(),
+        &event.kind(),
+        event.tags(),
+        event.content(),
+    )
+}
+
+fn is_script_code(config: &ScriptConfig, code_hash: CodeHash) -> bool {
+    match code_hash {
+        CodeHash::Type(hash) => {
+            config.hash_type == ScriptHashType::Type && config.code_hash.0 == hash
+        }
+        CodeHash::Data(hash) => {
+            config.hash_type != ScriptHashType::Type && config.code_hash.0 == hash
+        }
+    }
+}
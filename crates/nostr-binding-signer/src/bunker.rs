@@ -0,0 +1,175 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use nostr::{
+    nips::nip46::{Message, Request, ResponseResult},
+    Keys, PublicKey,
+};
+use nostr_binding_relay::{client::DEFAULT_TIMEOUT, RelayClient};
+
+use crate::{error::Error, transport};
+
+const SUBSCRIPTION_ID: &str = "bunker";
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A remote signer holding `keys` in memory and answering NIP-46 requests on
+/// a relay from a thread, so that remote signing can be tested on localhost.
+/// It signs with the same key it talks with, and stops on drop.
+pub struct MockBunker {
+    keys: Keys,
+    relay_url: String,
+    secret: Option<String>,
+    approve: Arc<AtomicBool>,
+    requests: Arc<Mutex<Vec<Request>>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockBunker {
+    /// Serve on the relay at `relay_url`. Clients must connect with `secret`
+    /// when it is set.
+    pub fn spawn(relay_url: &str, keys: Keys, secret: Option<String>) -> Result<Self, Error> {
+        let mut client = RelayClient::connect(relay_url)?;
+        transport::subscribe(
+            &mut client,
+            SUBSCRIPTION_ID,
+            &keys.public_key(),
+            DEFAULT_TIMEOUT,
+        )?;
+
+        let approve = Arc::new(AtomicBool::new(true));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut session = Session {
+            client,
+            keys: keys.clone(),
+            secret: secret.clone(),
+            approve: approve.clone(),
+            requests: requests.clone(),
+            connected: HashSet::new(),
+        };
+        let stopped = stop.clone();
+        let handle = thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                match session.client.next_event(SUBSCRIPTION_ID, POLL_INTERVAL) {
+                    Ok(event) => {
+                        if let Some((sender, message)) = transport::open(&session.keys, &event) {
+                            session.handle(sender, message);
+                        }
+                    }
+                    Err(nostr_binding_relay::Error::Timeout) => {}
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Ok(Self {
+            keys,
+            relay_url: relay_url.to_string(),
+            secret,
+            approve,
+            requests,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// `bunker://` URI to connect to the bunker with
+    pub fn uri(&self) -> String {
+        let mut uri = format!(
+            "bunker://{}?relay={}",
+            self.keys.public_key().to_hex(),
+            self.relay_url
+        );
+        if let Some(secret) = &self.secret {
+            uri.push_str(&format!("&secret={}", secret));
+        }
+        uri
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.keys.public_key()
+    }
+
+    /// Approve or refuse the following `sign_event` requests, like the user
+    /// of a bunker would
+    pub fn set_approve(&self, approve: bool) {
+        self.approve.store(approve, Ordering::SeqCst);
+    }
+
+    /// The requests received so far
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockBunker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+struct Session {
+    client: RelayClient,
+    keys: Keys,
+    secret: Option<String>,
+    approve: Arc<AtomicBool>,
+    requests: Arc<Mutex<Vec<Request>>>,
+    connected: HashSet<PublicKey>,
+}
+
+impl Session {
+    fn handle(&mut self, sender: PublicKey, message: Message) {
+        let Message::Request { id, req } = message else {
+            return;
+        };
+        self.requests.lock().unwrap().push(req.clone());
+        let response = match self.answer(sender, req) {
+            Ok(result) => Message::response(id, Some(result), None),
+            Err(error) => Message::response(id, None, Some(error)),
+        };
+        // a client gone is not the bunker's concern
+        let _ = transport::send(&mut self.client, &self.keys, sender, response);
+    }
+
+    fn answer(&mut self, sender: PublicKey, request: Request) -> Result<ResponseResult, String> {
+        if let Request::Connect { public_key, secret } = request {
+            if public_key != self.keys.public_key() {
+                return Err("wrong signer".into());
+            }
+            if self.secret.is_some() && secret != self.secret {
+                return Err("invalid secret".into());
+            }
+            self.connected.insert(sender);
+            return Ok(ResponseResult::Connect);
+        }
+        if !self.connected.contains(&sender) {
+            return Err("not connected".into());
+        }
+        match request {
+            Request::GetPublicKey => Ok(ResponseResult::GetPublicKey(self.keys.public_key())),
+            Request::SignEvent(unsigned) => {
+                if !self.approve.load(Ordering::SeqCst) {
+                    return Err("denied".into());
+                }
+                if unsigned.pubkey != self.keys.public_key() {
+                    return Err("wrong pubkey".into());
+                }
+                let event = unsigned.sign(&self.keys).map_err(|err| err.to_string())?;
+                Ok(ResponseResult::SignEvent(Box::new(event)))
+            }
+            Request::Ping => Ok(ResponseResult::Pong),
+            request => Err(format!("unsupported method {}", request.method())),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use core::fmt;
+
+use nostr::nips::nip46;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Error from the relay connection, e.g. no response in time
+    Relay(nostr_binding_relay::Error),
+    /// Error from the SDK, e.g. a malformed witness
+    Sdk(nostr_binding_sdk::Error),
+    /// Malformed bunker URI or NIP-46 message
+    Nip46(nip46::Error),
+    /// Error from encrypting, decrypting or converting a message event
+    Nostr(String),
+    /// The URI is not a bunker URI with a relay
+    InvalidUri(String),
+    /// The remote signer refused the request, with its reason
+    Rejected(String),
+    /// The remote signer asks to authorize the client at the URL first
+    AuthRequired(String),
+    /// The response doesn't answer the request, e.g. a signed event other
+    /// than the requested one
+    UnexpectedResponse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Relay(err) => write!(f, "relay: {}", err),
+            Error::Sdk(err) => write!(f, "{}", err),
+            Error::Nip46(err) => write!(f, "nip46: {}", err),
+            Error::Nostr(reason) => write!(f, "nostr: {}", reason),
+            Error::InvalidUri(reason) => write!(f, "invalid bunker uri: {}", reason),
+            Error::Rejected(reason) => write!(f, "remote signer refused: {}", reason),
+            Error::AuthRequired(url) => write!(f, "authorize the client at {}", url),
+            Error::UnexpectedResponse(reason) => write!(f, "unexpected response: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<nostr_binding_relay::Error> for Error {
+    fn from(err: nostr_binding_relay::Error) -> Self {
+        Error::Relay(err)
+    }
+}
+
+impl From<nostr_binding_sdk::Error> for Error {
+    fn from(err: nostr_binding_sdk::Error) -> Self {
+        Error::Sdk(err)
+    }
+}
+
+impl From<nip46::Error> for Error {
+    fn from(err: nip46::Error) -> Self {
+        Error::Nip46(err)
+    }
+}
@@ -0,0 +1,14 @@
+//! NIP-46 remote signing of nostr lock unlock events. [`RemoteSigner`] sends
+//! the unlock event of a prepared transaction to a bunker over a relay, waits
+//! for the signed event and puts it into the witness, so the owner key never
+//! leaves the bunker. [`MockBunker`] answers the requests from memory for
+//! tests against a local relay.
+
+pub mod bunker;
+pub mod error;
+pub mod remote;
+mod transport;
+
+pub use bunker::MockBunker;
+pub use error::Error;
+pub use remote::RemoteSigner;
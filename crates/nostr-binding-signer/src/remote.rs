@@ -0,0 +1,170 @@
+use std::time::{Duration, Instant};
+
+use ckb_types::core::TransactionView;
+use nostr::{
+    nips::nip46::{Message, NostrConnectURI, Request, ResponseResult},
+    Event, EventBuilder, EventId, Keys, PublicKey,
+};
+use nostr_binding_relay::RelayClient;
+use nostr_binding_sdk::NostrLock;
+
+use crate::{error::Error, transport};
+
+const SUBSCRIPTION_ID: &str = "nip46";
+
+/// How long requests wait for the remote signer, which may ask its user to
+/// approve them
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A NIP-46 client of a remote signer, e.g. a bunker holding the owner key of
+/// nostr lock cells. Requests are sent over the relay of the bunker URI with
+/// `keys`, the local key of the client, which never signs unlock events.
+pub struct RemoteSigner {
+    client: RelayClient,
+    keys: Keys,
+    signer_public_key: PublicKey,
+    public_key: PublicKey,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    /// Connect to the remote signer of `uri`,
+    /// `bunker://<signer pubkey>?relay=<url>&secret=<secret>`, and get the
+    /// public key it signs with
+    pub fn connect(uri: &str, keys: Keys) -> Result<Self, Error> {
+        Self::connect_with_timeout(uri, keys, DEFAULT_TIMEOUT)
+    }
+
+    pub fn connect_with_timeout(uri: &str, keys: Keys, timeout: Duration) -> Result<Self, Error> {
+        let uri = NostrConnectURI::parse(uri)?;
+        let signer_public_key = uri
+            .signer_public_key()
+            .ok_or_else(|| Error::InvalidUri("not a bunker uri".into()))?;
+        let relay = uri
+            .relays()
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::InvalidUri("no relay".into()))?;
+
+        let mut client = RelayClient::connect(relay.as_str())?;
+        transport::subscribe(&mut client, SUBSCRIPTION_ID, &keys.public_key(), timeout)?;
+        let mut signer = Self {
+            client,
+            keys,
+            signer_public_key,
+            // the signer key until the signer tells the user key
+            public_key: signer_public_key,
+            timeout,
+        };
+        signer
+            .request(Request::Connect {
+                public_key: signer_public_key,
+                secret: uri.secret(),
+            })?
+            .to_connect()?;
+        signer.public_key = signer.request(Request::GetPublicKey)?.to_get_public_key()?;
+        Ok(signer)
+    }
+
+    /// The key events are signed with, the owner key of the nostr lock
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    pub fn signer_public_key(&self) -> PublicKey {
+        self.signer_public_key
+    }
+
+    /// Have the remote signer sign the event of `builder`, and check it signed
+    /// that event with the user key
+    pub fn sign_event(&mut self, builder: EventBuilder) -> Result<Event, Error> {
+        let unsigned = builder.to_unsigned_event(self.public_key);
+        let id = EventId::new(
+            &unsigned.pubkey,
+            &unsigned.created_at,
+            &unsigned.kind,
+            &unsigned.tags,
+            &unsigned.content,
+        );
+        let event = self
+            .request(Request::SignEvent(unsigned))?
+            .to_sign_event()?;
+        if event.id != id || event.pubkey != self.public_key {
+            return Err(Error::UnexpectedResponse(format!(
+                "signed event {} instead of {}",
+                event.id, id
+            )));
+        }
+        event
+            .verify()
+            .map_err(|err| Error::UnexpectedResponse(err.to_string()))?;
+        Ok(event)
+    }
+
+    /// `NostrLock::sign_tx` with the unlock event signed by the remote signer
+    pub fn sign_tx(
+        &mut self,
+        lock: &NostrLock,
+        tx: TransactionView,
+        lock_indexes: &[usize],
+        content: &str,
+    ) -> Result<TransactionView, Error> {
+        let tx = lock.prepare_tx(tx, lock_indexes, content)?;
+        self.sign_prepared_tx(lock, tx, lock_indexes, content)
+    }
+
+    /// `NostrLock::sign_prepared_tx` with the unlock event signed by the
+    /// remote signer
+    pub fn sign_prepared_tx(
+        &mut self,
+        lock: &NostrLock,
+        tx: TransactionView,
+        lock_indexes: &[usize],
+        content: &str,
+    ) -> Result<TransactionView, Error> {
+        // the SDK error can't carry the remote one, which is kept aside
+        let mut remote_error = None;
+        lock.sign_prepared_tx(tx, lock_indexes, content, |builder| {
+            self.sign_event(builder).map_err(|err| {
+                let message = err.to_string();
+                remote_error = Some(err);
+                nostr_binding_sdk::Error::Nostr(message)
+            })
+        })
+        .map_err(|err| remote_error.take().unwrap_or(err.into()))
+    }
+
+    /// Send the request and wait for its response
+    fn request(&mut self, request: Request) -> Result<ResponseResult, Error> {
+        let message = Message::request(request);
+        let request_id = message.id().to_string();
+        transport::send(
+            &mut self.client,
+            &self.keys,
+            self.signer_public_key,
+            message,
+        )?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let event = self.client.next_event(SUBSCRIPTION_ID, timeout)?;
+            let Some((sender, response)) = transport::open(&self.keys, &event) else {
+                continue;
+            };
+            match response {
+                Message::Response { id, result, error }
+                    if id == request_id && sender == self.signer_public_key =>
+                {
+                    return match (result, error) {
+                        (Some(ResponseResult::AuthUrl), Some(url)) => Err(Error::AuthRequired(url)),
+                        (_, Some(error)) => Err(Error::Rejected(error)),
+                        (Some(result), None) => Ok(result),
+                        (None, None) => Err(Error::UnexpectedResponse("empty response".into())),
+                    };
+                }
+                _ => continue,
+            }
+        }
+    }
+}
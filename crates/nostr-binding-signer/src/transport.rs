@@ -0,0 +1,53 @@
+//! NIP-46 messages over the relay: kind 24133 events to the receiver's key,
+//! with the JSON message encrypted by NIP-04
+
+use std::time::Duration;
+
+use ckb_nostr_utils::event::Event;
+use nostr::{nips::nip04, nips::nip46::Message, EventBuilder, JsonUtil, Keys, Kind, PublicKey};
+use nostr_binding_relay::{Filter, RelayClient, RelayMessage};
+
+use crate::error::Error;
+
+/// Subscribe to the messages to `public_key`, and wait until the relay has
+/// the subscription in place, since message events are not stored
+pub(crate) fn subscribe(
+    client: &mut RelayClient,
+    subscription_id: &str,
+    public_key: &PublicKey,
+    timeout: Duration,
+) -> Result<(), Error> {
+    let filter = Filter::new()
+        .kind(Kind::NostrConnect.as_u16())
+        .tag('p', public_key.to_hex());
+    client.subscribe(subscription_id, vec![filter])?;
+    client.wait_for(timeout, |message| match message {
+        RelayMessage::Eose(id) if id == subscription_id => Some(()),
+        _ => None,
+    })?;
+    Ok(())
+}
+
+/// Publish `message` from `keys` to `receiver`
+pub(crate) fn send(
+    client: &mut RelayClient,
+    keys: &Keys,
+    receiver: PublicKey,
+    message: Message,
+) -> Result<(), Error> {
+    let event = EventBuilder::nostr_connect(keys, receiver, message)
+        .and_then(|builder| builder.to_event(keys))
+        .map_err(|err| Error::Nostr(err.to_string()))?;
+    let event = Event::from_json(event.as_json())
+        .map_err(|_| Error::Nostr("unsupported message event".into()))?;
+    client.publish(&event)?;
+    Ok(())
+}
+
+/// The sender and the message of an event to `keys`, `None` if it is not a
+/// message for them
+pub(crate) fn open(keys: &Keys, event: &Event) -> Option<(PublicKey, Message)> {
+    let event = nostr::Event::from_json(serde_json::to_string(event).ok()?).ok()?;
+    let json = nip04::decrypt(keys.secret_key().ok()?, &event.pubkey, &event.content).ok()?;
+    Some((event.pubkey, Message::from_json(json).ok()?))
+}
@@ -0,0 +1,166 @@
+import { HexString } from '@ckb-lumos/base';
+import { bytes } from '@ckb-lumos/codec';
+import { FIELD_FLAGS, NostrLockFlags } from './lock';
+
+// args longer than the legacy ones have `VERSION_MARK | version` after the pubkey hash
+export const VERSION_MARK = 0x80;
+export const VERSION_1 = 1;
+// fields of version 1 with this bit set can be ignored by parsers not knowing them
+export const FIELD_IGNORABLE = 0x80;
+const FLAGS_ALL = 0x7f;
+const FIELD_LENGTHS: Record<number, number> = {
+  [NostrLockFlags.CreatedAtWindow]: 4,
+  [NostrLockFlags.Recovery]: 28,
+  [NostrLockFlags.DynamicPow]: 32,
+  [NostrLockFlags.ContentConfig]: 32,
+};
+
+// legacy: <pow 1><pubkey hash 20>
+// v1: <pow><pubkey hash><0x81><flags 1>{<field id 1><length 1><value>}, field ids are ascending
+export type LockArgsFormat = 'legacy' | 'v1';
+
+export interface LockArgs {
+  format: LockArgsFormat;
+  powDifficulty: number;
+  pubkeyHash: HexString;
+  flags: number;
+  createdAtWindow: number | null;
+  recovery: { pubkeyHash: HexString; delay: HexString } | null;
+  difficultyConfig: HexString | null;
+  contentConfig: HexString | null;
+  // ignorable fields of v1 unknown to this decoder
+  extraFields: [number, HexString][];
+}
+
+function uintLE(data: Uint8Array): bigint {
+  let value = BigInt(0);
+  for (let i = data.length - 1; i >= 0; i--) {
+    value = (value << BigInt(8)) | BigInt(data[i]);
+  }
+  return value;
+}
+
+function uintToLE(value: bigint, length: number): Uint8Array {
+  const data = new Uint8Array(length);
+  for (let i = 0; i < length; i++) {
+    data[i] = Number((value >> BigInt(8 * i)) & BigInt(0xff));
+  }
+  return data;
+}
+
+function setField(args: LockArgs, flag: number, field: Uint8Array) {
+  if (flag === NostrLockFlags.CreatedAtWindow) {
+    args.createdAtWindow = Number(uintLE(field));
+  } else if (flag === NostrLockFlags.Recovery) {
+    args.recovery = {
+      pubkeyHash: bytes.hexify(field.slice(0, 20)),
+      delay: '0x' + uintLE(field.slice(20, 28)).toString(16),
+    };
+  } else if (flag === NostrLockFlags.DynamicPow) {
+    args.difficultyConfig = bytes.hexify(field);
+  } else {
+    args.contentConfig = bytes.hexify(field);
+  }
+}
+
+function getField(args: LockArgs, flag: number): Uint8Array {
+  if (flag === NostrLockFlags.CreatedAtWindow && args.createdAtWindow != null) {
+    return uintToLE(BigInt(args.createdAtWindow), 4);
+  } else if (flag === NostrLockFlags.Recovery && args.recovery != null) {
+    return bytes.concat(args.recovery.pubkeyHash, uintToLE(BigInt(args.recovery.delay), 8));
+  } else if (flag === NostrLockFlags.DynamicPow && args.difficultyConfig != null) {
+    return bytes.bytify(args.difficultyConfig);
+  } else if (flag === NostrLockFlags.ContentConfig && args.contentConfig != null) {
+    return bytes.bytify(args.contentConfig);
+  }
+  throw new Error(`missing field of flag ${flag}`);
+}
+
+// decode nostr lock script args, the same way as the lock script does
+export function decodeLockArgs(lockArgs: HexString): LockArgs {
+  const data = bytes.bytify(lockArgs);
+  if (data.length < 21) {
+    throw new Error('invalid lock args length');
+  }
+  const args: LockArgs = {
+    format: 'legacy',
+    powDifficulty: data[0],
+    pubkeyHash: bytes.hexify(data.slice(1, 21)),
+    flags: 0,
+    createdAtWindow: null,
+    recovery: null,
+    difficultyConfig: null,
+    contentConfig: null,
+    extraFields: [],
+  };
+  if (data.length === 21) {
+    return args;
+  }
+  let rest = data.slice(22);
+  const take = (length: number) => {
+    if (rest.length < length) {
+      throw new Error('invalid lock args length');
+    }
+    const taken = rest.slice(0, length);
+    rest = rest.slice(length);
+    return taken;
+  };
+  if (data[21] === (VERSION_MARK | VERSION_1)) {
+    args.format = 'v1';
+    args.flags = take(1)[0];
+    if ((args.flags & ~FLAGS_ALL) !== 0) {
+      throw new Error('unknown lock args flags');
+    }
+    let lastId = -1;
+    let found = 0;
+    while (rest.length > 0) {
+      const [id, length] = take(2);
+      const value = take(length);
+      if (id <= lastId) {
+        throw new Error('lock args field ids should be ascending');
+      }
+      lastId = id;
+      if ((id & FIELD_IGNORABLE) !== 0) {
+        args.extraFields.push([id, bytes.hexify(value)]);
+        continue;
+      }
+      if ((args.flags & id) === 0 || FIELD_LENGTHS[id] !== length) {
+        throw new Error(`invalid lock args field ${id}`);
+      }
+      found |= id;
+      setField(args, id, value);
+    }
+    const fieldFlags = FIELD_FLAGS.reduce((all, flag) => all | flag, 0);
+    if (found !== (args.flags & fieldFlags)) {
+      throw new Error('missing lock args field');
+    }
+  } else {
+    throw new Error('unknown lock args version');
+  }
+  if ((args.flags & NostrLockFlags.SummaryContent) !== 0 && (args.flags & NostrLockFlags.ContentConfig) !== 0) {
+    throw new Error('summary content can not be used with content config');
+  }
+  if (args.recovery != null && (BigInt(args.recovery.delay) & BigInt('0x8000000000000000')) === BigInt(0)) {
+    throw new Error('recovery delay should be a relative since');
+  }
+  return args;
+}
+
+// encode nostr lock script args. A legacy format with flags set is encoded in version 1.
+export function encodeLockArgs(args: LockArgs): HexString {
+  const parts: Uint8Array[] = [new Uint8Array([args.powDifficulty]), bytes.bytify(args.pubkeyHash)];
+  const fields = FIELD_FLAGS.filter((flag) => (args.flags & flag) !== 0).map(
+    (flag) => [flag, getField(args, flag)] as [number, Uint8Array],
+  );
+  if (args.format === 'v1' || args.flags !== 0) {
+    parts.push(new Uint8Array([VERSION_MARK | VERSION_1, args.flags]));
+    const all = fields.concat(
+      args.extraFields.map(([id, value]): [number, Uint8Array] => [id, bytes.bytify(value)]),
+    );
+    all.sort((a, b) => a[0] - b[0]);
+    for (const [id, value] of all) {
+      parts.push(new Uint8Array([id, value.length]), value);
+    }
+  }
+  return bytes.hexify(bytes.concat(...parts));
+}
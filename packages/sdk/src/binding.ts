@@ -7,6 +7,22 @@ import { calcEventId, EventToBind, parseSignedEvent } from './event';
 import { minimalCellCapacity } from '@ckb-lumos/helpers';
 import { RPC } from '@ckb-lumos/rpc';
 
+// flags byte appended to binding script args
+export enum BindingFlags {
+  // cell data equals the content of bound event
+  DataContent = 0x01,
+  // cell data equals sha256 of the content of bound event
+  DataContentHash = 0x02,
+  // cell data equals the `x` tag of a NIP-94 file metadata event
+  DataFileHash = 0x04,
+  // cell data is the canonical JSON of bound event
+  Archive = 0x08,
+  // cell data starts with the NIP-40 expiration of bound event, 8 bytes little endian
+  Expiration = 0x10,
+  // cell data carries the royalty of bound event, paid to the recipient lock on sales
+  Royalty = 0x20,
+}
+
 export class NostrBinding {
   readonly prefix: 'ckt' | 'ckb';
   readonly scriptConfig: NostrScriptConfig;
@@ -42,8 +58,15 @@ export class NostrBinding {
     return null;
   }
 
-  buildScript(eventId: HexString, ckbGlobalUniqueId: HexString): Script {
-    const bindingArgs = `0x${eventId}${ckbGlobalUniqueId}`;
+  // flags is an optional trailing byte of args, see BindingFlags
+  buildScript(eventId: HexString, ckbGlobalUniqueId: HexString, flags?: number): Script {
+    let bindingArgs = `0x${eventId}${ckbGlobalUniqueId}`;
+    if (flags != null) {
+      if (flags < 0 || flags > 255) {
+        throw new Error('flags should be a single byte');
+      }
+      bindingArgs += flags.toString(16).padStart(2, '0');
+    }
     return {
       codeHash: this.scriptConfig.CODE_HASH,
       hashType: this.scriptConfig.HASH_TYPE,
@@ -66,15 +89,15 @@ export class NostrBinding {
     return finalizedEvent;
   }
 
-  buildBindingCell(eventId: HexString, ckbGlobalUniqueId: HexString, lock: Script) {
-    const type = this.buildScript(eventId, ckbGlobalUniqueId);
+  buildBindingCell(eventId: HexString, ckbGlobalUniqueId: HexString, lock: Script, data = '0x00', flags?: number) {
+    const type = this.buildScript(eventId, ckbGlobalUniqueId, flags);
     const bindingOutput: Cell = {
       cellOutput: {
         capacity: '0x0',
         lock,
         type,
       },
-      data: '0x00',
+      data,
     };
     const capacity = minimalCellCapacity(bindingOutput);
     bindingOutput.cellOutput.capacity = '0x' + capacity.toString(16);
@@ -3,6 +3,7 @@ import { NostrLock } from './lock';
 import { SDKConfig } from './config';
 
 export * from './lock';
+export * from './args';
 export * from './binding';
 export * from './config';
 export * from './tag';
@@ -5,19 +5,72 @@ import { CellDep, HexString, utils, WitnessArgs, Script, blockchain, Transaction
 import { bytes, number } from '@ckb-lumos/codec';
 import { EventToSign, parseSignedEvent, SignedEvent } from './event';
 import { RPC } from '@ckb-lumos/rpc';
+import { sha256 } from '@noble/hashes/sha256';
 import { encodeToAddress, parseAddress } from '@ckb-lumos/helpers';
 
 const { Uint64 } = number;
 
+// flags of version 1 lock args
+export enum NostrLockFlags {
+  // `content` of unlock event is the summary of transaction, see buildTxSummary
+  SummaryContent = 0x01,
+  // `created_at` of unlock event must be within a window around the proven timestamp, see buildCreatedAtWindowField
+  CreatedAtWindow = 0x02,
+  // a recovery key can unlock after inputs have been idle for a relative since, see buildRecoveryField
+  Recovery = 0x04,
+  // anyone can deposit capacity or UDT into the cells without a signature
+  AnyoneCanPay = 0x08,
+  // PoW difficulty is read from a difficulty config cell, see buildDynamicPowScript
+  DynamicPow = 0x10,
+  // the unlock event must be signed by the owner and meet the PoW difficulty, see buildKeyAndPowScript
+  KeyAndPow = 0x20,
+  // kind and content of unlock events are declared by a content config cell, see useContentConfig
+  ContentConfig = 0x40,
+}
+
+// flags carrying a field, in the order of flag bits
+export const FIELD_FLAGS = [
+  NostrLockFlags.CreatedAtWindow,
+  NostrLockFlags.Recovery,
+  NostrLockFlags.DynamicPow,
+  NostrLockFlags.ContentConfig,
+];
+
+const toHexByte = (value: number) => value.toString(16).padStart(2, '0');
+
+// lock args after the pubkey hash in version 1, each field is prefixed with its flag as
+// field id and its length
+function buildVersion1Args(flags: number, fields: HexString[]) {
+  const ids = FIELD_FLAGS.filter((flag) => (flags & flag) !== 0);
+  if (ids.length !== fields.length) {
+    throw new Error('fields should match the flags');
+  }
+  let args = '81' + toHexByte(flags);
+  fields.forEach((field, index) => {
+    args += toHexByte(ids[index]) + toHexByte(bytes.bytify(field).length) + field.slice(2);
+  });
+  return args;
+}
+
 export class NostrLock {
-  readonly kind = 23334;
-  readonly content =
-    'Signing a CKB transaction\n\nIMPORTANT: Please verify the integrity and authenticity of connected Nostr client before signing this message\n';
+  // kind and content of unlock events, can be replaced by useContentConfig
+  kind = 23334;
+  readonly sessionKind = 23335;
+  readonly warning =
+    'IMPORTANT: Please verify the integrity and authenticity of connected Nostr client before signing this message';
+  content = `Signing a CKB transaction\n\n${this.warning}\n`;
   readonly dummyCkbSigHashAll = '0x' + '00'.repeat(32);
 
   readonly prefix: 'ckt' | 'ckb';
   readonly scriptConfig: NostrScriptConfig;
   rpc: RPC;
+  // when set, unlock events carry a `ckb_chain` tag and transactions depend on the genesis header, see bindChain
+  genesisHash?: HexString;
+  // NIP-26 delegation tag granted by the lock key to the signer, added to unlock events when set.
+  // Its conditions must include kind=23334.
+  delegationTag?: string[];
+  // authorization event signed by the owner key, added to unlock events signed by a session key when set
+  sessionAuthorization?: SignedEvent;
 
   constructor(
     scriptConfig = TESTNET_CONFIGS.NOSTR_LOCK,
@@ -29,6 +82,18 @@ export class NostrLock {
     this.rpc = new RPC(rpcUrl);
   }
 
+  // sign unlock events of `kind` and `content`, for locks with NostrLockFlags.ContentConfig.
+  // The content config cell must be in cell deps of the transaction.
+  useContentConfig(kind: number, content: string) {
+    this.kind = kind;
+    this.content = content;
+  }
+
+  // data of a content config cell: kind, 2 bytes little endian, followed by sha256 of content
+  buildContentConfigData(kind: number, content: string) {
+    return bytes.hexify(bytes.concat(number.Uint16LE.pack(kind), sha256(new TextEncoder().encode(content))));
+  }
+
   isNostrLock(lock: Script | undefined) {
     if (lock == null) return false;
     return lock.codeHash === this.scriptConfig.CODE_HASH && lock.hashType === this.scriptConfig.HASH_TYPE;
@@ -56,12 +121,29 @@ export class NostrLock {
     return hasher.digestHex().slice(0, 42);
   }
 
-  buildPubkeyScriptArgs(ownerPubkey: HexString) {
+  // version 1 args with flags, fields are in the order of their flag bits
+  buildPubkeyScriptArgs(ownerPubkey: HexString, flags?: number, fields: HexString[] = []) {
     const pubkeyHash = this.buildPubkeyHash(ownerPubkey);
-    const lockArgs = '0x00' + pubkeyHash.slice(2);
+    let lockArgs = '0x00' + pubkeyHash.slice(2);
+    if (flags != null) {
+      lockArgs += buildVersion1Args(flags, fields);
+    }
     return lockArgs;
   }
 
+  // window in seconds, 4 bytes little endian
+  buildCreatedAtWindowField(window: number) {
+    return bytes.hexify(number.Uint32LE.pack(window));
+  }
+
+  // recovery pubkey hash followed by the relative since delay, 8 bytes little endian
+  buildRecoveryField(recoveryPubkey: HexString, delay: bigint) {
+    if ((delay & BigInt('0x8000000000000000')) === BigInt(0)) {
+      throw new Error('recovery delay should be a relative since');
+    }
+    return this.buildPubkeyHash(recoveryPubkey) + bytes.hexify(Uint64.pack(delay)).slice(2);
+  }
+
   buildPowScriptArgs(pow: number) {
     if (pow > 255) {
       throw new Error('max pow value is 255');
@@ -72,8 +154,39 @@ export class NostrLock {
     return lockArgs;
   }
 
-  buildScript(ownerPubkey: HexString) {
-    const lockArgs = this.buildPubkeyScriptArgs(ownerPubkey);
+  // PoW difficulty is the one in the config cell of `configTypeHash`, no less than `floor`.
+  // The unlocking transaction must record the claim by updating the config cell, see the pow-difficulty type script.
+  buildDynamicPowScript(configTypeHash: HexString, floor = 0) {
+    if (floor > 255) {
+      throw new Error('max pow value is 255');
+    }
+    const lockArgs =
+      '0x' +
+      floor.toString(16).padStart(2, '0') +
+      '00'.repeat(20) +
+      buildVersion1Args(NostrLockFlags.DynamicPow, [configTypeHash]);
+    return {
+      codeHash: this.scriptConfig.CODE_HASH,
+      hashType: this.scriptConfig.HASH_TYPE,
+      args: lockArgs,
+    };
+  }
+
+  // both the owner signature and PoW of `pow` difficulty are required to unlock
+  buildKeyAndPowScript(ownerPubkey: HexString, pow: number, flags = 0, fields: HexString[] = []) {
+    if (pow === 0 || pow > 255) {
+      throw new Error('pow value should be within 1 to 255');
+    }
+    const lockArgs = this.buildPubkeyScriptArgs(ownerPubkey, flags | NostrLockFlags.KeyAndPow, fields);
+    return {
+      codeHash: this.scriptConfig.CODE_HASH,
+      hashType: this.scriptConfig.HASH_TYPE,
+      args: '0x' + pow.toString(16).padStart(2, '0') + lockArgs.slice(4),
+    };
+  }
+
+  buildScript(ownerPubkey: HexString, flags?: number, fields: HexString[] = []) {
+    const lockArgs = this.buildPubkeyScriptArgs(ownerPubkey, flags, fields);
     return {
       codeHash: this.scriptConfig.CODE_HASH,
       hashType: this.scriptConfig.HASH_TYPE,
@@ -142,28 +255,49 @@ export class NostrLock {
     return cellDeps;
   }
 
+  // bindChain makes unlock events only valid on the chain of genesisHash(fetched from rpc by default),
+  // so they can't be replayed on another network or fork
+  async bindChain(genesisHash?: HexString) {
+    this.genesisHash = genesisHash ?? (await this.rpc.getBlockHash('0x0'));
+    return this.genesisHash;
+  }
+
+  // the genesis header named by `ckb_chain` tag must be in header deps, which affects the transaction hash
+  addChainHeaderDep(transaction: Transaction) {
+    if (this.genesisHash && !transaction.headerDeps.includes(this.genesisHash)) {
+      transaction.headerDeps.push(this.genesisHash);
+    }
+    return transaction;
+  }
+
   // signTx will overwrite the witness lock with dummyLock and then generate sigHashAll,
   // sign it and return signed transaction. It is a easy way to do nostr lock signing if
   // transaction fee estimation is not a problem to you
-  async signTx(transaction: Transaction, signer: (_event: EventToSign) => Promise<SignedEvent>) {
+  // content should be buildTxSummary of the transaction when NostrLockFlags.SummaryContent is set
+  async signTx(
+    transaction: Transaction,
+    signer: (_event: EventToSign) => Promise<SignedEvent>,
+    content = this.content,
+  ) {
     const lockIndexes: Array<number> = await this.getLockIndexes(transaction);
 
     if (lockIndexes.length === 0) {
       throw new Error('there is no nostr lock input.');
     }
 
+    this.addChainHeaderDep(transaction);
     const witnessIndex = lockIndexes[0];
     while (witnessIndex >= transaction.witnesses.length) {
       transaction.witnesses.push('0x');
     }
 
     let witness: string = transaction.witnesses[witnessIndex]!;
-    witness = this.fillInDummyLockWitness(witness);
+    witness = this.fillInDummyLockWitness(witness, content);
     transaction.witnesses[witnessIndex] = witness;
     const sigHashAll = this.buildSigHashAll(transaction, lockIndexes);
     console.debug('sighash_all = ', sigHashAll);
 
-    const event = this.buildUnlockEvent(sigHashAll);
+    const event = this.buildUnlockEvent(sigHashAll, content);
 
     const signedEvent = await signer(event);
     const eventJson = jsonStringToBytes(JSON.stringify(signedEvent));
@@ -190,6 +324,7 @@ export class NostrLock {
     transaction: Transaction,
     lockIndexes: Array<number>,
     signer: (_event: EventToSign) => Promise<SignedEvent>,
+    content = this.content,
   ) {
     if (lockIndexes.length === 0) {
       throw new Error('there is no nostr lock input.');
@@ -204,7 +339,7 @@ export class NostrLock {
       }
       const witnessArgs = blockchain.WitnessArgs.unpack(bytes.bytify(witness));
       const lock = witnessArgs.lock;
-      if (lock && !bytes.equal(lock, this.buildDummyLock())) {
+      if (lock && !bytes.equal(lock, this.buildDummyLock(content))) {
         throw new Error('Lock field in first witness is a invalid dummy lock!');
       }
     }
@@ -212,7 +347,7 @@ export class NostrLock {
     const sigHashAll = this.buildSigHashAll(transaction, lockIndexes);
     console.debug('sighash_all = ', sigHashAll);
 
-    const event = this.buildUnlockEvent(sigHashAll);
+    const event = this.buildUnlockEvent(sigHashAll, content);
 
     const signedEvent = await signer(event);
     const eventJson = jsonStringToBytes(JSON.stringify(signedEvent));
@@ -233,20 +368,21 @@ export class NostrLock {
   }
 
   // fill-in the witness of nostr-lock with corresponding dummyLock
-  async prepareTx(transaction: Transaction) {
+  async prepareTx(transaction: Transaction, content = this.content) {
     const lockIndexes: Array<number> = await this.getLockIndexes(transaction);
 
     if (lockIndexes.length === 0) {
       throw new Error('there is no nostr lock input.');
     }
 
+    this.addChainHeaderDep(transaction);
     const witnessIndex = lockIndexes[0];
     while (witnessIndex >= transaction.witnesses.length) {
       transaction.witnesses.push('0x');
     }
 
     let witness: string = transaction.witnesses[witnessIndex]!;
-    witness = this.fillInDummyLockWitness(witness);
+    witness = this.fillInDummyLockWitness(witness, content);
     transaction.witnesses[witnessIndex] = witness;
 
     return { transaction, lockIndexes };
@@ -324,9 +460,9 @@ export class NostrLock {
     return message;
   }
 
-  fillInDummyLockWitness(witness: string) {
+  fillInDummyLockWitness(witness: string, content = this.content) {
     const newWitnessArgs: WitnessArgs = {
-      lock: this.buildDummyLock(),
+      lock: this.buildDummyLock(content),
     };
 
     if (witness !== '0x') {
@@ -343,8 +479,8 @@ export class NostrLock {
     return bytes.hexify(blockchain.WitnessArgs.pack(newWitnessArgs));
   }
 
-  buildDummyLock() {
-    const dummyEvent = this.buildDummyEvent();
+  buildDummyLock(content = this.content) {
+    const dummyEvent = this.buildDummyEvent(content);
     const dummyLength = jsonStringToBytes(dummyEvent).length;
     console.debug('dummyEvent and length: ', dummyEvent, dummyLength);
 
@@ -352,28 +488,99 @@ export class NostrLock {
     return dummyLock;
   }
 
-  buildDummyEvent() {
-    const tags = [[TagName.ckbSigHashAll, this.dummyCkbSigHashAll.slice(2)]];
+  buildTags(ckbSigHashAll: HexString) {
+    const tags = [[TagName.ckbSigHashAll, ckbSigHashAll.slice(2)]];
+    if (this.genesisHash) {
+      tags.push([TagName.ckbChain, this.genesisHash.slice(2)]);
+    }
+    if (this.delegationTag) {
+      tags.push(this.delegationTag);
+    }
+    if (this.sessionAuthorization) {
+      tags.push([TagName.ckbSession, JSON.stringify(this.sessionAuthorization)]);
+    }
+    return tags;
+  }
+
+  buildDummyEvent(content = this.content) {
+    const tags = this.buildTags(this.dummyCkbSigHashAll);
     const event = {
       id: '00'.repeat(32),
       pubkey: '00'.repeat(32),
       tags,
       created_at: getTimestampNowSecs(),
       kind: this.kind,
-      content: this.content,
+      content,
       sig: '00'.repeat(64),
     };
 
     return JSON.stringify(event);
   }
 
-  buildUnlockEvent(ckbSigHashAll: HexString): EventToSign {
+  buildUnlockEvent(ckbSigHashAll: HexString, content = this.content): EventToSign {
     const unlockEvent: EventToSign = {
-      tags: [[TagName.ckbSigHashAll, ckbSigHashAll.slice(2)]],
+      tags: this.buildTags(ckbSigHashAll),
       created_at: getTimestampNowSecs(),
       kind: this.kind,
-      content: this.content,
+      content,
     };
     return unlockEvent;
   }
+
+  // authorization event to be signed by the owner key, granting sessionPubkey to unlock until expiration(unix
+  // timestamp in seconds). Each transaction can move at most maxCapacity shannons out of the lock, and only to
+  // allowedLockHashes. Each transaction must prove a time before expiration by a header dep or an absolute since.
+  buildSessionAuthorizationEvent(
+    sessionPubkey: HexString,
+    expiration: number,
+    maxCapacity: bigint,
+    allowedLockHashes: HexString[],
+  ): EventToSign {
+    const tags = [
+      [TagName.ckbSessionPubkey, sessionPubkey],
+      [TagName.expiration, expiration.toString()],
+      [TagName.ckbMaxCapacity, maxCapacity.toString()],
+      ...allowedLockHashes.map((lockHash) => [TagName.ckbAllowedLock, lockHash.slice(2)]),
+    ];
+    return {
+      tags,
+      created_at: getTimestampNowSecs(),
+      kind: this.sessionKind,
+      content: '',
+    };
+  }
+
+  // deterministic summary of the transaction, which is rebuilt and compared on chain when
+  // NostrLockFlags.SummaryContent is set. It must be generated after outputs and inputs are final.
+  async buildTxSummary(transaction: Transaction) {
+    const formatCapacity = (shannons: bigint) =>
+      `${shannons / BigInt(100000000)}.${(shannons % BigInt(100000000)).toString().padStart(8, '0')}`;
+
+    let summary = `Signing a CKB transaction on ${this.prefix}\n\n${this.warning}\n`;
+    let outputsCapacity = BigInt(0);
+    transaction.outputs.forEach((output, index) => {
+      const capacity = BigInt(output.capacity);
+      outputsCapacity += capacity;
+      const address = encodeToAddress(output.lock, { config: { PREFIX: this.prefix, SCRIPTS: {} } });
+      summary += `\nOutput ${index}: ${formatCapacity(capacity)} CKB\nTo: ${address}\n`;
+      if (output.type) {
+        summary += `Type: ${output.type.codeHash} ${output.type.hashType} ${output.type.args}\n`;
+      }
+      summary += `Data: ${bytes.bytify(transaction.outputsData[index] ?? '0x').byteLength} bytes\n`;
+    });
+
+    const inputCells = await Promise.all(
+      transaction.inputs.map((input) => this.rpc.getLiveCell(input.previousOutput, false)),
+    );
+    let inputsCapacity = BigInt(0);
+    for (const inputCell of inputCells) {
+      if (inputCell.status !== 'live') {
+        throw new Error('input cell is not live');
+      }
+      inputsCapacity += BigInt(inputCell.cell!.output.capacity);
+    }
+    const fee = inputsCapacity > outputsCapacity ? inputsCapacity - outputsCapacity : BigInt(0);
+    summary += `\nFee: ${formatCapacity(fee)} CKB\n`;
+    return summary;
+  }
 }
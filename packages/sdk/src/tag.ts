@@ -1,4 +1,18 @@
 export enum TagName {
   ckbGlobalUniqueId = 'ckb_global_unique_id',
   ckbSigHashAll = 'ckb_sighash_all',
+  // [ckbChain, <genesis hash>], the unlock event is only valid on the chain of the genesis hash
+  ckbChain = 'ckb_chain',
+  // NIP-26 [delegation, <delegator pubkey>, <conditions>, <token>]
+  delegation = 'delegation',
+  // [ckbSession, <authorization event JSON>], the unlock event is signed by a session key
+  ckbSession = 'ckb_session',
+  ckbSessionPubkey = 'ckb_session_pubkey',
+  ckbMaxCapacity = 'ckb_max_capacity',
+  ckbAllowedLock = 'ckb_allowed_lock',
+  expiration = 'expiration',
+  // [ckbMintTo, <lock hash>], the binding cell can only be minted to the named lock
+  ckbMintTo = 'ckb_mint_to',
+  // [ckbMintToAuthor, <nostr lock code hash>], the binding cell can only be minted to the author's nostr lock
+  ckbMintToAuthor = 'ckb_mint_to_author',
 }
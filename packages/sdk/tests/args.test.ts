@@ -0,0 +1,26 @@
+import { readFileSync } from 'fs';
+import { join } from 'path';
+import { decodeLockArgs, encodeLockArgs, LockArgs } from '../src/index';
+
+// test vectors shared with the Rust decoder of the lock script
+const vectors = JSON.parse(readFileSync(join(__dirname, '../../../tests/vectors/lock-args.json'), 'utf-8'));
+
+describe('Nostr Lock Args', () => {
+  test('Decode Lock Args', () => {
+    for (const vector of vectors.valid) {
+      expect(decodeLockArgs(vector.args)).toEqual(vector.decoded);
+    }
+  });
+
+  test('Encode Lock Args', () => {
+    for (const vector of vectors.valid) {
+      expect(encodeLockArgs(vector.decoded as LockArgs)).toEqual(vector.args);
+    }
+  });
+
+  test('Reject Invalid Lock Args', () => {
+    for (const vector of vectors.invalid) {
+      expect(() => decodeLockArgs(vector.args)).toThrow();
+    }
+  });
+});
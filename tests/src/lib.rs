@@ -7,12 +7,40 @@ mod tests_type;
 #[cfg(test)]
 mod tests_both;
 
+#[cfg(test)]
+mod tests_args;
+
+#[cfg(test)]
+mod tests_difficulty;
+
+#[cfg(test)]
+mod tests_sdk;
+
+#[cfg(test)]
+mod tests_cli;
+
+#[cfg(test)]
+mod tests_relay;
+
+#[cfg(test)]
+mod tests_indexer;
+
+#[cfg(test)]
+mod tests_bridge;
+
+#[cfg(test)]
+mod tests_signer;
+
 use ::hex;
+use ckb_nostr_error::{ScriptError, ScriptFailure, ScriptGroupType, ScriptKind};
 use ckb_testtool::{
     builtin::ALWAYS_SUCCESS,
     ckb_types::{
         bytes::Bytes,
-        core::{TransactionBuilder, TransactionView},
+        core::{
+            EpochNumberWithFraction, HeaderBuilder, ScriptHashType, TransactionBuilder,
+            TransactionView,
+        },
         packed::{self, Script, WitnessArgsBuilder},
         prelude::*,
     },
@@ -20,9 +48,10 @@ use ckb_testtool::{
 };
 use ckb_testtool::{
     ckb_error::Error as CkbError,
-    ckb_hash::{Blake2b, Blake2bBuilder},
+    ckb_hash::{blake2b_256, Blake2b, Blake2bBuilder},
 };
 use lazy_static::lazy_static;
+use nostr::hashes::{sha256, Hash};
 use nostr::prelude::*;
 use std::{
     str::FromStr,
@@ -35,7 +64,24 @@ pub const NOSTR_LOCK_KIND: u16 = 23334;
 pub const SCRIPT_ARGS_LEN: usize = 33;
 pub const NOSTR_LOCK_CONTENT: &str = "Signing a CKB transaction\n\nIMPORTANT: Please verify the integrity and authenticity of connected Nostr client before signing this message\n";
 pub const NONCE: &str = "nonce";
+pub const CHAIN_TAG_NAME: &str = "ckb_chain";
+pub const LOCK_FLAG_SUMMARY_CONTENT: u8 = 0x01;
+pub const LOCK_FLAG_CREATED_AT_WINDOW: u8 = 0x02;
+pub const LOCK_FLAG_RECOVERY: u8 = 0x04;
+pub const SESSION_KIND: u16 = 23335;
+pub const LOCK_FLAG_ANYONE_CAN_PAY: u8 = 0x08;
+pub const LOCK_FLAG_DYNAMIC_POW: u8 = 0x10;
+pub const LOCK_FLAG_KEY_AND_POW: u8 = 0x20;
+pub const LOCK_FLAG_CONTENT_CONFIG: u8 = 0x40;
+pub const NOSTR_LOCK_WARNING: &str = "IMPORTANT: Please verify the integrity and authenticity of connected Nostr client before signing this message";
 pub const GLOBAL_UNIQUE_ID_TAG_NAME: &str = "ckb_global_unique_id";
+pub const BINDING_FLAG_ARCHIVE: u8 = 0x08;
+pub const BINDING_FLAG_EXPIRATION: u8 = 0x10;
+pub const EXPIRATION_TAG_NAME: &str = "expiration";
+pub const BINDING_FLAG_ROYALTY: u8 = 0x20;
+pub const ROYALTY_TAG_NAME: &str = "royalty";
+pub const MINT_TO_TAG_NAME: &str = "ckb_mint_to";
+pub const MINT_TO_AUTHOR_TAG_NAME: &str = "ckb_mint_to_author";
 
 #[derive(Clone, PartialEq)]
 pub enum TestSchema {
@@ -56,6 +102,7 @@ pub struct TestConfig {
     pub created_at: u64,
     pub kind: u16,
     pub lock_content: String,
+    pub tags: Vec<Tag>,
 }
 
 impl TestConfig {
@@ -65,14 +112,16 @@ impl TestConfig {
             created_at: unix_time_now(),
             kind: NOSTR_LOCK_KIND,
             lock_content: NOSTR_LOCK_CONTENT.to_string(),
+            tags: vec![],
         }
     }
 
     pub fn get_event(&self, sighash_all: [u8; 32]) -> Event {
-        let tags = [Tag::custom(
+        let mut tags = vec![Tag::custom(
             TagKind::from(SIGHASH_ALL_TAG_NAME),
             vec![hex::encode(sighash_all)],
         )];
+        tags.extend(self.tags.clone());
         EventBuilder::new(Kind::from(self.kind), &self.lock_content, tags)
             .custom_created_at(self.created_at.into())
             .to_event(&self.key)
@@ -86,6 +135,27 @@ impl Default for TestConfig {
     }
 }
 
+/// The event bound on minting and the binding cell built from it
+pub struct BindingConfig {
+    pub kind: u16,
+    pub content: String,
+    pub tags: Vec<Tag>,
+    pub flags: Option<u8>,
+    pub data: Bytes,
+}
+
+impl Default for BindingConfig {
+    fn default() -> Self {
+        Self {
+            kind: 0,
+            content: "hello,world".into(),
+            tags: vec![],
+            flags: None,
+            data: Bytes::new(),
+        }
+    }
+}
+
 ///
 /// sign a transaction for a nostr lock script, with key, timestamp and witness index
 ///
@@ -303,14 +373,15 @@ pub fn sign_pow_lock_script(
 pub fn type_script_mint(
     key: &Keys,
     created_at: u64,
-    content: String,
+    config: &BindingConfig,
     global_unique_id: [u8; 32],
 ) -> (Bytes, [u8; 32]) {
-    let tags = [Tag::custom(
+    let mut tags = config.tags.clone();
+    tags.push(Tag::custom(
         TagKind::from(GLOBAL_UNIQUE_ID_TAG_NAME),
         vec![hex::encode(global_unique_id)],
-    )];
-    let event: Event = EventBuilder::new(Kind::from(0), content, tags)
+    ));
+    let event: Event = EventBuilder::new(Kind::from(config.kind), &config.content, tags)
         .custom_created_at(created_at.into())
         .to_event(key)
         .unwrap();
@@ -396,13 +467,27 @@ pub fn unix_time_now() -> u64 {
     }
 }
 
-pub fn assert_script_error(err: CkbError, err_code: i8) {
-    let error_string = err.to_string();
-    assert!(
-        error_string.contains(format!("error code {} ", err_code).as_str()),
-        "error_string: {}, expected_error_code: {}",
-        error_string,
-        err_code
+/// `err` is a script of `expected` kind exiting with the code of `expected`
+pub fn assert_script_error(err: CkbError, expected: impl Into<ScriptError>) {
+    let expected = expected.into();
+    let failure = ScriptFailure::from_ckb_error(&err)
+        .unwrap_or_else(|| panic!("not a script error: {}", err));
+    let group_type = match expected.kind() {
+        ScriptKind::NostrLock => ScriptGroupType::Lock,
+        _ => ScriptGroupType::Type,
+    };
+    assert_eq!(
+        failure.source.map(|(_, _, group_type)| group_type),
+        Some(group_type),
+        "error_string: {}",
+        err
+    );
+    assert_eq!(
+        failure.decode(expected.kind()),
+        Some(expected),
+        "error_string: {}, expected: {}",
+        err,
+        expected
     );
 }
 
@@ -415,6 +500,10 @@ lazy_static! {
         let bin = include_bytes!("../../build/release/nostr-binding");
         bin.to_vec().into()
     };
+    static ref POW_DIFFICULTY_BIN: Bytes = {
+        let bin = include_bytes!("../../build/release/pow-difficulty");
+        bin.to_vec().into()
+    };
     static ref KEY: Keys = {
         Keys::parse("a9e5f16529cbe055c1f7b6d928b980a2ee0cc0a1f07a8444b85b72b3f1d5c6ba").unwrap()
     };
@@ -426,6 +515,13 @@ lazy_static! {
 /// 1 output cell with nostr type binding type script
 ///
 pub fn new_type_mint_template(schema: TestSchema) -> (Context, TransactionView, Script) {
+    new_type_mint_template_with_config(schema, BindingConfig::default())
+}
+
+pub fn new_type_mint_template_with_config(
+    schema: TestSchema,
+    config: BindingConfig,
+) -> (Context, TransactionView, Script) {
     let mut context = Context::default();
     context.set_capture_debug(false);
     let type_out_point = context.deploy_cell(NOSTR_BINDING_BIN.clone());
@@ -462,12 +558,7 @@ pub fn new_type_mint_template(schema: TestSchema) -> (Context, TransactionView,
     if schema == TestSchema::WrongGlobalUniqueId2 {
         global_unique_id[0] ^= 1;
     }
-    let (json, mut id) = type_script_mint(
-        &KEY,
-        unix_time_now(),
-        "hello,world".into(),
-        global_unique_id,
-    );
+    let (json, mut id) = type_script_mint(&KEY, unix_time_now(), &config, global_unique_id);
     // reset it to correct value
     if schema == TestSchema::WrongGlobalUniqueId2 {
         global_unique_id[0] ^= 1;
@@ -483,8 +574,12 @@ pub fn new_type_mint_template(schema: TestSchema) -> (Context, TransactionView,
     args.extend(&global_unique_id);
     assert_eq!(args.len(), 64);
 
+    if let Some(flags) = config.flags {
+        args.push(flags);
+    }
+    // 65 bytes args are valid with a trailing flags byte
     if schema == TestSchema::WrongArgsLen {
-        args.extend([0u8; 1]);
+        args.extend([0u8; 2]);
     }
 
     let type_script = context
@@ -496,7 +591,13 @@ pub fn new_type_mint_template(schema: TestSchema) -> (Context, TransactionView,
         .lock(always_success_script.clone())
         .type_(Some(type_script.clone()).pack())
         .build()];
-    let mut outputs_data = vec![Bytes::new()];
+    // archival mode stores the bound event in cell data, after other fields
+    let data = if config.flags.unwrap_or_default() & BINDING_FLAG_ARCHIVE != 0 {
+        [config.data, json.clone()].concat().into()
+    } else {
+        config.data
+    };
+    let mut outputs_data = vec![data.clone()];
     if schema == TestSchema::WrongMultiTypeCell {
         outputs.push(
             packed::CellOutput::new_builder()
@@ -505,7 +606,7 @@ pub fn new_type_mint_template(schema: TestSchema) -> (Context, TransactionView,
                 .type_(Some(type_script.clone()).pack())
                 .build(),
         );
-        outputs_data.push(Bytes::new());
+        outputs_data.push(data);
     }
 
     let witness = WitnessArgsBuilder::default()
@@ -528,22 +629,86 @@ pub fn new_type_mint_template(schema: TestSchema) -> (Context, TransactionView,
 // 2 output cells
 //
 pub fn new_lock_template(schema: TestSchema) -> (Context, TransactionView, Script) {
+    new_lock_template_with_flags(schema, None)
+}
+
+pub fn new_lock_template_with_flags(
+    schema: TestSchema,
+    flags: Option<u8>,
+) -> (Context, TransactionView, Script) {
+    new_lock_template_with_args(schema, flags, &[])
+}
+
+/// `fields` of the flags are in the order of their flag bits, see
+/// [`lock_args_v1`]
+pub fn new_lock_template_with_args(
+    schema: TestSchema,
+    flags: Option<u8>,
+    fields: &[u8],
+) -> (Context, TransactionView, Script) {
+    new_lock_template_with_pow(schema, 0, flags, fields)
+}
+
+/// a template with both the pubkey hash and `pow_difficulty` in args, which
+/// are in version 1 with `flags`
+pub fn new_lock_template_with_pow(
+    schema: TestSchema,
+    pow_difficulty: u8,
+    flags: Option<u8>,
+    fields: &[u8],
+) -> (Context, TransactionView, Script) {
+    let rest = flags
+        .map(|flags| lock_args_v1(flags, fields))
+        .unwrap_or_default();
+    new_lock_template_with_raw_args(schema, pow_difficulty, &rest)
+}
+
+/// lock args after the pubkey hash in version 1, `<0x81><flags>` followed by
+/// `fields` split in the order of their flag bits, each prefixed with its
+/// flag as field id and its length. Fields missing in `fields` are left out.
+pub fn lock_args_v1(flags: u8, mut fields: &[u8]) -> Vec<u8> {
+    let mut args = vec![0x81, flags];
+    for (flag, len) in [
+        (LOCK_FLAG_CREATED_AT_WINDOW, 4),
+        (LOCK_FLAG_RECOVERY, 28),
+        (LOCK_FLAG_DYNAMIC_POW, 32),
+        (LOCK_FLAG_CONTENT_CONFIG, 32),
+    ] {
+        if flags & flag == 0 || fields.len() < len {
+            continue;
+        }
+        let (field, rest) = fields.split_at(len);
+        args.extend([flag, len as u8]);
+        args.extend(field);
+        fields = rest;
+    }
+    args
+}
+
+/// a template with `rest` after the pubkey hash in args as it is
+pub fn new_lock_template_with_raw_args(
+    schema: TestSchema,
+    pow_difficulty: u8,
+    rest: &[u8],
+) -> (Context, TransactionView, Script) {
     let mut context = Context::default();
     context.set_capture_debug(false);
     let lock_out_point = context.deploy_cell(NOSTR_LOCK_BIN.clone());
     let pubkey = KEY.public_key().to_bytes().to_vec();
     let mut args = [0u8; 21];
+    args[0] = pow_difficulty;
     let pubkey_hash = blake160(&pubkey);
     (&mut args[1..21]).copy_from_slice(&pubkey_hash);
     if schema == TestSchema::WrongPubkey {
         args[1] ^= 1;
     }
 
-    let args = if schema == TestSchema::WrongArgsLen {
-        Bytes::copy_from_slice(&vec![args.to_vec(), vec![00u8]].concat())
-    } else {
-        Bytes::copy_from_slice(&args)
-    };
+    let mut args = args.to_vec();
+    args.extend(rest);
+    if schema == TestSchema::WrongArgsLen {
+        args.extend([0u8; 2]);
+    }
+    let args = Bytes::from(args);
     let lock_script = context
         .build_script(&lock_out_point, args.into())
         .expect("lock script");
@@ -637,6 +802,579 @@ pub fn new_lock_pow_template(_schema: TestSchema) -> (Context, TransactionView,
     (context, tx, pow_difficult)
 }
 
+///
+/// a nostr type binding transfer transaction template
+/// 1 input cell with nostr type binding type script, holding `config.data`
+/// 1 output cell with the same type script, holding `output_data`
+///
+pub fn new_type_transfer_template(
+    config: BindingConfig,
+    output_data: Bytes,
+) -> (Context, TransactionView) {
+    let mut context = Context::default();
+    context.set_capture_debug(false);
+    let type_out_point = context.deploy_cell(NOSTR_BINDING_BIN.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_script = context
+        .build_script(&always_success_out_point, Bytes::new())
+        .unwrap();
+
+    let mut args = vec![0x11u8; 64];
+    if let Some(flags) = config.flags {
+        args.push(flags);
+    }
+    let type_script = context
+        .build_script(&type_out_point, Bytes::from(args))
+        .unwrap();
+    let output = packed::CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(always_success_script)
+        .type_(Some(type_script).pack())
+        .build();
+    let input_out_point = context.create_cell(output.clone(), config.data);
+    let input = packed::CellInput::new_builder()
+        .previous_output(input_out_point)
+        .build();
+
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(output)
+        .output_data(output_data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+    (context, tx)
+}
+
+/// binding cell data of a royalty paid to `recipient` in capacity only
+pub fn royalty_data(recipient: &[u8], rate: u16) -> Bytes {
+    let mut data = recipient.to_vec();
+    data.extend_from_slice(&rate.to_le_bytes());
+    data.extend_from_slice(&[0u8; 32]);
+    Bytes::from(data)
+}
+
+/// a nostr binding sale transaction, the binding cell carries a royalty of
+/// `rate` basis points, the seller receives `price` and the creator receives
+/// `royalty` from the buyer
+pub fn new_type_sale_template(rate: u16, price: u64, royalty: u64) -> (Context, TransactionView) {
+    let mut context = Context::default();
+    context.set_capture_debug(false);
+    let type_out_point = context.deploy_cell(NOSTR_BINDING_BIN.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let seller_lock = context
+        .build_script(&always_success_out_point, Bytes::from("seller"))
+        .unwrap();
+    let buyer_lock = context
+        .build_script(&always_success_out_point, Bytes::from("buyer"))
+        .unwrap();
+    let creator_lock = context
+        .build_script(&always_success_out_point, Bytes::from("creator"))
+        .unwrap();
+
+    let mut args = vec![0x11u8; 64];
+    args.push(BINDING_FLAG_ROYALTY);
+    let type_script = context
+        .build_script(&type_out_point, Bytes::from(args))
+        .unwrap();
+    let data = royalty_data(creator_lock.calc_script_hash().as_slice(), rate);
+    let binding_input = context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(seller_lock.clone())
+            .type_(Some(type_script.clone()).pack())
+            .build(),
+        data.clone(),
+    );
+    let payment_input = context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity((1000 + price + royalty).pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let outputs = vec![
+        packed::CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(buyer_lock)
+            .type_(Some(type_script).pack())
+            .build(),
+        packed::CellOutput::new_builder()
+            .capacity((1000 + price).pack())
+            .lock(seller_lock)
+            .build(),
+        packed::CellOutput::new_builder()
+            .capacity(royalty.pack())
+            .lock(creator_lock)
+            .build(),
+    ];
+    let outputs_data = vec![data, Bytes::new(), Bytes::new()];
+
+    let tx = TransactionBuilder::default()
+        .input(
+            packed::CellInput::new_builder()
+                .previous_output(binding_input)
+                .build(),
+        )
+        .input(
+            packed::CellInput::new_builder()
+                .previous_output(payment_input)
+                .build(),
+        )
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+    (context, tx)
+}
+
+/// nostr lock script of the key signing template events, in key mode
+pub fn author_nostr_lock(code_hash: [u8; 32]) -> Script {
+    let mut args = vec![0u8];
+    args.extend(blake160(&KEY.public_key().to_bytes()));
+    Script::new_builder()
+        .code_hash(code_hash.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(args).pack())
+        .build()
+}
+
+/// replace lock of the first output
+pub fn set_output_lock(tx: TransactionView, lock: Script) -> TransactionView {
+    let mut outputs: Vec<packed::CellOutput> = tx.outputs().into_iter().collect();
+    outputs[0] = outputs[0].clone().as_builder().lock(lock).build();
+    tx.as_advanced_builder().set_outputs(outputs).build()
+}
+
+/// add a header dep with `timestamp` in seconds
+pub fn add_header_dep(
+    context: &mut Context,
+    tx: TransactionView,
+    timestamp: u64,
+) -> TransactionView {
+    let header = HeaderBuilder::default()
+        .timestamp((timestamp * 1000).pack())
+        .build();
+    context.insert_header(header.clone());
+    tx.as_advanced_builder().header_dep(header.hash()).build()
+}
+
+/// NIP-26 delegation tag granted by `delegator` to `delegatee`
+pub fn delegation_tag(delegator: &Keys, delegatee: &Keys, conditions: &str) -> Tag {
+    let conditions = Conditions::from_str(conditions).unwrap();
+    let delegation = DelegationTag::new(delegator, &delegatee.public_key(), conditions).unwrap();
+    Tag::custom(
+        TagKind::from("delegation"),
+        vec![
+            delegation.delegator_pubkey().to_hex(),
+            delegation.conditions().to_string(),
+            delegation.signature().to_string(),
+        ],
+    )
+}
+
+/// `ckb_session` tag carrying the authorization event of `owner` granting
+/// `session` key
+pub fn session_tag(
+    owner: &Keys,
+    session: &Keys,
+    expiration: u64,
+    max_capacity: u64,
+    allowed_locks: &[[u8; 32]],
+) -> Tag {
+    let mut tags = vec![
+        Tag::custom(
+            TagKind::from("ckb_session_pubkey"),
+            vec![session.public_key().to_hex()],
+        ),
+        Tag::custom(TagKind::from("expiration"), vec![expiration.to_string()]),
+        Tag::custom(
+            TagKind::from("ckb_max_capacity"),
+            vec![max_capacity.to_string()],
+        ),
+    ];
+    for lock in allowed_locks {
+        tags.push(Tag::custom(
+            TagKind::from("ckb_allowed_lock"),
+            vec![hex::encode(lock)],
+        ));
+    }
+    let auth = EventBuilder::new(Kind::from(SESSION_KIND), "", tags)
+        .to_event(owner)
+        .unwrap();
+    Tag::custom(TagKind::from("ckb_session"), vec![auth.as_json()])
+}
+
+/// a payment into an anyone-can-pay nostr lock cell, from `(capacity, data)`
+/// of input to output. Both are of an UDT type when `udt` is set. The payer
+/// cell is locked by always success.
+pub fn new_lock_acp_template(
+    input: (u64, Bytes),
+    output: (u64, Bytes),
+    udt: bool,
+) -> (Context, TransactionView) {
+    let mut context = Context::default();
+    context.set_capture_debug(false);
+    let lock_out_point = context.deploy_cell(NOSTR_LOCK_BIN.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let mut args = blake160(&KEY.public_key().to_bytes()).to_vec();
+    args.insert(0, 0);
+    args.extend(lock_args_v1(LOCK_FLAG_ANYONE_CAN_PAY, &[]));
+    let lock_script = context
+        .build_script(&lock_out_point, Bytes::from(args))
+        .unwrap();
+    let payer_lock = context
+        .build_script(&always_success_out_point, Bytes::from("payer"))
+        .unwrap();
+    let type_script = if udt {
+        context.build_script(&always_success_out_point, Bytes::from("udt"))
+    } else {
+        None
+    };
+
+    let acp_input = context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(input.0.pack())
+            .lock(lock_script.clone())
+            .type_(type_script.clone().pack())
+            .build(),
+        input.1,
+    );
+    let payer_input = context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(10000u64.pack())
+            .lock(payer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let outputs = vec![
+        packed::CellOutput::new_builder()
+            .capacity(output.0.pack())
+            .lock(lock_script)
+            .type_(type_script.pack())
+            .build(),
+        packed::CellOutput::new_builder()
+            .capacity((10000 + input.0 - output.0).pack())
+            .lock(payer_lock)
+            .build(),
+    ];
+    let tx = TransactionBuilder::default()
+        .input(
+            packed::CellInput::new_builder()
+                .previous_output(acp_input)
+                .build(),
+        )
+        .input(
+            packed::CellInput::new_builder()
+                .previous_output(payer_input)
+                .build(),
+        )
+        .outputs(outputs)
+        .output_data(output.1.pack())
+        .output_data(Bytes::new().pack())
+        .build();
+    let tx = context.complete_tx(tx);
+    (context, tx)
+}
+
+/// data of a PoW difficulty config cell
+pub fn difficulty_config_data(
+    difficulty: u8,
+    min_difficulty: u8,
+    target_interval: u64,
+    window: u8,
+    claims: &[u64],
+) -> Bytes {
+    let mut data = vec![difficulty, min_difficulty];
+    data.extend(target_interval.to_le_bytes());
+    data.push(window);
+    for claim in claims {
+        data.extend(claim.to_le_bytes());
+    }
+    Bytes::from(data)
+}
+
+/// deploy nostr lock and the difficulty config type script of `type_id`,
+/// return the nostr lock in dynamic PoW mode with a `floor` difficulty and the
+/// config type script
+pub fn deploy_dynamic_pow(context: &mut Context, floor: u8, type_id: [u8; 32]) -> (Script, Script) {
+    let lock_out_point = context.deploy_cell(NOSTR_LOCK_BIN.clone());
+    let config_out_point = context.deploy_cell(POW_DIFFICULTY_BIN.clone());
+    let lock_code_hash = context
+        .build_script(&lock_out_point, Bytes::new())
+        .unwrap()
+        .code_hash();
+    let mut config_args = type_id.to_vec();
+    config_args.extend(lock_code_hash.as_slice());
+    let config_type = context
+        .build_script(&config_out_point, Bytes::from(config_args))
+        .unwrap();
+    let mut args = vec![floor];
+    args.extend([0u8; 20]);
+    args.extend(lock_args_v1(
+        LOCK_FLAG_DYNAMIC_POW,
+        config_type.calc_script_hash().as_slice(),
+    ));
+    let lock = context
+        .build_script(&lock_out_point, Bytes::from(args))
+        .unwrap();
+    (lock, config_type)
+}
+
+/// a claim of a nostr lock cell in dynamic PoW mode with a `floor`
+/// difficulty, recording itself at block 110 by updating the config cell
+/// holding `config_data`, see `new_difficulty_update_template`
+pub fn new_lock_dynamic_pow_template(config_data: Bytes, floor: u8) -> (Context, TransactionView) {
+    let mut output_data = config_data.to_vec();
+    output_data.extend(110u64.to_le_bytes());
+    new_claim_template(config_data, Bytes::from(output_data), floor, 105, 110)
+}
+
+/// a claim recording itself by updating the config cell from `input_data` to
+/// `output_data`. The config input is created at block `created` and spent
+/// with an absolute block number `since`. Input 0 is the claim locked by
+/// nostr lock in dynamic PoW mode, input 1 is the config cell.
+pub fn new_difficulty_update_template(
+    input_data: Bytes,
+    output_data: Bytes,
+    created: u64,
+    since: u64,
+) -> (Context, TransactionView) {
+    new_claim_template(input_data, output_data, 0, created, since)
+}
+
+fn new_claim_template(
+    input_data: Bytes,
+    output_data: Bytes,
+    floor: u8,
+    created: u64,
+    since: u64,
+) -> (Context, TransactionView) {
+    let mut context = Context::default();
+    context.set_capture_debug(false);
+    let (lock_script, config_type) = deploy_dynamic_pow(&mut context, floor, [1u8; 32]);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success = context
+        .build_script(&always_success_out_point, Bytes::new())
+        .unwrap();
+    let config_output = packed::CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(always_success.clone())
+        .type_(Some(config_type).pack())
+        .build();
+    let config_out_point = context.create_cell(config_output.clone(), input_data);
+    let header = HeaderBuilder::default()
+        .number(created.pack())
+        .epoch(EpochNumberWithFraction::new(0, 0, 1).pack())
+        .build();
+    context.insert_header(header.clone());
+    context.link_cell_with_block(config_out_point.clone(), header.hash(), 0);
+    let claim_out_point = context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script)
+            .build(),
+        Bytes::new(),
+    );
+    let tx = TransactionBuilder::default()
+        .input(
+            packed::CellInput::new_builder()
+                .previous_output(claim_out_point)
+                .build(),
+        )
+        .input(
+            packed::CellInput::new_builder()
+                .previous_output(config_out_point)
+                .since(since.pack())
+                .build(),
+        )
+        .output(
+            packed::CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(always_success)
+                .build(),
+        )
+        .output(config_output)
+        .output_data(Bytes::new().pack())
+        .output_data(output_data.pack())
+        .header_dep(header.hash())
+        .witness(Bytes::new().pack())
+        .build();
+    let tx = context.complete_tx(tx);
+    (context, tx)
+}
+
+/// create a config cell holding `data`, with a legit type id when `valid_type_id`
+pub fn new_difficulty_create_template(
+    data: Bytes,
+    valid_type_id: bool,
+) -> (Context, TransactionView) {
+    let mut context = Context::default();
+    context.set_capture_debug(false);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success = context
+        .build_script(&always_success_out_point, Bytes::new())
+        .unwrap();
+    let input = packed::CellInput::new_builder()
+        .previous_output(
+            context.create_cell(
+                packed::CellOutput::new_builder()
+                    .capacity(1000u64.pack())
+                    .lock(always_success.clone())
+                    .build(),
+                Bytes::new(),
+            ),
+        )
+        .build();
+    let mut type_id = [0u8; 32];
+    let mut blake2b = new_blake2b();
+    blake2b.update(input.as_slice());
+    blake2b.update(&0u64.to_le_bytes());
+    blake2b.finalize(&mut type_id);
+    if !valid_type_id {
+        type_id[0] ^= 1;
+    }
+    let (_, config_type) = deploy_dynamic_pow(&mut context, 0, type_id);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(
+            packed::CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(always_success)
+                .type_(Some(config_type).pack())
+                .build(),
+        )
+        .output_data(data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+    (context, tx)
+}
+
+/// type script of content config cells, an always success script of `args`
+pub fn content_config_type(args: &str) -> Script {
+    Script::new_builder()
+        .code_hash(blake2b_256(ALWAYS_SUCCESS.as_ref()).pack())
+        .hash_type(ScriptHashType::Data1.into())
+        .args(Bytes::from(args.to_string()).pack())
+        .build()
+}
+
+/// add a content config cell of `type_script` holding `data` to cell deps
+pub fn add_content_config(
+    context: &mut Context,
+    tx: TransactionView,
+    type_script: Script,
+    data: Bytes,
+) -> TransactionView {
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock = context
+        .build_script(&always_success_out_point, Bytes::new())
+        .unwrap();
+    let out_point = context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock)
+            .type_(Some(type_script).pack())
+            .build(),
+        data,
+    );
+    tx.as_advanced_builder()
+        .cell_dep(packed::CellDep::new_builder().out_point(out_point).build())
+        .build()
+}
+
+/// data of a content config cell
+pub fn content_config_data(kind: u16, content: &str) -> Bytes {
+    let mut data = kind.to_le_bytes().to_vec();
+    data.extend(sha256::Hash::hash(content.as_bytes()).as_byte_array());
+    Bytes::from(data)
+}
+
+/// bind the unlock event to a chain: add the genesis header of `timestamp` to
+/// header deps and a `ckb_chain` tag naming it to `config`
+pub fn add_chain(
+    context: &mut Context,
+    tx: TransactionView,
+    config: &mut TestConfig,
+    timestamp: u64,
+) -> TransactionView {
+    let genesis = HeaderBuilder::default()
+        .number(0u64.pack())
+        .timestamp(timestamp.pack())
+        .build();
+    context.insert_header(genesis.clone());
+    config.tags.push(Tag::custom(
+        TagKind::from(CHAIN_TAG_NAME),
+        vec![hex::encode(genesis.hash().as_slice())],
+    ));
+    tx.as_advanced_builder().header_dep(genesis.hash()).build()
+}
+
+/// full format CKB address of `script`
+pub fn encode_address(hrp: &str, script: &Script) -> String {
+    let mut payload = vec![0x00];
+    payload.extend(script.code_hash().as_slice());
+    payload.extend(script.hash_type().as_slice());
+    payload.extend(script.args().raw_data());
+    bech32::encode::<bech32::Bech32m>(bech32::Hrp::parse(hrp).unwrap(), &payload).unwrap()
+}
+
+/// the `content` of unlock event in summary mode, generated from `tx`
+pub fn tx_summary(context: &Context, tx: &TransactionView, hrp: &str) -> String {
+    let mut summary = format!(
+        "Signing a CKB transaction on {}\n\n{}\n",
+        hrp, NOSTR_LOCK_WARNING
+    );
+    let format_capacity =
+        |shannons: u64| format!("{}.{:08}", shannons / 100_000_000, shannons % 100_000_000);
+    for (index, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+        let capacity: u64 = output.capacity().unpack();
+        summary += &format!(
+            "\nOutput {}: {} CKB\nTo: {}\n",
+            index,
+            format_capacity(capacity),
+            encode_address(hrp, &output.lock())
+        );
+        if let Some(type_script) = output.type_().to_opt() {
+            let hash_type = match ScriptHashType::try_from(type_script.hash_type()).unwrap() {
+                ScriptHashType::Data => "data",
+                ScriptHashType::Type => "type",
+                ScriptHashType::Data1 => "data1",
+                ScriptHashType::Data2 => "data2",
+            };
+            summary += &format!(
+                "Type: 0x{} {} 0x{}\n",
+                hex::encode(type_script.code_hash().as_slice()),
+                hash_type,
+                hex::encode(type_script.args().raw_data())
+            );
+        }
+        summary += &format!("Data: {} bytes\n", data.len());
+    }
+    let inputs_capacity: u64 = tx
+        .input_pts_iter()
+        .map(|out_point| {
+            let (output, _) = context.get_cell(&out_point).unwrap();
+            Unpack::<u64>::unpack(&output.capacity())
+        })
+        .sum();
+    let outputs_capacity: u64 = tx.outputs_capacity().unwrap().as_u64();
+    summary += &format!(
+        "\nFee: {} CKB\n",
+        format_capacity(inputs_capacity - outputs_capacity)
+    );
+    summary
+}
+
+/// set `since` of all inputs
+pub fn set_inputs_since(tx: TransactionView, since: u64) -> TransactionView {
+    let inputs: Vec<packed::CellInput> = tx
+        .inputs()
+        .into_iter()
+        .map(|input| input.as_builder().since(since.pack()).build())
+        .collect();
+    tx.as_advanced_builder().set_inputs(inputs).build()
+}
+
 pub fn new_both_template(schema: TestSchema) -> (Context, TransactionView, Script) {
     let mut context = Context::default();
     context.set_capture_debug(false);
@@ -675,7 +1413,7 @@ pub fn new_both_template(schema: TestSchema) -> (Context, TransactionView, Scrip
     let (json, mut id) = type_script_mint(
         &KEY,
         unix_time_now(),
-        "hello,world".into(),
+        &BindingConfig::default(),
         global_unique_id,
     );
     // reset it to correct value
@@ -0,0 +1,82 @@
+use ckb_nostr_utils::lock_args::{ArgsFormat, LockArgs, Recovery};
+use serde_json::Value;
+
+fn vectors() -> Value {
+    serde_json::from_str(include_str!("../vectors/lock-args.json")).unwrap()
+}
+
+fn hex_bytes(value: &Value) -> Vec<u8> {
+    hex::decode(&value.as_str().unwrap()[2..]).unwrap()
+}
+
+fn expected(decoded: &Value) -> LockArgs {
+    let format = match decoded["format"].as_str().unwrap() {
+        "legacy" => ArgsFormat::Legacy,
+        "v1" => ArgsFormat::V1,
+        format => panic!("unknown format {}", format),
+    };
+    let recovery = match &decoded["recovery"] {
+        Value::Null => None,
+        recovery => Some(Recovery {
+            pubkey_hash: hex_bytes(&recovery["pubkeyHash"]).try_into().unwrap(),
+            delay: u64::from_str_radix(&recovery["delay"].as_str().unwrap()[2..], 16).unwrap(),
+        }),
+    };
+    LockArgs {
+        format,
+        pow_difficulty: decoded["powDifficulty"].as_u64().unwrap() as u8,
+        pubkey_hash: hex_bytes(&decoded["pubkeyHash"]).try_into().unwrap(),
+        flags: decoded["flags"].as_u64().unwrap() as u8,
+        created_at_window: decoded["createdAtWindow"].as_u64().map(|w| w as u32),
+        recovery,
+        difficulty_config: match &decoded["difficultyConfig"] {
+            Value::Null => None,
+            hash => Some(hex_bytes(hash).try_into().unwrap()),
+        },
+        content_config: match &decoded["contentConfig"] {
+            Value::Null => None,
+            hash => Some(hex_bytes(hash).try_into().unwrap()),
+        },
+        extra_fields: decoded["extraFields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| (field[0].as_u64().unwrap() as u8, hex_bytes(&field[1])))
+            .collect(),
+    }
+}
+
+#[test]
+fn test_lock_args_decode_vectors() {
+    for vector in vectors()["valid"].as_array().unwrap() {
+        let args = hex_bytes(&vector["args"]);
+        let lock_args = LockArgs::parse(&args).unwrap();
+        assert_eq!(
+            lock_args,
+            expected(&vector["decoded"]),
+            "{}",
+            vector["name"]
+        );
+    }
+}
+
+#[test]
+fn test_lock_args_encode_vectors() {
+    for vector in vectors()["valid"].as_array().unwrap() {
+        let args = hex_bytes(&vector["args"]);
+        assert_eq!(
+            expected(&vector["decoded"]).to_bytes(),
+            args,
+            "{}",
+            vector["name"]
+        );
+    }
+}
+
+#[test]
+fn test_lock_args_invalid_vectors() {
+    for vector in vectors()["invalid"].as_array().unwrap() {
+        let args = hex_bytes(&vector["args"]);
+        assert!(LockArgs::parse(&args).is_err(), "{}", vector["name"]);
+    }
+}
@@ -2,6 +2,7 @@ extern crate hex;
 use crate::{
     assert_script_error, new_both_template, sign_lock_script, TestConfig, TestSchema, MAX_CYCLES,
 };
+use ckb_nostr_error::{BindingError, LockError};
 
 #[test]
 fn test_both() {
@@ -25,7 +26,7 @@ fn test_both_failed_lock_sig() {
     );
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 18); // ValidationFail
+    assert_script_error(result.err().unwrap(), LockError::ValidationFail);
 }
 
 #[test]
@@ -34,5 +35,5 @@ fn test_both_failed_mint_sig() {
     let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 60); // TypeIdNotMatch
+    assert_script_error(result.err().unwrap(), BindingError::TypeIdNotMatch);
 }
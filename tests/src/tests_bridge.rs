@@ -0,0 +1,307 @@
+use crate::{new_lock_template, TestSchema, KEY, MAX_CYCLES};
+use ckb_nostr_utils::event::Event;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{DepType, TransactionView},
+    packed::{self, Script},
+    prelude::*,
+    H256,
+};
+use ckb_testtool::context::Context;
+use nostr::{EventBuilder, JsonUtil, Keys, Kind};
+use nostr_binding_bridge::{Bridge, Error, Outcome, PendingTx, Submit};
+use nostr_binding_relay::{Relay, RelayClient, RelayMessage};
+use nostr_binding_sdk::{NostrBinding, NostrBindingSdk, NostrLock, ResolvedTx, Rule, ScriptConfig};
+use std::{cell::RefCell, time::Duration};
+
+const WAIT: Duration = Duration::from_secs(5);
+const FEE_RATE: u64 = 1000;
+
+fn script_config(script: &Script) -> ScriptConfig {
+    ScriptConfig {
+        code_hash: script.code_hash().unpack(),
+        hash_type: script.hash_type().try_into().unwrap(),
+        tx_hash: Default::default(),
+        index: 0,
+        dep_type: DepType::Code,
+    }
+}
+
+fn lock_sdk(lock_script: &Script) -> NostrBindingSdk {
+    NostrBindingSdk {
+        binding: NostrBinding::new(script_config(&Script::default()), "ckt"),
+        lock: NostrLock::new(script_config(lock_script), "ckt"),
+    }
+}
+
+fn relay_event(event: &nostr::Event) -> Event {
+    Event::from_json(event.as_json()).unwrap()
+}
+
+/// A node stand-in verifying submitted transactions in the test context
+struct Node {
+    context: Context,
+    submitted: RefCell<Vec<TransactionView>>,
+}
+
+impl Node {
+    fn new(context: Context) -> Self {
+        Self {
+            context,
+            submitted: RefCell::new(vec![]),
+        }
+    }
+}
+
+impl Submit for Node {
+    fn send_transaction(&self, tx: &TransactionView) -> Result<H256, Error> {
+        self.context
+            .verify_tx(tx, MAX_CYCLES)
+            .map_err(|err| Error::Rpc(err.to_string()))?;
+        self.submitted.borrow_mut().push(tx.clone());
+        Ok(tx.hash().unpack())
+    }
+}
+
+/// A node rejecting every transaction, as for a rule of the transaction the
+/// bridge leaves to the node
+struct Refusing;
+
+impl Submit for Refusing {
+    fn send_transaction(&self, _tx: &TransactionView) -> Result<H256, Error> {
+        Err(Error::Rpc("refused".into()))
+    }
+}
+
+/// A transaction of `schema` paying the fee from the capacity of its nostr
+/// lock input, prepared for the unlock event
+fn prepared_template(schema: TestSchema) -> (Context, ResolvedTx, Script) {
+    let (mut context, template, lock_script) = new_lock_template(schema);
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let cell = |capacity: u64| {
+        packed::CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock_script.clone())
+            .build()
+    };
+    let out_point = context.create_cell(cell(1000_0000_0000), Bytes::new());
+    let tx = template
+        .as_advanced_builder()
+        .set_inputs(vec![packed::CellInput::new(out_point, 0)])
+        .set_outputs(vec![cell(1000_0000_0000)])
+        .set_outputs_data(vec![Bytes::new().pack()])
+        .build();
+    let (tx, _) = lock
+        .prepare_tx_with_fee(tx, &[0], 0, FEE_RATE, |_| Ok(lock.content.clone()))
+        .unwrap();
+    let rtx = ResolvedTx {
+        tx: tx.clone(),
+        inputs: tx
+            .input_pts_iter()
+            .map(|out_point| context.get_cell(&out_point).unwrap())
+            .collect(),
+        cell_deps: tx
+            .cell_deps_iter()
+            .map(|cell_dep| context.get_cell(&cell_dep.out_point()).unwrap())
+            .collect(),
+        header_deps: vec![],
+    };
+    (context, rtx, lock_script)
+}
+
+/// the unlock event of the prepared `tx` with `content`, signed by `keys`
+fn unlock_event(lock: &NostrLock, tx: &TransactionView, content: &str, keys: &Keys) -> Event {
+    let sighash_all = NostrLock::build_sighash_all(tx, &[0]).unwrap();
+    relay_event(
+        &lock
+            .build_unlock_event(sighash_all, content)
+            .to_event(keys)
+            .unwrap(),
+    )
+}
+
+/// connect to `relay` and subscribe the bridge to its unlock events
+fn subscribe<S: Submit>(bridge: &Bridge<S>, relay: &Relay) -> RelayClient {
+    let mut client = RelayClient::connect(&relay.url()).unwrap();
+    bridge.subscribe(&mut client).unwrap();
+    // unlock events are ephemeral, the subscription must be in place before
+    // they are published
+    assert!(matches!(client.recv(WAIT).unwrap(), RelayMessage::Eose(_)));
+    client
+}
+
+#[test]
+fn test_bridge_submit() {
+    let (context, rtx, lock_script) = prepared_template(TestSchema::Normal);
+    let sdk = lock_sdk(&lock_script);
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let node = Node::new(context);
+    let mut bridge = Bridge::new(sdk, node, FEE_RATE);
+    let tx_hash = bridge.add_pending(rtx.clone()).unwrap();
+    assert_eq!(bridge.pending_txs(), vec![tx_hash.clone()]);
+
+    let relay = Relay::bind("127.0.0.1:0").unwrap();
+    let mut client = subscribe(&bridge, &relay);
+    let mut signer = RelayClient::connect(&relay.url()).unwrap();
+
+    // an unlock event of another transaction is ignored
+    let other = EventBuilder::new(
+        Kind::from(lock.kind),
+        &lock.content,
+        lock.build_tags([0u8; 32]),
+    )
+    .to_event(&KEY)
+    .unwrap();
+    signer.publish(&relay_event(&other)).unwrap();
+    assert!(bridge.poll(&mut client, WAIT).unwrap().is_none());
+
+    // rule 3 is checked before the event is taken
+    let content = lock.content.replacen('S', "s", 1);
+    signer
+        .publish(&unlock_event(&lock, &rtx.tx, &content, &KEY))
+        .unwrap();
+    match bridge.poll(&mut client, WAIT).unwrap() {
+        Some(Outcome::Rejected {
+            witness_index: 0,
+            error: Error::Rule(diagnostic),
+            ..
+        }) => assert_eq!(diagnostic.rule, Rule::KindAndContent),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+    // an event of another length doesn't fit the reserved fee
+    signer
+        .publish(&unlock_event(&lock, &rtx.tx, "other", &KEY))
+        .unwrap();
+    assert!(matches!(
+        bridge.poll(&mut client, WAIT).unwrap(),
+        Some(Outcome::Rejected {
+            error: Error::Sdk(nostr_binding_sdk::Error::UnlockEventLengthMismatched { .. }),
+            ..
+        })
+    ));
+    assert_eq!(bridge.pending_txs(), vec![tx_hash.clone()]);
+
+    signer
+        .publish(&unlock_event(&lock, &rtx.tx, &lock.content, &KEY))
+        .unwrap();
+    match bridge.poll(&mut client, WAIT).unwrap() {
+        Some(Outcome::Submitted { tx_hash: submitted }) => assert_eq!(submitted, tx_hash),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+    assert!(bridge.pending_txs().is_empty());
+    let submitted = bridge.submitter().submitted.borrow();
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0].hash(), rtx.tx.hash());
+}
+
+#[test]
+fn test_bridge_wrong_key() {
+    let (context, rtx, lock_script) = prepared_template(TestSchema::Normal);
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let node = Node::new(context);
+    let mut bridge = Bridge::new(lock_sdk(&lock_script), node, FEE_RATE);
+    let tx_hash = bridge.add_pending(rtx.clone()).unwrap();
+
+    // the event of another key signs the right sighash_all, but never gets
+    // to the node
+    let event = unlock_event(&lock, &rtx.tx, &lock.content, &Keys::generate());
+    match bridge.handle_event(&event) {
+        Some(Outcome::Rejected {
+            tx_hash: rejected,
+            witness_index: 0,
+            error: Error::Rule(diagnostic),
+        }) => {
+            assert_eq!(rejected, tx_hash);
+            assert_eq!(diagnostic.rule, Rule::Pubkey);
+        }
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+    assert!(bridge.submitter().submitted.borrow().is_empty());
+    assert_eq!(bridge.pending_txs(), vec![tx_hash.clone()]);
+
+    // the group keeps waiting for the owner
+    let event = unlock_event(&lock, &rtx.tx, &lock.content, &KEY);
+    match bridge.handle_event(&event) {
+        Some(Outcome::Submitted { tx_hash: submitted }) => assert_eq!(submitted, tx_hash),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
+
+#[test]
+fn test_bridge_node_rejection() {
+    let (_, rtx, lock_script) = prepared_template(TestSchema::Normal);
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let mut bridge = Bridge::new(lock_sdk(&lock_script), Refusing, FEE_RATE);
+    let tx_hash = bridge.add_pending(rtx.clone()).unwrap();
+
+    let event = unlock_event(&lock, &rtx.tx, &lock.content, &KEY);
+    match bridge.handle_event(&event) {
+        Some(Outcome::Failed {
+            tx_hash: failed,
+            error: Error::Rpc(_),
+            script_error,
+        }) => {
+            assert_eq!(failed, tx_hash);
+            assert_eq!(script_error, None);
+        }
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+    // the transaction is dropped
+    assert!(bridge.pending_txs().is_empty());
+    assert!(bridge.handle_event(&event).is_none());
+}
+
+#[test]
+fn test_bridge_add_pending() {
+    let (context, rtx, lock_script) = prepared_template(TestSchema::Normal);
+    let node = Node::new(context);
+
+    let mut bridge = Bridge::new(lock_sdk(&lock_script), node, FEE_RATE);
+    // without the cells of inputs
+    assert!(matches!(
+        bridge.add_pending(ResolvedTx {
+            inputs: vec![],
+            ..rtx.clone()
+        }),
+        Err(Error::InputCellsMismatched {
+            expected: 1,
+            actual: 0
+        })
+    ));
+    // without dummy locks
+    let unprepared = rtx
+        .tx
+        .as_advanced_builder()
+        .set_witnesses(vec![packed::WitnessArgs::default().as_bytes().pack()])
+        .build();
+    assert!(matches!(
+        bridge.add_pending(ResolvedTx {
+            tx: unprepared,
+            ..rtx.clone()
+        }),
+        Err(Error::NothingToSign)
+    ));
+
+    // handed over in a file
+    let path = std::env::temp_dir().join(format!("nostr-binding-pending-{:x}.json", rtx.tx.hash()));
+    PendingTx::from(&rtx).save(&path).unwrap();
+    let loaded = PendingTx::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let tx_hash = bridge.add_pending(loaded.clone().into()).unwrap();
+    assert_eq!(tx_hash, rtx.tx.hash().unpack());
+    assert!(matches!(
+        bridge.add_pending(loaded.into()),
+        Err(Error::AlreadyPending(hash)) if hash == tx_hash
+    ));
+
+    // the reserved fee is below the minimal fee rate
+    let mut bridge = Bridge::new(
+        lock_sdk(&lock_script),
+        Node::new(Context::default()),
+        FEE_RATE * 2,
+    );
+    assert!(matches!(
+        bridge.add_pending(rtx),
+        Err(Error::FeeTooLow { .. })
+    ));
+}
@@ -0,0 +1,300 @@
+use crate::{new_lock_pow_template, new_lock_template, new_type_mint_template, KEY, MAX_CYCLES};
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{self, Script},
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use clap::Parser;
+use nostr::{
+    nips::nip49::{EncryptedSecretKey, KeySecurity},
+    ToBech32,
+};
+use nostr_binding_cli::{run, Cli, TxFile};
+use nostr_binding_sdk::fee::tx_size;
+use std::path::PathBuf;
+
+/// a transaction file in the temp dir, removed when dropped
+struct TempTx(PathBuf);
+
+impl TempTx {
+    fn new(name: &str, file: Option<TxFile>) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "nostr-binding-cli-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        match file {
+            Some(file) => file.save(&path).unwrap(),
+            None => {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+        Self(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+
+    fn tx(&self) -> TransactionView {
+        TxFile::load(&self.0).unwrap().tx()
+    }
+}
+
+impl Drop for TempTx {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn input_cells(context: &Context, tx: &TransactionView) -> Vec<packed::CellOutput> {
+    tx.input_pts_iter()
+        .map(|out_point| context.get_cell(&out_point).unwrap().0)
+        .collect()
+}
+
+/// run the CLI against scripts deployed in the test context
+fn cli(script: &Script, args: &[&str]) -> Result<String, String> {
+    let code_hash = hex::encode(script.code_hash().raw_data());
+    let mut argv = vec!["nostr-binding", "--lock-code-hash", &code_hash];
+    argv.extend(["--binding-code-hash", &code_hash]);
+    argv.extend(args);
+    let cli = Cli::try_parse_from(argv).map_err(|e| e.to_string())?;
+    run(cli).map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_cli_sign() {
+    let (context, tx, lock_script) = new_lock_template(crate::TestSchema::Normal);
+    let file = TempTx::new("sign", Some(TxFile::new(&tx, &input_cells(&context, &tx))));
+    let nsec = KEY.secret_key().unwrap().to_bech32().unwrap();
+
+    // sighash_all of the prepared transaction is the signed one
+    cli(&lock_script, &["prepare", "--tx", file.path()]).unwrap();
+    let sighash_all = cli(&lock_script, &["sighash", "--tx", file.path()]).unwrap();
+    cli(&lock_script, &["sign", "--tx", file.path(), "--key", &nsec]).unwrap();
+    context
+        .verify_tx(&file.tx(), MAX_CYCLES)
+        .expect("pass verification");
+
+    let inspect = cli(&lock_script, &["inspect", "--tx", file.path()]).unwrap();
+    assert!(inspect.contains(&sighash_all));
+    cli(&lock_script, &["verify", "--tx", file.path()]).unwrap();
+
+    // the signature is no longer valid when outputs change
+    let tx = file
+        .tx()
+        .as_advanced_builder()
+        .set_outputs(vec![tx.outputs().get(0).unwrap()])
+        .set_outputs_data(vec![Bytes::new().pack()])
+        .build();
+    let tampered = TempTx::new(
+        "sign-tampered",
+        Some(TxFile::new(&tx, &input_cells(&context, &tx))),
+    );
+    let err = cli(&lock_script, &["verify", "--tx", tampered.path()]).unwrap_err();
+    assert!(err.contains("ckb_sighash_all"));
+}
+
+#[test]
+fn test_cli_sign_ncryptsec() {
+    let (context, tx, lock_script) = new_lock_template(crate::TestSchema::Normal);
+    let file = TempTx::new("ncryptsec", Some(TxFile::new(&tx, &[])));
+    let ncryptsec = EncryptedSecretKey::new(
+        KEY.secret_key().unwrap(),
+        "password",
+        8,
+        KeySecurity::Unknown,
+    )
+    .unwrap()
+    .to_bech32()
+    .unwrap();
+
+    // lock inputs can't be found without input cells
+    let err = cli(
+        &lock_script,
+        &["sign", "--tx", file.path(), "--key", &ncryptsec],
+    )
+    .unwrap_err();
+    assert!(err.contains("--lock-index"));
+    let err = cli(
+        &lock_script,
+        &[
+            "sign",
+            "--tx",
+            file.path(),
+            "--lock-index",
+            "0",
+            "--key",
+            &ncryptsec,
+        ],
+    )
+    .unwrap_err();
+    assert!(err.contains("--password"));
+    cli(
+        &lock_script,
+        &[
+            "sign",
+            "--tx",
+            file.path(),
+            "--lock-index",
+            "0",
+            "--key",
+            &ncryptsec,
+            "--password",
+            "password",
+        ],
+    )
+    .unwrap();
+    context
+        .verify_tx(&file.tx(), MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_cli_fee() {
+    let (mut context, template, lock_script) = new_lock_template(crate::TestSchema::Normal);
+    let cell = |capacity: u64| {
+        packed::CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock_script.clone())
+            .build()
+    };
+    let out_point = context.create_cell(cell(1000_0000_0000), Bytes::new());
+    let tx = template
+        .as_advanced_builder()
+        .set_inputs(vec![packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()])
+        .set_outputs(vec![cell(500_0000_0000), cell(500_0000_0000)])
+        .build();
+    let file = TempTx::new("fee", Some(TxFile::new(&tx, &input_cells(&context, &tx))));
+    let nsec = KEY.secret_key().unwrap().to_bech32().unwrap();
+
+    assert!(cli(
+        &lock_script,
+        &["prepare", "--tx", file.path(), "--fee-rate", "1000"]
+    )
+    .is_err());
+    let output = cli(
+        &lock_script,
+        &[
+            "prepare",
+            "--tx",
+            file.path(),
+            "--fee-rate",
+            "1000",
+            "--change",
+            "1",
+        ],
+    )
+    .unwrap();
+    assert!(output.contains("paid a fee of"));
+    let prepared = file.tx();
+    cli(&lock_script, &["sign", "--tx", file.path(), "--key", &nsec]).unwrap();
+    let signed = file.tx();
+    context
+        .verify_tx(&signed, MAX_CYCLES)
+        .expect("pass verification");
+    assert_eq!(
+        prepared.outputs_capacity().unwrap(),
+        signed.outputs_capacity().unwrap()
+    );
+    assert_eq!(tx_size(&prepared), tx_size(&signed));
+}
+
+#[test]
+fn test_cli_mine() {
+    let (context, tx, pow_difficulty) = new_lock_pow_template(crate::TestSchema::Normal);
+    let cells = input_cells(&context, &tx);
+    let lock_script = cells[0].lock();
+    let file = TempTx::new("mine", Some(TxFile::new(&tx, &cells)));
+    cli(
+        &lock_script,
+        &[
+            "mine",
+            "--tx",
+            file.path(),
+            "--pow",
+            &pow_difficulty.to_string(),
+        ],
+    )
+    .unwrap();
+    context
+        .verify_tx(&file.tx(), MAX_CYCLES)
+        .expect("pass verification");
+    cli(&lock_script, &["verify", "--tx", file.path()]).unwrap();
+}
+
+#[test]
+fn test_cli_mint() {
+    let (mut context, template, type_script) = new_type_mint_template(crate::TestSchema::Normal);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock = context
+        .build_script(&always_success_out_point, Bytes::new())
+        .unwrap();
+    let lock_json = format!(
+        r#"{{"code_hash":"0x{}","hash_type":"type","args":"0x"}}"#,
+        hex::encode(lock.code_hash().raw_data())
+    );
+    let out_point = template.inputs().get(0).unwrap().previous_output();
+    let input = format!(
+        "0x{}:{}",
+        hex::encode(out_point.tx_hash().raw_data()),
+        u32::from_le_bytes(out_point.index().as_slice().try_into().unwrap())
+    );
+    let nsec = KEY.secret_key().unwrap().to_bech32().unwrap();
+
+    let file = TempTx::new("mint", None);
+    cli(
+        &type_script,
+        &[
+            "mint",
+            "--tx",
+            file.path(),
+            "--input",
+            &input,
+            "--key",
+            &nsec,
+            "--content",
+            "hello,world",
+            "--tag",
+            "t,nostr",
+            "--lock",
+            &lock_json,
+        ],
+    )
+    .unwrap();
+    cli(&type_script, &["verify", "--tx", file.path()]).unwrap();
+
+    // deps of the testnet are replaced with the ones in the test context
+    let tx = file
+        .tx()
+        .as_advanced_builder()
+        .set_cell_deps(template.cell_deps().into_iter().collect())
+        .build();
+    assert_eq!(
+        tx.inputs().get(0).unwrap(),
+        template.inputs().get(0).unwrap()
+    );
+    let tx = context.complete_tx(tx);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_cli_args() {
+    let script = Script::default();
+    let path = "nostr-binding-cli-missing.json";
+    assert!(cli(&script, &["mine", "--tx", path]).is_err());
+    assert!(cli(&script, &["mint", "--tx", path, "--input", "0x00"])
+        .unwrap_err()
+        .contains("out point"));
+    assert!(cli(&script, &["sighash", "--tx", path])
+        .unwrap_err()
+        .contains("failed to read"));
+}
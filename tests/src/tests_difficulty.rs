@@ -0,0 +1,168 @@
+use crate::{
+    assert_script_error, difficulty_config_data, new_difficulty_create_template,
+    new_difficulty_update_template, sign_pow_lock_script, unix_time_now, TestSchema, KEY,
+    MAX_CYCLES,
+};
+use ckb_nostr_error::DifficultyError;
+use ckb_testtool::ckb_types::{bytes::Bytes, core::TransactionView, prelude::*};
+
+fn sign_claim(tx: TransactionView, difficulty: u8) -> TransactionView {
+    sign_pow_lock_script(
+        &KEY,
+        unix_time_now(),
+        vec![0],
+        2,
+        difficulty,
+        tx,
+        TestSchema::Normal,
+    )
+}
+
+#[test]
+fn test_difficulty_create() {
+    let (context, tx) =
+        new_difficulty_create_template(difficulty_config_data(2, 1, 10, 4, &[]), true);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_difficulty_create_wrong_type_id() {
+    let (context, tx) =
+        new_difficulty_create_template(difficulty_config_data(2, 1, 10, 4, &[]), false);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), DifficultyError::TypeIdNotMatch);
+}
+
+#[test]
+fn test_difficulty_create_with_claims() {
+    let (context, tx) =
+        new_difficulty_create_template(difficulty_config_data(2, 1, 10, 4, &[5]), true);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), DifficultyError::InvalidConfigData);
+}
+
+#[test]
+fn test_difficulty_create_below_min() {
+    let (context, tx) =
+        new_difficulty_create_template(difficulty_config_data(1, 2, 10, 4, &[]), true);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), DifficultyError::InvalidConfigData);
+}
+
+#[test]
+fn test_difficulty_record_claim() {
+    let (context, tx) = new_difficulty_update_template(
+        difficulty_config_data(2, 1, 10, 4, &[100]),
+        difficulty_config_data(2, 1, 10, 4, &[100, 110]),
+        105,
+        110,
+    );
+    let tx = sign_claim(tx, 2);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_difficulty_retarget_up() {
+    // 3 intervals of 10 blocks expected, came in 6
+    let (context, tx) = new_difficulty_update_template(
+        difficulty_config_data(2, 1, 10, 4, &[100, 102, 104]),
+        difficulty_config_data(3, 1, 10, 4, &[106]),
+        104,
+        106,
+    );
+    let tx = sign_claim(tx, 2);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_difficulty_retarget_down() {
+    // 3 intervals of 10 blocks expected, came in 100
+    let (context, tx) = new_difficulty_update_template(
+        difficulty_config_data(3, 1, 10, 4, &[100, 150, 180]),
+        difficulty_config_data(2, 1, 10, 4, &[200]),
+        180,
+        200,
+    );
+    let tx = sign_claim(tx, 3);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_difficulty_retarget_floor() {
+    let (context, tx) = new_difficulty_update_template(
+        difficulty_config_data(2, 2, 10, 4, &[100, 150, 180]),
+        difficulty_config_data(1, 2, 10, 4, &[200]),
+        180,
+        200,
+    );
+    let tx = sign_claim(tx, 2);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), DifficultyError::InvalidConfigData);
+}
+
+#[test]
+fn test_difficulty_retarget_mismatched() {
+    let (context, tx) = new_difficulty_update_template(
+        difficulty_config_data(2, 1, 10, 4, &[100, 102, 104]),
+        difficulty_config_data(2, 1, 10, 4, &[106]),
+        104,
+        106,
+    );
+    let tx = sign_claim(tx, 2);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), DifficultyError::RetargetMismatched);
+}
+
+#[test]
+fn test_difficulty_config_changed() {
+    let (context, tx) = new_difficulty_update_template(
+        difficulty_config_data(2, 1, 10, 4, &[100]),
+        difficulty_config_data(2, 1, 20, 4, &[100, 110]),
+        105,
+        110,
+    );
+    let tx = sign_claim(tx, 2);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), DifficultyError::ConfigChanged);
+}
+
+#[test]
+fn test_difficulty_claim_before_config() {
+    // claimed at a block earlier than the config input is created
+    let (context, tx) = new_difficulty_update_template(
+        difficulty_config_data(2, 1, 10, 4, &[100]),
+        difficulty_config_data(2, 1, 10, 4, &[100, 104]),
+        105,
+        104,
+    );
+    let tx = sign_claim(tx, 2);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), DifficultyError::InvalidClaimSince);
+}
+
+#[test]
+fn test_difficulty_claim_not_found() {
+    let (context, tx) = new_difficulty_update_template(
+        difficulty_config_data(2, 1, 10, 4, &[100]),
+        difficulty_config_data(2, 1, 10, 4, &[100, 110]),
+        105,
+        110,
+    );
+    // drop the claim input
+    let inputs: Vec<_> = tx.inputs().into_iter().skip(1).collect();
+    let tx = tx
+        .as_advanced_builder()
+        .set_inputs(inputs)
+        .set_witnesses(vec![Bytes::new().pack()])
+        .build();
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), DifficultyError::ClaimNotFound);
+}
@@ -0,0 +1,324 @@
+use crate::{
+    new_type_mint_template, new_type_mint_template_with_config, BindingConfig, TestSchema,
+    BINDING_FLAG_ARCHIVE, MAX_CYCLES,
+};
+use ckb_nostr_utils::event::Event;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{
+        BlockBuilder, BlockView, DepType, EpochNumberWithFraction, TransactionBuilder,
+        TransactionView,
+    },
+    packed::{self, CellInput, OutPoint, Script, WitnessArgs},
+    prelude::*,
+    H256,
+};
+use nostr_binding_indexer::{api, Api, Error, Indexer, MockSource};
+use nostr_binding_sdk::ScriptConfig;
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+fn script_config(script: &Script) -> ScriptConfig {
+    ScriptConfig {
+        code_hash: script.code_hash().unpack(),
+        hash_type: script.hash_type().try_into().unwrap(),
+        tx_hash: Default::default(),
+        index: 0,
+        dep_type: DepType::Code,
+    }
+}
+
+/// a chain of the genesis block only
+fn genesis() -> MockSource {
+    MockSource::new(vec![BlockBuilder::default().number(0.pack()).build()])
+}
+
+/// append a block of `txs` to the tip, `nonce` tells forks apart
+fn push_block(source: &MockSource, txs: Vec<TransactionView>, nonce: u128) -> BlockView {
+    let parent = source.tip().unwrap();
+    let number = parent.number() + 1;
+    let block = BlockBuilder::default()
+        .parent_hash(parent.hash())
+        .number(number.pack())
+        .epoch(EpochNumberWithFraction::new(0, number, 1800).pack())
+        .nonce(nonce.pack())
+        .transactions(txs)
+        .build();
+    source.push(block.clone());
+    block
+}
+
+/// the event in the mint witness of `tx`
+fn mint_event(tx: &TransactionView) -> Event {
+    let witness = WitnessArgs::from_slice(&tx.witnesses().get(0).unwrap().raw_data()).unwrap();
+    Event::from_json(witness.output_type().to_opt().unwrap().raw_data()).unwrap()
+}
+
+fn event_id(type_script: &Script) -> String {
+    hex::encode(&type_script.args().raw_data()[0..32])
+}
+
+/// move the cell at `out_point` to `lock`, or burn it without `output`
+fn spend(out_point: OutPoint, output: Option<packed::CellOutput>) -> TransactionView {
+    let builder = TransactionBuilder::default().input(CellInput::new(out_point, 0));
+    match output {
+        Some(output) => builder.output(output).output_data(Bytes::new().pack()),
+        None => builder,
+    }
+    .build()
+}
+
+fn lock_hash(script: &Script) -> H256 {
+    script.calc_script_hash().unpack()
+}
+
+#[test]
+fn test_indexer_transfer() {
+    let (context, mint, type_script) = new_type_mint_template(TestSchema::Normal);
+    context
+        .verify_tx(&mint, MAX_CYCLES)
+        .expect("pass verification");
+    let event_id = event_id(&type_script);
+    let owner = mint.outputs().get(0).unwrap().lock();
+    let source = genesis();
+    let mut indexer = Indexer::new(source.clone(), script_config(&type_script), 0);
+
+    push_block(&source, vec![mint.clone()], 0);
+    assert_eq!(indexer.sync().unwrap(), 2);
+    let cell = indexer.store().cell_by_event(&event_id).unwrap();
+    assert_eq!(cell.out_point, OutPoint::new(mint.hash(), 0).into());
+    assert_eq!(cell.block_number, 1);
+    assert_eq!(cell.lock_hash, lock_hash(&owner));
+    assert_eq!(indexer.store().event(&event_id), Some(&mint_event(&mint)));
+    let bindings = indexer.store().bindings_by_lock(&lock_hash(&owner));
+    assert_eq!(bindings.len(), 1);
+    assert_eq!(bindings[0].event_id, event_id);
+
+    // transfer to a new lock
+    let new_owner = owner
+        .clone()
+        .as_builder()
+        .args(Bytes::from("new owner").pack())
+        .build();
+    let transfer = spend(
+        OutPoint::new(mint.hash(), 0),
+        Some(
+            mint.outputs()
+                .get(0)
+                .unwrap()
+                .as_builder()
+                .lock(new_owner.clone())
+                .build(),
+        ),
+    );
+    push_block(&source, vec![transfer.clone()], 0);
+    assert_eq!(indexer.sync().unwrap(), 1);
+    assert_eq!(
+        indexer.store().cell_by_event(&event_id).unwrap().out_point,
+        OutPoint::new(transfer.hash(), 0).into()
+    );
+    assert!(indexer
+        .store()
+        .bindings_by_lock(&lock_hash(&owner))
+        .is_empty());
+    let bindings = indexer.store().bindings_by_lock(&lock_hash(&new_owner));
+    assert_eq!(bindings.len(), 1);
+    // the event of the mint is kept
+    assert_eq!(bindings[0].event, Some(mint_event(&mint)));
+
+    // the event is known after the cell is burnt
+    push_block(
+        &source,
+        vec![spend(OutPoint::new(transfer.hash(), 0), None)],
+        0,
+    );
+    indexer.sync().unwrap();
+    let binding = indexer.store().binding(&event_id).unwrap();
+    assert_eq!(binding.cell, None);
+    assert_eq!(binding.event, Some(mint_event(&mint)));
+    assert_eq!(indexer.store().cells().count(), 0);
+}
+
+#[test]
+fn test_indexer_archive() {
+    let config = BindingConfig {
+        flags: Some(BINDING_FLAG_ARCHIVE),
+        ..Default::default()
+    };
+    let (context, mint, type_script) =
+        new_type_mint_template_with_config(TestSchema::Normal, config);
+    let event = mint_event(&mint);
+    let mint = mint.as_advanced_builder().set_witnesses(Vec::new()).build();
+    context
+        .verify_tx(&mint, MAX_CYCLES)
+        .expect("pass verification");
+
+    let source = genesis();
+    push_block(&source, vec![mint], 0);
+    let mut indexer = Indexer::new(source, script_config(&type_script), 0);
+    indexer.sync().unwrap();
+    // the event is read from the cell data
+    assert_eq!(indexer.store().event(&event_id(&type_script)), Some(&event));
+}
+
+#[test]
+fn test_indexer_skip_mismatched_event() {
+    let (_, mint, type_script) = new_type_mint_template(TestSchema::WrongId);
+    let source = genesis();
+    push_block(&source, vec![mint], 0);
+    let mut indexer = Indexer::new(source, script_config(&type_script), 0);
+    indexer.sync().unwrap();
+    // the cell is indexed by its args, the witness event doesn't match them
+    let binding = indexer.store().binding(&event_id(&type_script)).unwrap();
+    assert!(binding.cell.is_some());
+    assert_eq!(binding.event, None);
+}
+
+#[test]
+fn test_indexer_reorg() {
+    let (_, mint, type_script) = new_type_mint_template(TestSchema::Normal);
+    let event_id = event_id(&type_script);
+    let source = genesis();
+    let mut indexer = Indexer::new(source.clone(), script_config(&type_script), 0);
+    push_block(&source, vec![mint.clone()], 0);
+    let transfer = spend(
+        OutPoint::new(mint.hash(), 0),
+        Some(mint.outputs().get(0).unwrap()),
+    );
+    push_block(&source, vec![transfer], 0);
+    indexer.sync().unwrap();
+
+    // the transfer is forked away, the mint is kept
+    source.truncate(1);
+    push_block(&source, vec![], 1);
+    let tip = push_block(&source, vec![], 1);
+    assert_eq!(indexer.sync().unwrap(), 2);
+    assert_eq!(indexer.tip(), Some((tip.number(), tip.hash().unpack())));
+    assert_eq!(
+        indexer.store().cell_by_event(&event_id).unwrap().out_point,
+        OutPoint::new(mint.hash(), 0).into()
+    );
+
+    // and then the mint, once the fork is longer
+    source.truncate(0);
+    for _ in 0..4 {
+        push_block(&source, vec![], 2);
+    }
+    indexer.sync().unwrap();
+    assert_eq!(indexer.store().binding(&event_id), None);
+}
+
+#[test]
+fn test_indexer_reorg_same_block() {
+    let (_, mint, type_script) = new_type_mint_template(TestSchema::Normal);
+    let event_id = event_id(&type_script);
+    let source = genesis();
+    let mut indexer = Indexer::new(source.clone(), script_config(&type_script), 0);
+    // minted and transferred in the same block
+    let transfer = spend(
+        OutPoint::new(mint.hash(), 0),
+        Some(mint.outputs().get(0).unwrap()),
+    );
+    push_block(&source, vec![mint.clone(), transfer.clone()], 0);
+    indexer.sync().unwrap();
+    assert_eq!(
+        indexer.store().cell_by_event(&event_id).unwrap().out_point,
+        OutPoint::new(transfer.hash(), 0).into()
+    );
+
+    // neither cell is live on the fork
+    source.truncate(0);
+    push_block(&source, vec![], 1);
+    push_block(&source, vec![], 1);
+    indexer.sync().unwrap();
+    assert_eq!(indexer.store().binding(&event_id), None);
+    assert_eq!(indexer.store().cells().count(), 0);
+}
+
+#[test]
+fn test_indexer_reorg_too_deep() {
+    let source = genesis();
+    for _ in 0..nostr_binding_indexer::indexer::MAX_ROLLBACK_BLOCKS + 1 {
+        push_block(&source, vec![], 0);
+    }
+    let mut indexer = Indexer::new(source.clone(), script_config(&Script::default()), 0);
+    indexer.sync().unwrap();
+
+    source.truncate(0);
+    for _ in 0..nostr_binding_indexer::indexer::MAX_ROLLBACK_BLOCKS + 2 {
+        push_block(&source, vec![], 1);
+    }
+    assert!(matches!(indexer.sync(), Err(Error::ReorgTooDeep(_))));
+}
+
+/// GET `path` from the API, returning the status and JSON body
+fn get(addr: &str, path: &str) -> (u16, serde_json::Value) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "GET {} HTTP/1.0\r\nHost: {}\r\n\r\n", path, addr).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn test_indexer_api() {
+    let (_, mint, type_script) = new_type_mint_template(TestSchema::Normal);
+    let event_id = event_id(&type_script);
+    let owner = lock_hash(&mint.outputs().get(0).unwrap().lock());
+
+    // blocks exported to a file
+    let source = genesis();
+    push_block(&source, vec![mint.clone()], 0);
+    let path = std::env::temp_dir().join(format!("nostr-binding-blocks-{}.json", event_id));
+    source.save(&path).unwrap();
+    let source = MockSource::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut indexer = Indexer::new(source, script_config(&type_script), 0);
+    indexer.sync().unwrap();
+    let (_, tip_hash) = indexer.tip().unwrap();
+    let indexer = Arc::new(Mutex::new(indexer));
+    let api = Api::bind("127.0.0.1:0").unwrap();
+    let addr = api.local_addr().unwrap().to_string();
+    let serving = indexer.clone();
+    thread::spawn(move || api.serve(&serving));
+
+    let (status, tip) = get(&addr, "/tip");
+    assert_eq!(status, 200);
+    assert_eq!(tip["block_number"], 1);
+    assert_eq!(tip["block_hash"], format!("{:#x}", tip_hash));
+
+    let (status, binding) = get(&addr, &format!("/events/{}", event_id));
+    assert_eq!(status, 200);
+    assert_eq!(binding["event_id"], event_id);
+    assert_eq!(
+        binding["cell"]["out_point"]["tx_hash"],
+        format!("{:#x}", mint.hash())
+    );
+    // the event as it was signed
+    assert_eq!(
+        binding["event"],
+        serde_json::to_value(mint_event(&mint)).unwrap()
+    );
+    assert_eq!(
+        api::handle(&indexer.lock().unwrap(), &format!("/events/{}", event_id)),
+        (status, binding)
+    );
+
+    let (status, bindings) = get(&addr, &format!("/locks/{:#x}/events", owner));
+    assert_eq!(status, 200);
+    assert_eq!(bindings.as_array().unwrap().len(), 1);
+    assert_eq!(bindings[0]["event_id"], event_id);
+    let (_, bindings) = get(&addr, &format!("/locks/{:x}/events", H256::default()));
+    assert_eq!(bindings, serde_json::json!([]));
+
+    assert_eq!(get(&addr, &format!("/events/{:x}", H256::default())).0, 404);
+    assert_eq!(get(&addr, "/events/xyz").0, 400);
+    assert_eq!(get(&addr, "/unknown").0, 404);
+}
@@ -1,13 +1,25 @@
 extern crate hex;
 use crate::{
-    assert_script_error, get_witness, new_lock_pow_template, new_lock_template, sign_lock_script,
-    sign_pow_lock_script, unix_time_now, update_witness, TestConfig, TestSchema, KEY, MAX_CYCLES,
+    add_chain, add_content_config, add_header_dep, assert_script_error, blake160,
+    content_config_data, content_config_type, delegation_tag, difficulty_config_data, get_witness,
+    new_lock_acp_template, new_lock_dynamic_pow_template, new_lock_pow_template, new_lock_template,
+    new_lock_template_with_args, new_lock_template_with_flags, new_lock_template_with_pow,
+    new_lock_template_with_raw_args, session_tag, set_inputs_since, set_output_lock,
+    sign_lock_script, sign_pow_lock_script, tx_summary, unix_time_now, update_witness, TestConfig,
+    TestSchema, KEY, LOCK_FLAG_ANYONE_CAN_PAY, LOCK_FLAG_CONTENT_CONFIG,
+    LOCK_FLAG_CREATED_AT_WINDOW, LOCK_FLAG_KEY_AND_POW, LOCK_FLAG_RECOVERY,
+    LOCK_FLAG_SUMMARY_CONTENT, MAX_CYCLES,
 };
+use ckb_nostr_error::LockError;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
 use ckb_testtool::ckb_types::{
     bytes::Bytes,
+    core::TransactionView,
     packed::{self},
     prelude::*,
 };
+use ckb_testtool::context::Context;
+use nostr::Keys;
 
 #[test]
 fn test_unlock_lock() {
@@ -52,7 +64,7 @@ fn test_unlock_lock_pow_failed_difficulty() {
         TestSchema::Normal,
     );
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 23);
+    assert_script_error(result.err().unwrap(), LockError::WrongTargetDifficulty);
 }
 
 #[test]
@@ -60,7 +72,7 @@ fn test_unlock_lock_pubkey_failed() {
     let (context, tx, _) = new_lock_template(TestSchema::WrongPubkey);
     let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 26);
+    assert_script_error(result.err().unwrap(), LockError::PubkeyNotFound);
 }
 
 #[test]
@@ -74,7 +86,7 @@ fn test_unlock_lock_signature_failed() {
         TestSchema::WrongSignature,
     );
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 18);
+    assert_script_error(result.err().unwrap(), LockError::ValidationFail);
 }
 
 #[test]
@@ -148,7 +160,7 @@ fn test_unlock_failed_json() {
     );
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 21);
+    assert_script_error(result.err().unwrap(), LockError::Json);
 }
 
 #[test]
@@ -176,7 +188,7 @@ fn test_unlock_no_tags() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 21);
+    assert_script_error(result.err().unwrap(), LockError::Json);
 }
 
 #[test]
@@ -207,7 +219,7 @@ fn test_unlock_failed_tag_info() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 11); // SighashAllMismatched
+    assert_script_error(result.err().unwrap(), LockError::SighashAllMismatched);
 }
 
 #[test]
@@ -245,7 +257,7 @@ fn test_unlock_failed_event_id() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 17); // InvalidEventId
+    assert_script_error(result.err().unwrap(), LockError::InvalidEventId);
 }
 
 #[test]
@@ -256,7 +268,7 @@ fn test_unlock_failed_kind() {
     let tx = sign_lock_script(event, vec![0], 1, tx, TestSchema::Normal);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 12); // KindMismatched
+    assert_script_error(result.err().unwrap(), LockError::KindMismatched);
 }
 
 #[test]
@@ -267,7 +279,7 @@ fn test_unlock_failed_content() {
     let tx = sign_lock_script(event, vec![0], 1, tx, TestSchema::Normal);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 13); // ContentMismatched
+    assert_script_error(result.err().unwrap(), LockError::ContentMismatched);
 }
 
 #[test]
@@ -278,7 +290,7 @@ fn test_unlock_failed_args_len() {
     let tx = sign_lock_script(event, vec![0], 1, tx, TestSchema::Normal);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 15); // InvalidScriptArgs
+    assert_script_error(result.err().unwrap(), LockError::InvalidScriptArgs);
 }
 
 #[test]
@@ -295,7 +307,7 @@ fn test_unlock_empty_witness() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 21);
+    assert_script_error(result.err().unwrap(), LockError::Json);
 }
 
 #[test]
@@ -305,7 +317,7 @@ fn test_unlock_empty_witnesses() {
     let tx = tx.as_advanced_builder().set_witnesses(Vec::new()).build();
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 1);
+    assert_script_error(result.err().unwrap(), LockError::IndexOutOfBound);
 }
 
 #[test]
@@ -343,7 +355,7 @@ fn test_unlock_failed_sign_format() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 19); // InvalidSignatureFormat
+    assert_script_error(result.err().unwrap(), LockError::InvalidSignatureFormat);
 }
 
 #[test]
@@ -385,5 +397,823 @@ fn test_unlock_failed_sign_format2() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 19); // InvalidSignatureFormat
+    assert_script_error(result.err().unwrap(), LockError::InvalidSignatureFormat);
+}
+
+#[test]
+fn test_unlock_lock_chain() {
+    let (mut context, tx, _) = new_lock_template(TestSchema::Normal);
+    let mut config = TestConfig::default();
+    let tx = add_chain(&mut context, tx, &mut config, 1_573_852_190_812);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_chain_mismatched() {
+    let (mut context, tx, _) = new_lock_template(TestSchema::Normal);
+    let mut config = TestConfig::default();
+    // the genesis header is named by the tag but not in header deps
+    let _ = add_chain(&mut context, tx.clone(), &mut config, 1_573_852_190_812);
+    let tx = add_header_dep(&mut context, tx, 1_573_852_190);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::ChainMismatched);
+}
+
+#[test]
+fn test_unlock_lock_summary() {
+    let (mut context, tx, _) =
+        new_lock_template_with_flags(TestSchema::Normal, Some(LOCK_FLAG_SUMMARY_CONTENT));
+    // an output with type script and data, paying fee
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let type_script = context
+        .build_script(&always_success_out_point, Bytes::from("token"))
+        .unwrap();
+    let output = tx
+        .output(1)
+        .unwrap()
+        .as_builder()
+        .capacity(400u64.pack())
+        .type_(Some(type_script).pack())
+        .build();
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs(vec![tx.output(0).unwrap(), output])
+        .set_outputs_data(vec![Bytes::new().pack(), Bytes::from("data").pack()])
+        .build();
+    let tx = context.complete_tx(tx);
+    let config = TestConfig {
+        lock_content: tx_summary(&context, &tx, "ckb"),
+        ..Default::default()
+    };
+    println!("summary = {}", config.lock_content);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_summary_mismatched() {
+    let (context, tx, _) =
+        new_lock_template_with_flags(TestSchema::Normal, Some(LOCK_FLAG_SUMMARY_CONTENT));
+    let config = TestConfig {
+        lock_content: tx_summary(&context, &tx, "ckt"),
+        ..Default::default()
+    };
+    // the transaction is changed after the summary is shown
+    let output = tx
+        .output(1)
+        .unwrap()
+        .as_builder()
+        .capacity(1u64.pack())
+        .build();
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs(vec![tx.output(0).unwrap(), output])
+        .build();
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::SummaryMismatched);
+}
+
+#[test]
+fn test_unlock_lock_summary_fixed_content() {
+    let (context, tx, _) =
+        new_lock_template_with_flags(TestSchema::Normal, Some(LOCK_FLAG_SUMMARY_CONTENT));
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::SummaryMismatched);
+}
+
+#[test]
+fn test_unlock_lock_invalid_flags() {
+    let (context, tx, _) = new_lock_template_with_flags(TestSchema::Normal, Some(0x80));
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidScriptArgs);
+}
+
+const CREATED_AT_WINDOW: u32 = 600;
+
+fn new_lock_created_at_window_template() -> (Context, TransactionView) {
+    let (context, tx, _) = new_lock_template_with_args(
+        TestSchema::Normal,
+        Some(LOCK_FLAG_CREATED_AT_WINDOW),
+        &CREATED_AT_WINDOW.to_le_bytes(),
+    );
+    (context, tx)
+}
+
+#[test]
+fn test_unlock_lock_created_at_in_window() {
+    let (mut context, tx) = new_lock_created_at_window_template();
+    let config = TestConfig::default();
+    let tx = add_header_dep(&mut context, tx, config.created_at - 300);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_created_at_in_window_since() {
+    let (context, tx) = new_lock_created_at_window_template();
+    let config = TestConfig::default();
+    let tx = set_inputs_since(tx, 0x4000_0000_0000_0000 | (config.created_at + 300));
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_created_at_too_old() {
+    let (mut context, tx) = new_lock_created_at_window_template();
+    let config = TestConfig::default();
+    let tx = add_header_dep(&mut context, tx, config.created_at + 3600);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::CreatedAtOutOfWindow);
+}
+
+#[test]
+fn test_unlock_lock_created_at_in_future() {
+    let (mut context, tx) = new_lock_created_at_window_template();
+    let config = TestConfig::default();
+    let tx = add_header_dep(&mut context, tx, config.created_at - 3600);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::CreatedAtOutOfWindow);
+}
+
+#[test]
+fn test_unlock_lock_created_at_not_proven() {
+    let (context, tx) = new_lock_created_at_window_template();
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::TimestampNotProven);
+}
+
+#[test]
+fn test_unlock_lock_created_at_window_missing() {
+    let (context, tx, _) =
+        new_lock_template_with_flags(TestSchema::Normal, Some(LOCK_FLAG_CREATED_AT_WINDOW));
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidScriptArgs);
+}
+
+// relative since of 100 blocks
+const RECOVERY_DELAY: u64 = 0x8000_0000_0000_0000 | 100;
+
+fn new_lock_recovery_template(recovery_key: &Keys, delay: u64) -> (Context, TransactionView) {
+    let mut fields = blake160(&recovery_key.public_key().to_bytes()).to_vec();
+    fields.extend(delay.to_le_bytes());
+    let (context, tx, _) =
+        new_lock_template_with_args(TestSchema::Normal, Some(LOCK_FLAG_RECOVERY), &fields);
+    (context, tx)
+}
+
+#[test]
+fn test_unlock_lock_recovery_primary_key() {
+    let (context, tx) = new_lock_recovery_template(&Keys::generate(), RECOVERY_DELAY);
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_recovery_key() {
+    let recovery_key = Keys::generate();
+    let (context, tx) = new_lock_recovery_template(&recovery_key, RECOVERY_DELAY);
+    let tx = set_inputs_since(tx, RECOVERY_DELAY + 1);
+    let tx = sign_lock_script(
+        TestConfig::new(&recovery_key),
+        vec![0],
+        1,
+        tx,
+        TestSchema::Normal,
+    );
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_recovery_key_epoch() {
+    let recovery_key = Keys::generate();
+    // relative 1.5 epochs
+    let delay = 0xa000_0000_0000_0000 | (2 << 40) | (1 << 24) | 1;
+    let (context, tx) = new_lock_recovery_template(&recovery_key, delay);
+    // relative 1.6 epochs
+    let tx = set_inputs_since(tx, 0xa000_0000_0000_0000 | (5 << 40) | (3 << 24) | 1);
+    let tx = sign_lock_script(
+        TestConfig::new(&recovery_key),
+        vec![0],
+        1,
+        tx,
+        TestSchema::Normal,
+    );
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_recovery_key_before_delay() {
+    let recovery_key = Keys::generate();
+    let (context, tx) = new_lock_recovery_template(&recovery_key, RECOVERY_DELAY);
+    let tx = set_inputs_since(tx, RECOVERY_DELAY - 1);
+    let tx = sign_lock_script(
+        TestConfig::new(&recovery_key),
+        vec![0],
+        1,
+        tx,
+        TestSchema::Normal,
+    );
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::RecoveryDelayNotReached);
+}
+
+#[test]
+fn test_unlock_lock_recovery_key_without_since() {
+    let recovery_key = Keys::generate();
+    let (context, tx) = new_lock_recovery_template(&recovery_key, RECOVERY_DELAY);
+    let tx = sign_lock_script(
+        TestConfig::new(&recovery_key),
+        vec![0],
+        1,
+        tx,
+        TestSchema::Normal,
+    );
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::RecoveryDelayNotReached);
+}
+
+#[test]
+fn test_unlock_lock_recovery_other_key() {
+    let (context, tx) = new_lock_recovery_template(&Keys::generate(), RECOVERY_DELAY);
+    let tx = set_inputs_since(tx, RECOVERY_DELAY);
+    let tx = sign_lock_script(
+        TestConfig::new(&Keys::generate()),
+        vec![0],
+        1,
+        tx,
+        TestSchema::Normal,
+    );
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::PubkeyNotFound);
+}
+
+#[test]
+fn test_unlock_lock_recovery_absolute_delay() {
+    let (context, tx) = new_lock_recovery_template(&Keys::generate(), 100);
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidScriptArgs);
+}
+
+fn delegated_config(delegator: &Keys, conditions: &str) -> TestConfig {
+    let delegatee = Keys::generate();
+    let mut config = TestConfig::new(&delegatee);
+    config.tags = vec![delegation_tag(delegator, &delegatee, conditions)];
+    config
+}
+
+#[test]
+fn test_unlock_lock_delegation() {
+    let (mut context, tx, _) = new_lock_template(TestSchema::Normal);
+    let now = unix_time_now();
+    let tx = add_header_dep(&mut context, tx, now);
+    let config = delegated_config(
+        &KEY,
+        &format!(
+            "kind=23334&created_at>{}&created_at<{}",
+            now - 100,
+            now + 100
+        ),
+    );
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_delegation_expired() {
+    let (mut context, tx, _) = new_lock_template(TestSchema::Normal);
+    let now = unix_time_now();
+    let tx = add_header_dep(&mut context, tx, now);
+    let config = delegated_config(
+        &KEY,
+        &format!(
+            "kind=23334&created_at>{}&created_at<{}",
+            now - 200,
+            now - 100
+        ),
+    );
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::DelegationConditionsNotMet);
+}
+
+#[test]
+fn test_unlock_lock_delegation_backdated() {
+    // signed after the window, with `created_at` inside it
+    let (mut context, tx, _) = new_lock_template(TestSchema::Normal);
+    let now = unix_time_now();
+    let tx = add_header_dep(&mut context, tx, now);
+    let mut config = delegated_config(
+        &KEY,
+        &format!(
+            "kind=23334&created_at>{}&created_at<{}",
+            now - 200,
+            now - 100
+        ),
+    );
+    config.created_at = now - 150;
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::DelegationConditionsNotMet);
+}
+
+#[test]
+fn test_unlock_lock_delegation_timestamp_not_proven() {
+    let (context, tx, _) = new_lock_template(TestSchema::Normal);
+    let now = unix_time_now();
+    let config = delegated_config(
+        &KEY,
+        &format!(
+            "kind=23334&created_at>{}&created_at<{}",
+            now - 100,
+            now + 100
+        ),
+    );
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::TimestampNotProven);
+}
+
+#[test]
+fn test_unlock_lock_delegation_without_kind() {
+    let (context, tx, _) = new_lock_template(TestSchema::Normal);
+    let config = delegated_config(&KEY, "");
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::DelegationConditionsNotMet);
+}
+
+#[test]
+fn test_unlock_lock_delegation_other_kind() {
+    let (context, tx, _) = new_lock_template(TestSchema::Normal);
+    let config = delegated_config(&KEY, "kind=1");
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::DelegationConditionsNotMet);
+}
+
+#[test]
+fn test_unlock_lock_delegation_other_delegator() {
+    let (context, tx, _) = new_lock_template(TestSchema::Normal);
+    let config = delegated_config(&Keys::generate(), "kind=23334");
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::PubkeyNotFound);
+}
+
+#[test]
+fn test_unlock_lock_delegation_other_delegatee() {
+    let (context, tx, _) = new_lock_template(TestSchema::Normal);
+    let mut config = delegated_config(&KEY, "kind=23334");
+    // the token is granted to another key
+    config.key = Keys::generate();
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::ValidationFail);
+}
+
+fn session_config(
+    owner: &Keys,
+    expiration: u64,
+    max_capacity: u64,
+    allowed_locks: &[[u8; 32]],
+) -> TestConfig {
+    let session = Keys::generate();
+    let mut config = TestConfig::new(&session);
+    config.tags = vec![session_tag(
+        owner,
+        &session,
+        expiration,
+        max_capacity,
+        allowed_locks,
+    )];
+    config
+}
+
+// send output 0 of 500 to another lock
+fn new_lock_session_template_without_header() -> (Context, TransactionView, [u8; 32]) {
+    let (context, tx, _) = new_lock_template(TestSchema::Normal);
+    let lock = packed::Script::new_builder()
+        .args(Bytes::from("receiver").pack())
+        .build();
+    let lock_hash = lock.calc_script_hash().unpack();
+    (context, set_output_lock(tx, lock), lock_hash)
+}
+
+// the session template proving the current time by a header dep
+fn new_lock_session_template() -> (Context, TransactionView, [u8; 32]) {
+    let (mut context, tx, lock_hash) = new_lock_session_template_without_header();
+    let tx = add_header_dep(&mut context, tx, unix_time_now());
+    (context, tx, lock_hash)
+}
+
+#[test]
+fn test_unlock_lock_session() {
+    let (context, tx, lock_hash) = new_lock_session_template();
+    let config = session_config(&KEY, unix_time_now() + 3600, 500, &[lock_hash]);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_session_capacity_exceeded() {
+    let (context, tx, lock_hash) = new_lock_session_template();
+    let config = session_config(&KEY, unix_time_now() + 3600, 499, &[lock_hash]);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::SessionCapacityExceeded);
+}
+
+#[test]
+fn test_unlock_lock_session_lock_not_allowed() {
+    let (context, tx, _) = new_lock_session_template();
+    let config = session_config(&KEY, unix_time_now() + 3600, 500, &[[0x22; 32]]);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::SessionLockNotAllowed);
+}
+
+#[test]
+fn test_unlock_lock_session_expired() {
+    let (mut context, tx, lock_hash) = new_lock_session_template();
+    let expiration = unix_time_now() + 3600;
+    let config = session_config(&KEY, expiration, 500, &[lock_hash]);
+    let tx = add_header_dep(&mut context, tx, expiration);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::SessionExpired);
+}
+
+#[test]
+fn test_unlock_lock_session_timestamp_not_proven() {
+    let (context, tx, lock_hash) = new_lock_session_template_without_header();
+    let config = session_config(&KEY, unix_time_now() + 3600, 500, &[lock_hash]);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::TimestampNotProven);
+}
+
+#[test]
+fn test_unlock_lock_session_other_owner() {
+    let (context, tx, lock_hash) = new_lock_session_template();
+    let config = session_config(&Keys::generate(), unix_time_now() + 3600, 500, &[lock_hash]);
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::PubkeyNotFound);
+}
+
+#[test]
+fn test_unlock_lock_session_other_key() {
+    let (context, tx, lock_hash) = new_lock_session_template();
+    let mut config = session_config(&KEY, unix_time_now() + 3600, 500, &[lock_hash]);
+    // the authorization grants another session key
+    config.key = Keys::generate();
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidSession);
+}
+
+fn udt_data(amount: u128) -> Bytes {
+    let mut data = amount.to_le_bytes().to_vec();
+    data.extend(b"extra");
+    Bytes::from(data)
+}
+
+#[test]
+fn test_unlock_lock_acp_pay_capacity() {
+    let data = Bytes::from("hello");
+    let (context, tx) = new_lock_acp_template((1000, data.clone()), (1500, data), false);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_acp_pay_udt() {
+    let (context, tx) = new_lock_acp_template((1000, udt_data(100)), (1000, udt_data(150)), true);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_acp_capacity_decreased() {
+    let (context, tx) = new_lock_acp_template((1000, Bytes::new()), (999, Bytes::new()), false);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::AcpAmountDecreased);
+}
+
+#[test]
+fn test_unlock_lock_acp_udt_decreased() {
+    let (context, tx) = new_lock_acp_template((1000, udt_data(100)), (1000, udt_data(99)), true);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::AcpAmountDecreased);
+}
+
+#[test]
+fn test_unlock_lock_acp_data_changed() {
+    let (context, tx) = new_lock_acp_template((1000, udt_data(100)), (1000, udt_data(150)), false);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::AcpAmountDecreased);
+}
+
+#[test]
+fn test_unlock_lock_acp_output_not_found() {
+    let (context, tx) = new_lock_acp_template((1000, Bytes::new()), (1000, Bytes::new()), false);
+    let lock = packed::Script::new_builder()
+        .args(Bytes::from("thief").pack())
+        .build();
+    let tx = set_output_lock(tx, lock);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::AcpOutputMismatched);
+}
+
+#[test]
+fn test_unlock_lock_acp_with_signature() {
+    let (context, tx, _) =
+        new_lock_template_with_flags(TestSchema::Normal, Some(LOCK_FLAG_ANYONE_CAN_PAY));
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_dynamic_pow() {
+    let (context, tx) = new_lock_dynamic_pow_template(difficulty_config_data(2, 1, 10, 4, &[]), 0);
+    let tx = sign_pow_lock_script(&KEY, unix_time_now(), vec![0], 2, 2, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_dynamic_pow_floor() {
+    // the difficulty in args is a floor of the config
+    let (context, tx) = new_lock_dynamic_pow_template(difficulty_config_data(2, 1, 10, 4, &[]), 3);
+    let tx = sign_pow_lock_script(&KEY, unix_time_now(), vec![0], 2, 2, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::WrongTargetDifficulty);
+}
+
+#[test]
+fn test_unlock_lock_dynamic_pow_config_not_found() {
+    let (mut context, tx) =
+        new_lock_dynamic_pow_template(difficulty_config_data(2, 1, 10, 4, &[]), 0);
+    let tx = tx
+        .as_advanced_builder()
+        .set_inputs(vec![tx.inputs().get(0).unwrap()])
+        .set_outputs(vec![tx.output(0).unwrap()])
+        .set_outputs_data(vec![Bytes::new().pack()])
+        .build();
+    let tx = context.complete_tx(tx);
+    let tx = sign_pow_lock_script(&KEY, unix_time_now(), vec![0], 1, 2, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::DifficultyConfigNotFound);
+}
+
+#[test]
+fn test_unlock_lock_dynamic_pow_config_in_cell_deps() {
+    // reading the config cell without recording the claim
+    let (mut context, tx) =
+        new_lock_dynamic_pow_template(difficulty_config_data(2, 1, 10, 4, &[]), 0);
+    let config = tx.inputs().get(1).unwrap().previous_output();
+    let tx = tx
+        .as_advanced_builder()
+        .set_inputs(vec![tx.inputs().get(0).unwrap()])
+        .set_outputs(vec![tx.output(0).unwrap()])
+        .set_outputs_data(vec![Bytes::new().pack()])
+        .cell_dep(packed::CellDep::new_builder().out_point(config).build())
+        .build();
+    let tx = context.complete_tx(tx);
+    let tx = sign_pow_lock_script(&KEY, unix_time_now(), vec![0], 1, 2, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::DifficultyConfigNotFound);
+}
+
+#[test]
+fn test_unlock_lock_dynamic_pow_invalid_config() {
+    let (context, tx) = new_lock_dynamic_pow_template(Bytes::from(vec![2u8; 5]), 0);
+    let tx = sign_pow_lock_script(&KEY, unix_time_now(), vec![0], 2, 2, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidDifficultyConfig);
+}
+
+fn new_lock_key_and_pow_template(pow_difficulty: u8) -> (Context, TransactionView) {
+    let (context, tx, _) = new_lock_template_with_pow(
+        TestSchema::Normal,
+        pow_difficulty,
+        Some(LOCK_FLAG_KEY_AND_POW),
+        &[],
+    );
+    (context, tx)
+}
+
+#[test]
+fn test_unlock_lock_key_and_pow() {
+    let (context, tx) = new_lock_key_and_pow_template(3);
+    let tx = sign_pow_lock_script(&KEY, unix_time_now(), vec![0], 1, 3, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_key_and_pow_wrong_key() {
+    let (context, tx) = new_lock_key_and_pow_template(3);
+    let key = Keys::generate();
+    let tx = sign_pow_lock_script(&key, unix_time_now(), vec![0], 1, 3, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::PubkeyNotFound);
+}
+
+#[test]
+fn test_unlock_lock_key_and_pow_wrong_target_difficulty() {
+    let (context, tx) = new_lock_key_and_pow_template(3);
+    let tx = sign_pow_lock_script(&KEY, unix_time_now(), vec![0], 1, 2, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(
+        result.err().unwrap(),
+        LockError::KeyAndPowWrongTargetDifficulty,
+    );
+}
+
+#[test]
+fn test_unlock_lock_key_and_pow_nonce_not_found() {
+    let (context, tx) = new_lock_key_and_pow_template(3);
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::KeyAndPowNonceNotFound);
+}
+
+#[test]
+fn test_unlock_lock_key_and_pow_difficulty_zero() {
+    let (context, tx) = new_lock_key_and_pow_template(0);
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::KeyAndPowDifficultyZero);
+}
+
+// version 1 args: <VERSION_MARK | 1><flags>{<field id><length><value>}
+fn new_lock_v1_template(flags: u8, fields: &[u8]) -> (Context, TransactionView) {
+    let mut args = vec![0x81, flags];
+    args.extend(fields);
+    let (context, tx, _) = new_lock_template_with_raw_args(TestSchema::Normal, 0, &args);
+    (context, tx)
+}
+
+#[test]
+fn test_unlock_lock_v1_args() {
+    let mut fields = vec![LOCK_FLAG_CREATED_AT_WINDOW, 4];
+    fields.extend(600u32.to_le_bytes());
+    // an ignorable field
+    fields.extend([0x90, 2, 0xaa, 0xbb]);
+    let (mut context, tx) = new_lock_v1_template(LOCK_FLAG_CREATED_AT_WINDOW, &fields);
+    let tx = add_header_dep(&mut context, tx, unix_time_now());
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_v1_args_unknown_field() {
+    let (context, tx) = new_lock_v1_template(0, &[0x03, 1, 0xff]);
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidScriptArgs);
+}
+
+#[test]
+fn test_unlock_lock_unknown_args_version() {
+    let (context, tx, _) = new_lock_template_with_raw_args(TestSchema::Normal, 0, &[0x82, 0]);
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidScriptArgs);
+}
+
+#[test]
+fn test_unlock_lock_args_without_version() {
+    // flags right after the pubkey hash
+    let (context, tx, _) =
+        new_lock_template_with_raw_args(TestSchema::Normal, 0, &[LOCK_FLAG_ANYONE_CAN_PAY]);
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidScriptArgs);
+}
+
+fn new_lock_content_config_template(data: Bytes) -> (Context, TransactionView) {
+    let type_script = content_config_type("content");
+    let (mut context, tx, _) = new_lock_template_with_args(
+        TestSchema::Normal,
+        Some(LOCK_FLAG_CONTENT_CONFIG),
+        type_script.calc_script_hash().as_slice(),
+    );
+    let tx = add_content_config(&mut context, tx, type_script, data);
+    (context, tx)
+}
+
+const LOCALIZED_CONTENT: &str =
+    "签署一笔 CKB 交易\n\n重要提示：签名前请确认 Nostr 客户端的完整性与真实性\n";
+
+fn localized_config() -> TestConfig {
+    TestConfig {
+        kind: 1,
+        lock_content: LOCALIZED_CONTENT.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_unlock_lock_content_config() {
+    let (context, tx) = new_lock_content_config_template(content_config_data(1, LOCALIZED_CONTENT));
+    let tx = sign_lock_script(localized_config(), vec![0], 1, tx, TestSchema::Normal);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_unlock_lock_content_config_content_mismatched() {
+    let (context, tx) = new_lock_content_config_template(content_config_data(1, LOCALIZED_CONTENT));
+    let config = TestConfig {
+        kind: 1,
+        ..Default::default()
+    };
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::ContentMismatched);
+}
+
+#[test]
+fn test_unlock_lock_content_config_kind_mismatched() {
+    let (context, tx) = new_lock_content_config_template(content_config_data(1, LOCALIZED_CONTENT));
+    let config = TestConfig {
+        lock_content: LOCALIZED_CONTENT.to_string(),
+        ..Default::default()
+    };
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::KindMismatched);
+}
+
+#[test]
+fn test_unlock_lock_content_config_not_found() {
+    let type_script = content_config_type("content");
+    let (context, tx, _) = new_lock_template_with_args(
+        TestSchema::Normal,
+        Some(LOCK_FLAG_CONTENT_CONFIG),
+        type_script.calc_script_hash().as_slice(),
+    );
+    let tx = sign_lock_script(localized_config(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::ContentConfigNotFound);
+}
+
+#[test]
+fn test_unlock_lock_content_config_invalid() {
+    let (context, tx) = new_lock_content_config_template(Bytes::from(vec![1u8; 5]));
+    let tx = sign_lock_script(localized_config(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidContentConfig);
+}
+
+#[test]
+fn test_unlock_lock_content_config_with_summary() {
+    let type_script = content_config_type("content");
+    let (context, tx, _) = new_lock_template_with_args(
+        TestSchema::Normal,
+        Some(LOCK_FLAG_CONTENT_CONFIG | LOCK_FLAG_SUMMARY_CONTENT),
+        type_script.calc_script_hash().as_slice(),
+    );
+    let tx = sign_lock_script(TestConfig::default(), vec![0], 1, tx, TestSchema::Normal);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), LockError::InvalidScriptArgs);
 }
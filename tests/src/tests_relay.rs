@@ -0,0 +1,265 @@
+use crate::{new_lock_template, new_type_mint_template, unix_time_now, KEY, MAX_CYCLES};
+use ckb_nostr_utils::event::Event;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::DepType,
+    packed::{Script, WitnessArgs},
+    prelude::*,
+};
+use nostr::{EventBuilder, JsonUtil, Keys, Kind, Tag, Timestamp};
+use nostr_binding_relay::{Error, Filter, Relay, RelayClient, RelayMessage};
+use nostr_binding_sdk::{NostrBinding, NostrLock, ScriptConfig};
+use std::time::Duration;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+fn script_config(script: &Script) -> ScriptConfig {
+    ScriptConfig {
+        code_hash: script.code_hash().unpack(),
+        hash_type: script.hash_type().try_into().unwrap(),
+        tx_hash: Default::default(),
+        index: 0,
+        dep_type: DepType::Code,
+    }
+}
+
+fn relay_event(event: &nostr::Event) -> Event {
+    Event::from_json(event.as_json()).unwrap()
+}
+
+fn nostr_event(event: &Event) -> nostr::Event {
+    nostr::Event::from_json(serde_json::to_string(event).unwrap()).unwrap()
+}
+
+fn note(keys: &Keys, created_at: u64, tags: Vec<Tag>) -> Event {
+    relay_event(
+        &EventBuilder::new(Kind::from(1), "hello,world", tags)
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap(),
+    )
+}
+
+#[test]
+fn test_relay_req() {
+    let relay = Relay::bind("127.0.0.1:0").unwrap();
+    let mut client = RelayClient::connect(&relay.url()).unwrap();
+    let other = Keys::generate();
+    let tag = Tag::parse(&["t", "ckb"]).unwrap();
+    let events = vec![
+        note(&KEY, 1000, vec![]),
+        note(&KEY, 2000, vec![tag]),
+        note(&other, 3000, vec![]),
+    ];
+    for event in &events {
+        client.publish(event).unwrap();
+    }
+    // a duplicate is accepted without being stored twice
+    client.publish(&events[0]).unwrap();
+    assert_eq!(relay.events().len(), 3);
+
+    let fetch = |client: &mut RelayClient, filter: Filter| client.fetch(vec![filter]).unwrap();
+    // newest first
+    assert_eq!(
+        fetch(&mut client, Filter::new().kind(1)),
+        vec![events[2].clone(), events[1].clone(), events[0].clone()]
+    );
+    assert_eq!(
+        fetch(&mut client, Filter::new().kind(1).limit(1)),
+        vec![events[2].clone()]
+    );
+    assert_eq!(fetch(&mut client, Filter::new().kind(0)), vec![]);
+    assert_eq!(
+        fetch(&mut client, Filter::new().author(KEY.public_key().to_hex())),
+        vec![events[1].clone(), events[0].clone()]
+    );
+    // prefixes of ids
+    assert_eq!(
+        fetch(&mut client, Filter::new().id(&events[0].id().to_hex()[..8])),
+        vec![events[0].clone()]
+    );
+    assert_eq!(
+        fetch(&mut client, Filter::new().tag('t', "ckb")),
+        vec![events[1].clone()]
+    );
+    assert_eq!(
+        fetch(&mut client, Filter::new().since(1500).until(2500)),
+        vec![events[1].clone()]
+    );
+    // filters of a REQ are ORed
+    assert_eq!(
+        client
+            .fetch(vec![
+                Filter::new().id(events[0].id().to_hex()),
+                Filter::new().id(events[2].id().to_hex())
+            ])
+            .unwrap(),
+        vec![events[2].clone(), events[0].clone()]
+    );
+}
+
+#[test]
+fn test_relay_reject() {
+    let relay = Relay::bind("127.0.0.1:0").unwrap();
+    let mut client = RelayClient::connect(&relay.url()).unwrap();
+
+    let mut json: serde_json::Value = serde_json::to_value(note(&KEY, 1000, vec![])).unwrap();
+    json["content"] = "tampered".into();
+    let tampered = Event::from_value(json).unwrap();
+    assert!(matches!(
+        client.publish(&tampered),
+        Err(Error::Rejected(message)) if message.starts_with("invalid:")
+    ));
+    assert!(relay.events().is_empty());
+}
+
+#[test]
+fn test_relay_replaceable() {
+    let relay = Relay::bind("127.0.0.1:0").unwrap();
+    let mut client = RelayClient::connect(&relay.url()).unwrap();
+    let metadata = |created_at: u64| {
+        relay_event(
+            &EventBuilder::new(Kind::Metadata, "{}", [])
+                .custom_created_at(Timestamp::from(created_at))
+                .to_event(&KEY)
+                .unwrap(),
+        )
+    };
+    let (old, new) = (metadata(1000), metadata(2000));
+    client.publish(&new).unwrap();
+    assert!(matches!(
+        client.publish(&old),
+        Err(Error::Rejected(message)) if message.starts_with("replaced:")
+    ));
+    assert_eq!(relay.events(), vec![new.clone()]);
+
+    let newer = metadata(3000);
+    client.publish(&newer).unwrap();
+    assert_eq!(relay.events(), vec![newer]);
+}
+
+#[test]
+fn test_relay_subscription() {
+    let relay = Relay::bind("127.0.0.1:0").unwrap();
+    let mut publisher = RelayClient::connect(&relay.url()).unwrap();
+    let mut subscriber = RelayClient::connect(&relay.url()).unwrap();
+    let stored = note(&KEY, 1000, vec![]);
+    publisher.publish(&stored).unwrap();
+
+    subscriber
+        .subscribe("notes", vec![Filter::new().kind(1)])
+        .unwrap();
+    assert_eq!(subscriber.next_event("notes", WAIT).unwrap(), stored);
+    assert_eq!(
+        subscriber.recv(WAIT).unwrap(),
+        RelayMessage::Eose("notes".into())
+    );
+
+    // events published after EOSE are broadcast
+    let live = note(&KEY, 2000, vec![]);
+    publisher.publish(&live).unwrap();
+    assert_eq!(subscriber.next_event("notes", WAIT).unwrap(), live);
+
+    subscriber.close("notes").unwrap();
+    // the relay handles CLOSE before the next publish of the same connection
+    subscriber.fetch(vec![Filter::new().kind(0)]).unwrap();
+    publisher.publish(&note(&KEY, 3000, vec![])).unwrap();
+    assert!(matches!(
+        subscriber.next_event("notes", Duration::from_millis(200)),
+        Err(Error::Timeout)
+    ));
+}
+
+#[test]
+fn test_relay_unlock_event() {
+    let (context, tx, lock_script) = new_lock_template(crate::TestSchema::Normal);
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let relay = Relay::bind("127.0.0.1:0").unwrap();
+    let mut collector = RelayClient::connect(&relay.url()).unwrap();
+    collector
+        .subscribe(
+            "unlock",
+            vec![Filter::new()
+                .kind(lock.kind)
+                .author(KEY.public_key().to_hex())],
+        )
+        .unwrap();
+    assert_eq!(
+        collector.recv(WAIT).unwrap(),
+        RelayMessage::Eose("unlock".into())
+    );
+
+    // the owner signs the unlock event of the prepared transaction somewhere
+    // else and publishes it
+    let tx = lock.prepare_tx(tx, &[0], &lock.content).unwrap();
+    let sighash_all = NostrLock::build_sighash_all(&tx, &[0]).unwrap();
+    let event = lock
+        .build_unlock_event(sighash_all, &lock.content)
+        .to_event(&KEY)
+        .unwrap();
+    let mut signer = RelayClient::connect(&relay.url()).unwrap();
+    signer.publish(&relay_event(&event)).unwrap();
+    // unlock events are ephemeral
+    assert!(relay.events().is_empty());
+
+    let received = nostr_event(&collector.next_event("unlock", WAIT).unwrap());
+    assert_eq!(received, event);
+    let tx = NostrLock::fill_in_signed_event(tx, 0, &received).unwrap();
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_relay_mint() {
+    let (mut context, template, type_script) = new_type_mint_template(crate::TestSchema::Normal);
+    let binding = NostrBinding::new(script_config(&type_script), "ckt");
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock = context
+        .build_script(&always_success_out_point, Bytes::new())
+        .unwrap();
+
+    // the asset event is published before it is bound
+    let global_unique_id =
+        NostrBinding::build_global_unique_id(&template.inputs().get(0).unwrap(), 0);
+    let event = binding
+        .finalize_event_to_bind(
+            global_unique_id,
+            EventBuilder::new(Kind::from(1), "hello,world", []),
+        )
+        .custom_created_at(Timestamp::from(unix_time_now()))
+        .to_event(&KEY)
+        .unwrap();
+    let relay = Relay::bind("127.0.0.1:0").unwrap();
+    RelayClient::connect(&relay.url())
+        .unwrap()
+        .publish(&relay_event(&event))
+        .unwrap();
+
+    let mut minter = RelayClient::connect(&relay.url()).unwrap();
+    let fetched = minter
+        .fetch(vec![Filter::new().id(event.id().to_hex())])
+        .unwrap();
+    assert_eq!(fetched.len(), 1);
+    let (output, data) = binding.build_binding_cell(
+        fetched[0].id().to_bytes(),
+        global_unique_id,
+        lock,
+        Bytes::new(),
+        None,
+    );
+    // the relayed JSON is the one the binding script checks
+    let witness = WitnessArgs::new_builder()
+        .output_type(Some(Bytes::from(serde_json::to_string(&fetched[0]).unwrap())).pack())
+        .build();
+    let tx = template
+        .as_advanced_builder()
+        .set_outputs(vec![output])
+        .set_outputs_data(vec![data.pack()])
+        .set_witnesses(vec![witness.as_bytes().pack()])
+        .build();
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
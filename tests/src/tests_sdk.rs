@@ -0,0 +1,743 @@
+use crate::{
+    assert_script_error, author_nostr_lock, blake160, delegation_tag, lock_args_v1,
+    new_lock_pow_template, new_lock_template, new_lock_template_with_args,
+    new_lock_template_with_flags, new_type_mint_template, new_type_mint_template_with_config,
+    set_inputs_since, set_output_lock, sign_lock_script, unix_time_now, BindingConfig, TestConfig,
+    TestSchema, KEY, LOCK_FLAG_RECOVERY, LOCK_FLAG_SUMMARY_CONTENT, MAX_CYCLES,
+    MINT_TO_AUTHOR_TAG_NAME,
+};
+use ckb_nostr_error::{
+    BindingError, CellField, CodeHash, LockError, ScriptFailure, ScriptGroupType,
+};
+use ckb_nostr_utils::error::Error as UtilsError;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{DepType, ScriptHashType, TransactionBuilder, TransactionView},
+    h256,
+    packed::{self, Script, WitnessArgs},
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use nostr::{EventBuilder, JsonUtil, Keys, Kind, PublicKey, Tag, TagKind, Timestamp};
+use nostr_binding_sdk::{
+    fee::{calculate_fee, tx_size},
+    lock_args::{ArgsFormat, Recovery, FLAG_RECOVERY},
+    verify::ScriptKind,
+    Diagnostic, Error, NostrBinding, NostrBindingSdk, NostrLock, ResolvedTx, Rule, ScriptConfig,
+    ScriptError,
+};
+
+/// the deployed script of `script` in the test context
+fn script_config(script: &Script) -> ScriptConfig {
+    ScriptConfig {
+        code_hash: script.code_hash().unpack(),
+        hash_type: script.hash_type().try_into().unwrap(),
+        tx_hash: Default::default(),
+        index: 0,
+        dep_type: DepType::Code,
+    }
+}
+
+/// cells spent by the inputs of `tx`
+fn input_cells(context: &Context, tx: &TransactionView) -> Vec<packed::CellOutput> {
+    tx.input_pts_iter()
+        .map(|out_point| context.get_cell(&out_point).unwrap().0)
+        .collect()
+}
+
+/// `tx` with the cells and headers it refers to in the test context
+fn resolve(context: &Context, tx: &TransactionView) -> ResolvedTx {
+    ResolvedTx {
+        tx: tx.clone(),
+        inputs: tx
+            .input_pts_iter()
+            .map(|out_point| context.get_cell(&out_point).unwrap())
+            .collect(),
+        cell_deps: tx
+            .cell_deps_iter()
+            .map(|cell_dep| context.get_cell(&cell_dep.out_point()).unwrap())
+            .collect(),
+        header_deps: vec![],
+    }
+}
+
+/// the single diagnostic of `tx`, whose error is the one the scripts exit with
+fn diagnose(context: &Context, sdk: &NostrBindingSdk, tx: &TransactionView) -> Diagnostic {
+    let diagnostics = sdk.verify_tx(&resolve(context, tx));
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    let err = context.verify_tx(tx, MAX_CYCLES).unwrap_err();
+    // the rejection decodes to the diagnosed error
+    assert_eq!(
+        sdk.decode_failure(&err.to_string()),
+        Some(diagnostics[0].error)
+    );
+    assert_script_error(err, diagnostics[0].error);
+    diagnostics[0].clone()
+}
+
+fn lock_sdk(lock_script: &Script) -> NostrBindingSdk {
+    NostrBindingSdk {
+        binding: NostrBinding::new(script_config(&Script::default()), "ckt"),
+        lock: NostrLock::new(script_config(lock_script), "ckt"),
+    }
+}
+
+#[test]
+fn test_sdk_address() {
+    let sdk = NostrBindingSdk::default();
+    let pubkey =
+        PublicKey::from_hex("45c41f21e1cf715fa6d9ca20b8e002a574db7bb49e96ee89834c66dac5446b7a")
+            .unwrap();
+    let address = sdk.lock.encode_to_ckb_address(&pubkey).unwrap();
+    assert_eq!(
+        address,
+        "ckt1qp4wtmsvhzrm9h66ngvpxuc4hx7u2klg65nr0vk7qcjqjt2lpjga2qgqszfcwyycsu3g4dj4qyuwyedz8fru3w5m56t770"
+    );
+    let pubkey_hash = sdk
+        .lock
+        .parse_ckb_address_to_nostr_pubkey_hash(&address)
+        .unwrap();
+    assert_eq!(pubkey_hash, NostrLock::build_pubkey_hash(&pubkey));
+
+    let binding = sdk.binding.build_script([1u8; 32], [2u8; 32], None);
+    assert!(sdk.binding.is_binding_type(&binding));
+    assert!(!sdk.lock.is_nostr_lock(&binding));
+}
+
+#[test]
+fn test_sdk_sighash_all() {
+    let lock = |args: &str| {
+        Script::new_builder()
+            .code_hash(
+                h256!("0x6ae5ee0cb887b2df5a9a18137315b9bdc55be8d52637b2de0624092d5f0c91d5").pack(),
+            )
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(hex::decode(args).unwrap()).pack())
+            .build()
+    };
+    let type_script = Script::new_builder()
+        .code_hash(
+            h256!("0x25c29dc317811a6f6f3985a7a9ebc4838bd388d19d0feeecf0bcd60f6c0975bb").pack(),
+        )
+        .hash_type(ScriptHashType::Type.into())
+        .args(
+            Bytes::from(
+                hex::decode("8cb223d3e2a07e60179bc9e95106b0a99c4bd859c2902338714dd736474dffe9")
+                    .unwrap(),
+            )
+            .pack(),
+        )
+        .build();
+    let cell_dep = |tx_hash| {
+        packed::CellDep::new_builder()
+            .out_point(packed::OutPoint::new(tx_hash, 0))
+            .dep_type(DepType::Code.into())
+            .build()
+    };
+    let input = |tx_hash, index| packed::CellInput::new(packed::OutPoint::new(tx_hash, index), 0);
+    let output = |capacity: u64, lock, type_script: Option<Script>| {
+        packed::CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock)
+            .type_(type_script.pack())
+            .build()
+    };
+    let token_data = Bytes::from(hex::decode("20a10700000000000000000000000000").unwrap());
+    let witness = WitnessArgs::new_builder()
+        .lock(Some(Bytes::from(vec![0u8; 572])).pack())
+        .build();
+    // the same transaction as the JavaScript SDK test
+    let tx = TransactionBuilder::default()
+        .cell_dep(cell_dep(
+            h256!("0xbf6fb538763efec2a70a6a3dcb7242787087e1030c4e7d86585bc63a9d337f5f").pack(),
+        ))
+        .cell_dep(cell_dep(
+            h256!("0xa2a434dcdbe280b9ed75bb7d6c7d68186a842456aba0fc506657dc5ed7c01d68").pack(),
+        ))
+        .input(input(
+            h256!("0xf6cd8c036f0924f89a4ba9ed4e8bdd20ef356fd5ef014994214f041c0625a975").pack(),
+            0,
+        ))
+        .input(input(
+            h256!("0x1f1da9a4c6cc17fb7a9a238ab4b783ca428293b1d28fe384097dd52a35283136").pack(),
+            1,
+        ))
+        .output(output(
+            0x35458af00,
+            lock("003f4ce62974e70f74e98ecc59f0c2f00067cb8879"),
+            Some(type_script.clone()),
+        ))
+        .output(output(
+            0x35458af00,
+            lock("004f1ae79592b8a82df8d1ef93361160966d718015"),
+            Some(type_script),
+        ))
+        .output(output(
+            0xd9dc10d6b4,
+            lock("004f1ae79592b8a82df8d1ef93361160966d718015"),
+            None,
+        ))
+        .output_data(token_data.clone().pack())
+        .output_data(token_data.pack())
+        .output_data(Bytes::new().pack())
+        .witness(witness.as_bytes().pack())
+        .witness(Bytes::new().pack())
+        .build();
+    let sighash_all = NostrLock::build_sighash_all(&tx, &[0, 1]).unwrap();
+    assert_eq!(
+        hex::encode(sighash_all),
+        "abbd1be2fe6b710eeeec8aa6c84930d4eeb8680698840f95f8c58c4a40ad40db"
+    );
+    assert!(matches!(
+        NostrLock::build_sighash_all(&tx, &[]),
+        Err(Error::NoLockInput)
+    ));
+}
+
+#[test]
+fn test_sdk_lock_args() {
+    let sdk = NostrBindingSdk::default();
+    let owner = KEY.public_key();
+    let mut args = NostrLock::build_lock_args(&owner);
+    assert_eq!(
+        sdk.lock.build_script_with_args(&args).unwrap(),
+        sdk.lock.build_script(&owner)
+    );
+
+    let recovery = Keys::generate().public_key();
+    assert!(matches!(
+        NostrLock::build_recovery_field(&recovery, 100),
+        Err(Error::InvalidLockArgs)
+    ));
+    args.format = ArgsFormat::V1;
+    args.flags = FLAG_RECOVERY;
+    args.recovery =
+        Some(NostrLock::build_recovery_field(&recovery, 0x8000_0000_0000_0064).unwrap());
+    let script = sdk.lock.build_script_with_args(&args).unwrap();
+    assert_eq!(script.args().raw_data()[21], 0x81);
+
+    // an absolute delay is rejected as the lock script does
+    args.recovery = Some(Recovery {
+        pubkey_hash: [0u8; 20],
+        delay: 100,
+    });
+    assert!(matches!(
+        sdk.lock.build_script_with_args(&args),
+        Err(Error::InvalidLockArgs)
+    ));
+    assert!(matches!(
+        sdk.lock
+            .build_key_and_pow_script(&NostrLock::build_lock_args(&owner), 0),
+        Err(Error::InvalidPowDifficulty(0))
+    ));
+}
+
+#[test]
+fn test_sdk_sign_tx() {
+    let (context, tx, lock_script) = new_lock_template(crate::TestSchema::Normal);
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let lock_indexes = lock.get_lock_indexes(&input_cells(&context, &tx));
+    assert_eq!(lock_indexes, vec![0]);
+
+    let tx = lock
+        .sign_tx(tx, &lock_indexes, &lock.content, |event| {
+            Ok(event.to_event(&KEY)?)
+        })
+        .unwrap();
+    let witness = WitnessArgs::from_slice(&tx.witnesses().get(0).unwrap().raw_data()).unwrap();
+    let event = NostrLock::parse_unlock_event_from_witness_args(&witness).unwrap();
+    assert_eq!(event.author(), KEY.public_key());
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+
+    // a witness not prepared with the dummy lock of the content
+    assert!(matches!(
+        lock.sign_prepared_tx(
+            tx,
+            &lock_indexes,
+            "other",
+            |event| Ok(event.to_event(&KEY)?)
+        ),
+        Err(Error::InvalidDummyLock)
+    ));
+}
+
+#[test]
+fn test_sdk_sign_tx_summary() {
+    let (context, tx, lock_script) =
+        new_lock_template_with_flags(crate::TestSchema::Normal, Some(LOCK_FLAG_SUMMARY_CONTENT));
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let summary = lock
+        .build_tx_summary(&tx, &input_cells(&context, &tx))
+        .unwrap();
+    let tx = lock
+        .sign_tx(tx, &[0], &summary, |event| Ok(event.to_event(&KEY)?))
+        .unwrap();
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_sdk_sign_tx_summary_large_capacity() {
+    // capacities summing beyond u64 saturate the same way on chain
+    let (mut context, template, lock_script) =
+        new_lock_template_with_flags(crate::TestSchema::Normal, Some(LOCK_FLAG_SUMMARY_CONTENT));
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let cell = |capacity: u64| {
+        packed::CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock_script.clone())
+            .build()
+    };
+    let inputs: Vec<_> = [u64::MAX - 1, u64::MAX - 2]
+        .into_iter()
+        .map(|capacity| {
+            let out_point = context.create_cell(cell(capacity), Bytes::new());
+            packed::CellInput::new_builder()
+                .previous_output(out_point)
+                .build()
+        })
+        .collect();
+    let tx = template
+        .as_advanced_builder()
+        .set_inputs(inputs)
+        .set_outputs(vec![cell(u64::MAX / 2 + 1), cell(u64::MAX / 2 + 1)])
+        .set_outputs_data(vec![Bytes::new().pack(), Bytes::new().pack()])
+        .set_witnesses(vec![Bytes::new().pack(), Bytes::new().pack()])
+        .build();
+    let summary = lock
+        .build_tx_summary(&tx, &input_cells(&context, &tx))
+        .unwrap();
+    assert!(summary.ends_with("\nFee: 0.00000000 CKB\n"));
+    let tx = lock
+        .sign_tx(tx, &[0, 1], &summary, |event| Ok(event.to_event(&KEY)?))
+        .unwrap();
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_sdk_unlock_event_len() {
+    let mut lock = NostrLock::new(script_config(&Script::default()), "ckt");
+    let sighash_all = [0xab; 32];
+    let assert_len = |lock: &NostrLock, content: &str| {
+        let event = match lock.pow_difficulty {
+            Some(_) => lock
+                .mine_unlock_event(sighash_all, content, KEY.public_key())
+                .unwrap()
+                .sign(&KEY)
+                .unwrap(),
+            None => lock
+                .build_unlock_event(sighash_all, content)
+                .to_event(&KEY)
+                .unwrap(),
+        };
+        assert_eq!(event.as_json().len(), lock.unlock_event_len(content));
+    };
+
+    let content = lock.content.clone();
+    assert_len(&lock, &content);
+    assert_len(&lock, "escaped \"quote\"\t\\ and ünïcode\n");
+    lock.use_content_config(1, "a custom kind");
+    assert_len(&lock, "a custom kind");
+    lock.bind_chain([1u8; 32]);
+    assert_len(&lock, &content);
+    lock.delegation_tag = Some(Tag::custom(
+        TagKind::from("delegation"),
+        [
+            KEY.public_key().to_hex(),
+            "kind=23334".into(),
+            "00".repeat(64),
+        ],
+    ));
+    assert_len(&lock, &content);
+    lock.session_authorization = Some(
+        lock.build_session_authorization_event(&KEY.public_key(), 1000, 100, &[[1u8; 32]])
+            .to_event(&KEY)
+            .unwrap(),
+    );
+    assert_len(&lock, &content);
+    lock.pow_difficulty = Some(4);
+    assert_len(&lock, &content);
+}
+
+#[test]
+fn test_sdk_fee() {
+    let (mut context, template, lock_script) =
+        new_lock_template_with_flags(crate::TestSchema::Normal, Some(LOCK_FLAG_SUMMARY_CONTENT));
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let capacity = 1000_0000_0000u64;
+    let cell = |capacity: u64| {
+        packed::CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock_script.clone())
+            .build()
+    };
+    let out_point = context.create_cell(cell(capacity), Bytes::new());
+    let input = packed::CellInput::new_builder()
+        .previous_output(out_point)
+        .build();
+    // output 1 is the change, holding all the capacity left
+    let tx = template
+        .as_advanced_builder()
+        .set_inputs(vec![input])
+        .set_outputs(vec![cell(capacity / 2), cell(capacity / 2)])
+        .build();
+    let cells = input_cells(&context, &tx);
+
+    let fee_rate = 1000;
+    let (prepared, summary) = lock
+        .prepare_tx_with_fee(tx.clone(), &[0], 1, fee_rate, |tx| {
+            lock.build_tx_summary(tx, &cells)
+        })
+        .unwrap();
+    let signed = lock
+        .sign_prepared_tx(prepared.clone(), &[0], &summary, |event| {
+            Ok(event.to_event(&KEY)?)
+        })
+        .unwrap();
+    context
+        .verify_tx(&signed, MAX_CYCLES)
+        .expect("pass verification");
+
+    // the fee paid before signing is the one of the signed transaction
+    assert_eq!(tx_size(&signed), tx_size(&prepared));
+    let fee = capacity - signed.outputs_capacity().unwrap().as_u64();
+    assert_eq!(fee, calculate_fee(tx_size(&signed), fee_rate));
+    assert!(summary.contains(&format!("Fee: 0.{:08} CKB", fee)));
+    assert_eq!(
+        lock.estimate_fee(signed.clone(), &[0], &summary, fee_rate)
+            .unwrap(),
+        fee
+    );
+    // rounded up from the fee a node requires, saturating on overflow
+    assert_eq!(calculate_fee(1000, 1), 1);
+    assert_eq!(calculate_fee(1001, 1), 2);
+    assert_eq!(calculate_fee(usize::MAX, u64::MAX), u64::MAX / 1000 + 1);
+
+    // extra tags change the length the fee and sighash_all are computed with
+    let err = lock
+        .sign_prepared_tx(prepared, &[0], &summary, |event| {
+            Ok(event
+                .add_tags([Tag::custom(TagKind::from("client"), ["test"])])
+                .to_event(&KEY)?)
+        })
+        .unwrap_err();
+    assert!(matches!(err, Error::UnlockEventLengthMismatched { .. }));
+
+    // the change can't go below its occupied capacity
+    let err = lock
+        .prepare_tx_with_fee(tx, &[0], 0, capacity, |tx| {
+            lock.build_tx_summary(tx, &cells)
+        })
+        .unwrap_err();
+    assert!(matches!(err, Error::InsufficientCapacity(0)));
+}
+
+#[test]
+fn test_sdk_mint() {
+    let (mut context, template, type_script) = new_type_mint_template(crate::TestSchema::Normal);
+    let binding = NostrBinding::new(script_config(&type_script), "ckt");
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock = context
+        .build_script(&always_success_out_point, Bytes::new())
+        .unwrap();
+
+    let first_input = template.inputs().get(0).unwrap();
+    let global_unique_id = NostrBinding::build_global_unique_id(&first_input, 0);
+    let event = binding
+        .finalize_event_to_bind(
+            global_unique_id,
+            EventBuilder::new(Kind::from(1), "hello,world", []),
+        )
+        .custom_created_at(Timestamp::from(unix_time_now()))
+        .to_event(&KEY)
+        .unwrap();
+    let (output, data) = binding.build_binding_cell(
+        event.id().to_bytes(),
+        global_unique_id,
+        lock,
+        Bytes::new(),
+        None,
+    );
+    let witness = WitnessArgs::new_builder()
+        .output_type(Some(Bytes::from(event.as_json())).pack())
+        .build();
+    let tx = template
+        .as_advanced_builder()
+        .set_outputs(vec![output])
+        .set_outputs_data(vec![data.pack()])
+        .set_witnesses(vec![witness.as_bytes().pack()])
+        .build();
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    assert_eq!(
+        NostrBinding::parse_binding_event_from_witness_args(&witness)
+            .unwrap()
+            .id(),
+        event.id()
+    );
+}
+
+#[test]
+fn test_sdk_verify_lock() {
+    let (context, tx, lock_script) = new_lock_template(TestSchema::Normal);
+    let sdk = lock_sdk(&lock_script);
+    let tx = sdk
+        .lock
+        .sign_tx(tx, &[0], &sdk.lock.content, |event| {
+            Ok(event.to_event(&KEY)?)
+        })
+        .unwrap();
+    assert!(sdk.verify_tx(&resolve(&context, &tx)).is_empty());
+
+    // outputs changed after signing
+    let sighash_all = |tx: &TransactionView| {
+        let prepared = sdk
+            .lock
+            .prepare_tx(tx.clone(), &[0], &sdk.lock.content)
+            .unwrap();
+        hex::encode(NostrLock::build_sighash_all(&prepared, &[0]).unwrap())
+    };
+    let tampered = tx
+        .as_advanced_builder()
+        .set_outputs(vec![tx.output(0).unwrap()])
+        .set_outputs_data(vec![Bytes::new().pack()])
+        .build();
+    let diagnostic = diagnose(&context, &sdk, &tampered);
+    assert_eq!(diagnostic.error.kind(), ScriptKind::NostrLock);
+    assert_eq!(diagnostic.rule, Rule::SighashAll);
+    assert_eq!(diagnostic.rule.number(), Some(1));
+    assert_eq!(diagnostic.witness_index, 0);
+    assert_eq!(diagnostic.expected, Some(sighash_all(&tampered)));
+    assert_eq!(diagnostic.actual, Some(sighash_all(&tx)));
+
+    // unlock event of other content
+    let (context, tx, lock_script) = new_lock_template(TestSchema::Normal);
+    let sdk = lock_sdk(&lock_script);
+    let tx = sdk
+        .lock
+        .sign_tx(tx, &[0], "other", |event| Ok(event.to_event(&KEY)?))
+        .unwrap();
+    let diagnostic = diagnose(&context, &sdk, &tx);
+    assert_eq!(diagnostic.rule, Rule::KindAndContent);
+    assert_eq!(diagnostic.expected, Some(sdk.lock.content.clone()));
+    assert_eq!(diagnostic.actual, Some("other".to_string()));
+
+    // signed by another key
+    let (context, tx, lock_script) = new_lock_template(TestSchema::WrongPubkey);
+    let sdk = lock_sdk(&lock_script);
+    let tx = sdk
+        .lock
+        .sign_tx(tx, &[0], &sdk.lock.content, |event| {
+            Ok(event.to_event(&KEY)?)
+        })
+        .unwrap();
+    let diagnostic = diagnose(&context, &sdk, &tx);
+    assert_eq!(diagnostic.rule, Rule::Pubkey);
+    assert_eq!(
+        diagnostic.actual,
+        Some(hex::encode(NostrLock::build_pubkey_hash(&KEY.public_key())))
+    );
+}
+
+#[test]
+fn test_sdk_verify_recovery_epoch() {
+    let recovery_key = Keys::generate();
+    // relative 1.5 epochs
+    let delay: u64 = 0xa000_0000_0000_0000 | (2 << 40) | (1 << 24) | 1;
+    let mut fields = blake160(&recovery_key.public_key().to_bytes()).to_vec();
+    fields.extend(delay.to_le_bytes());
+    let (context, template, lock_script) =
+        new_lock_template_with_args(TestSchema::Normal, Some(LOCK_FLAG_RECOVERY), &fields);
+    let sdk = lock_sdk(&lock_script);
+    let sign = |since: u64| {
+        sdk.lock
+            .sign_tx(
+                set_inputs_since(template.clone(), since),
+                &[0],
+                &sdk.lock.content,
+                |event| Ok(event.to_event(&recovery_key)?),
+            )
+            .unwrap()
+    };
+
+    // relative 1.6 epochs
+    let tx = sign(0xa000_0000_0000_0000 | (5 << 40) | (3 << 24) | 1);
+    assert!(sdk.verify_tx(&resolve(&context, &tx)).is_empty());
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+
+    // relative 1.4 epochs, whose raw value is above the delay
+    let since = 0xa000_0000_0000_0000 | (5 << 40) | (2 << 24) | 1;
+    assert!(since > delay);
+    let diagnostic = diagnose(&context, &sdk, &sign(since));
+    assert_eq!(diagnostic.rule, Rule::Recovery);
+}
+
+#[test]
+fn test_sdk_verify_pow() {
+    let (context, tx, pow_difficulty) = new_lock_pow_template(TestSchema::Normal);
+    let lock_script = input_cells(&context, &tx)[0].lock();
+    let mut sdk = lock_sdk(&lock_script);
+    sdk.lock.pow_difficulty = Some(pow_difficulty);
+    let mined = sdk
+        .lock
+        .mine_tx(tx.clone(), &[0], &sdk.lock.content, &Keys::generate())
+        .unwrap();
+    assert!(sdk.verify_tx(&resolve(&context, &mined)).is_empty());
+    context
+        .verify_tx(&mined, MAX_CYCLES)
+        .expect("pass verification");
+
+    // mined to a lower difficulty
+    sdk.lock.pow_difficulty = Some(pow_difficulty - 1);
+    let mined = sdk
+        .lock
+        .mine_tx(tx, &[0], &sdk.lock.content, &Keys::generate())
+        .unwrap();
+    let diagnostic = diagnose(&context, &sdk, &mined);
+    assert_eq!(diagnostic.rule, Rule::TargetDifficulty);
+    assert_eq!(diagnostic.expected, Some(pow_difficulty.to_string()));
+    assert_eq!(diagnostic.actual, Some((pow_difficulty - 1).to_string()));
+}
+
+#[test]
+fn test_sdk_verify_mint() {
+    let cases = [
+        (TestSchema::Normal, None),
+        (TestSchema::WrongId, Some(Rule::BoundEventId)),
+        (TestSchema::WrongGlobalUniqueId, Some(Rule::TypeId)),
+        (TestSchema::WrongGlobalUniqueId2, Some(Rule::GlobalUniqueId)),
+        (TestSchema::WrongArgsLen, Some(Rule::BindingArgs)),
+        (TestSchema::WrongMultiTypeCell, Some(Rule::TypeId)),
+    ];
+    for (schema, rule) in cases {
+        let (context, tx, type_script) = new_type_mint_template(schema);
+        let sdk = NostrBindingSdk {
+            binding: NostrBinding::new(script_config(&type_script), "ckt"),
+            lock: NostrLock::new(script_config(&Script::default()), "ckt"),
+        };
+        match rule {
+            None => assert!(sdk.verify_tx(&resolve(&context, &tx)).is_empty()),
+            Some(rule) => {
+                let diagnostic = diagnose(&context, &sdk, &tx);
+                assert_eq!(diagnostic.error.kind(), ScriptKind::NostrBinding);
+                assert_eq!(diagnostic.rule, rule);
+                assert_eq!(diagnostic.witness_index, 0);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sdk_verify_mint_to_author() {
+    let config = BindingConfig {
+        tags: vec![Tag::custom(
+            TagKind::from(MINT_TO_AUTHOR_TAG_NAME),
+            vec![hex::encode([0x33; 32])],
+        )],
+        ..Default::default()
+    };
+    let (context, template, type_script) =
+        new_type_mint_template_with_config(TestSchema::Normal, config);
+    let sdk = NostrBindingSdk {
+        binding: NostrBinding::new(script_config(&type_script), "ckt"),
+        lock: NostrLock::new(script_config(&Script::default()), "ckt"),
+    };
+    // the author's lock with versioned args
+    let author_lock = |code_hash: [u8; 32]| {
+        let lock = author_nostr_lock(code_hash);
+        let mut args = lock.args().raw_data().to_vec();
+        args.extend(lock_args_v1(LOCK_FLAG_SUMMARY_CONTENT, &[]));
+        lock.as_builder().args(Bytes::from(args).pack()).build()
+    };
+
+    let tx = set_output_lock(template.clone(), author_lock([0x33; 32]));
+    assert!(sdk.verify_tx(&resolve(&context, &tx)).is_empty());
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+
+    let tx = set_output_lock(template.clone(), author_lock([0x44; 32]));
+    assert_eq!(diagnose(&context, &sdk, &tx).rule, Rule::MintTo);
+
+    // with a recovery key of the minter
+    let lock = author_nostr_lock([0x33; 32]);
+    let mut args = lock.args().raw_data().to_vec();
+    let mut fields = blake160(&Keys::generate().public_key().to_bytes()).to_vec();
+    fields.extend((0x8000_0000_0000_0000u64 | 100).to_le_bytes());
+    args.extend(lock_args_v1(LOCK_FLAG_RECOVERY, &fields));
+    let lock = lock.as_builder().args(Bytes::from(args).pack()).build();
+    let tx = set_output_lock(template, lock);
+    assert_eq!(diagnose(&context, &sdk, &tx).rule, Rule::MintTo);
+}
+
+#[test]
+fn test_sdk_verify_delegation_timestamp_not_proven() {
+    let (context, tx, lock_script) = new_lock_template(TestSchema::Normal);
+    let now = unix_time_now();
+    let delegatee = Keys::generate();
+    let mut config = TestConfig::new(&delegatee);
+    config.tags = vec![delegation_tag(
+        &KEY,
+        &delegatee,
+        &format!("kind=23334&created_at<{}", now + 100),
+    )];
+    // no header dep or since proves the time the window is checked against
+    let tx = sign_lock_script(config, vec![0], 1, tx, TestSchema::Normal);
+    let diagnostic = diagnose(&context, &lock_sdk(&lock_script), &tx);
+    assert_eq!(diagnostic.rule, Rule::Delegation);
+    assert_eq!(
+        diagnostic.error,
+        ScriptError::NostrLock(LockError::TimestampNotProven)
+    );
+}
+
+#[test]
+fn test_sdk_decode_utils_binding_errors() {
+    // errors of ckb-nostr-utils in the binding script have their own codes
+    let code_hash = [7u8; 32];
+    for (code, utils_error, error) in [
+        (
+            75,
+            UtilsError::InvalidDelegation,
+            BindingError::InvalidDelegation,
+        ),
+        (
+            76,
+            UtilsError::InvalidLockArgs,
+            BindingError::InvalidLockArgs,
+        ),
+        (
+            77,
+            UtilsError::InvalidDifficultyConfig,
+            BindingError::InvalidDifficultyConfig,
+        ),
+    ] {
+        assert_eq!(BindingError::from(utils_error), error);
+        let message = format!(
+            "TransactionScriptError {{ source: Outputs[0].Type, cause: ValidationFailure: see error code {} on page https://nervosnetwork.github.io/ckb-script-error-codes/by-type-hash/{}.html#{} }}",
+            code,
+            hex::encode(code_hash),
+            code
+        );
+        let failure = ScriptFailure::parse(&message).unwrap();
+        assert_eq!(failure.exit_code, code);
+        assert_eq!(
+            failure.source,
+            Some((CellField::Outputs, 0, ScriptGroupType::Type))
+        );
+        assert_eq!(failure.code_hash, Some(CodeHash::Type(code_hash)));
+        assert_eq!(
+            failure.decode(ScriptKind::NostrBinding),
+            Some(ScriptError::NostrBinding(error))
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{new_lock_template, TestSchema, KEY, MAX_CYCLES};
+use ckb_testtool::ckb_types::{core::DepType, packed::Script, prelude::*};
+use nostr::{nips::nip46::Request, Keys};
+use nostr_binding_relay::Relay;
+use nostr_binding_sdk::{NostrLock, ScriptConfig};
+use nostr_binding_signer::{Error, MockBunker, RemoteSigner};
+use std::time::Duration;
+
+fn script_config(script: &Script) -> ScriptConfig {
+    ScriptConfig {
+        code_hash: script.code_hash().unpack(),
+        hash_type: script.hash_type().try_into().unwrap(),
+        tx_hash: Default::default(),
+        index: 0,
+        dep_type: DepType::Code,
+    }
+}
+
+#[test]
+fn test_signer_sign_tx() {
+    let (context, tx, lock_script) = new_lock_template(TestSchema::Normal);
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let relay = Relay::bind("127.0.0.1:0").unwrap();
+    // the owner key is in the bunker only
+    let bunker = MockBunker::spawn(&relay.url(), KEY.clone(), Some("secret".into())).unwrap();
+
+    let mut signer = RemoteSigner::connect(&bunker.uri(), Keys::generate()).unwrap();
+    assert_eq!(signer.public_key(), KEY.public_key());
+    assert_eq!(signer.signer_public_key(), bunker.public_key());
+    let tx = signer.sign_tx(&lock, tx, &[0], &lock.content).unwrap();
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+
+    let requests = bunker.requests();
+    assert!(matches!(requests[0], Request::Connect { .. }));
+    assert_eq!(requests[1], Request::GetPublicKey);
+    match &requests[2] {
+        Request::SignEvent(unsigned) => assert_eq!(unsigned.kind.as_u16(), lock.kind),
+        request => panic!("unexpected request {:?}", request),
+    }
+    // nothing but the messages went through the relay, they are ephemeral
+    assert!(relay.events().is_empty());
+}
+
+#[test]
+fn test_signer_refused() {
+    let (_, tx, lock_script) = new_lock_template(TestSchema::Normal);
+    let lock = NostrLock::new(script_config(&lock_script), "ckt");
+    let relay = Relay::bind("127.0.0.1:0").unwrap();
+    let bunker = MockBunker::spawn(&relay.url(), KEY.clone(), Some("secret".into())).unwrap();
+
+    // the user of the bunker denies signing
+    let mut signer = RemoteSigner::connect(&bunker.uri(), Keys::generate()).unwrap();
+    bunker.set_approve(false);
+    let err = signer.sign_tx(&lock, tx, &[0], &lock.content).unwrap_err();
+    assert!(matches!(err, Error::Rejected(reason) if reason == "denied"));
+
+    // a wrong secret
+    let uri = bunker.uri().replace("secret=secret", "secret=other");
+    let err = RemoteSigner::connect(&uri, Keys::generate()).err().unwrap();
+    assert!(matches!(err, Error::Rejected(reason) if reason == "invalid secret"));
+
+    // nobody answers for another key
+    let uri = bunker.uri().replace(
+        &bunker.public_key().to_hex(),
+        &Keys::generate().public_key().to_hex(),
+    );
+    let err =
+        RemoteSigner::connect_with_timeout(&uri, Keys::generate(), Duration::from_millis(500))
+            .err()
+            .unwrap();
+    assert!(matches!(
+        err,
+        Error::Relay(nostr_binding_relay::Error::Timeout)
+    ));
+
+    let uri = format!("bunker://{}", bunker.public_key().to_hex());
+    let err = RemoteSigner::connect(&uri, Keys::generate()).err().unwrap();
+    assert!(matches!(err, Error::InvalidUri(_)));
+}
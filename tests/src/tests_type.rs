@@ -1,9 +1,16 @@
 extern crate hex;
 use crate::{
-    assert_script_error, get_witness, new_type_mint_template, update_witness, TestSchema,
-    MAX_CYCLES,
+    add_header_dep, assert_script_error, author_nostr_lock, blake160, get_witness, lock_args_v1,
+    new_type_mint_template, new_type_mint_template_with_config, new_type_sale_template,
+    new_type_transfer_template, royalty_data, set_inputs_since, set_output_lock, update_witness,
+    BindingConfig, TestSchema, BINDING_FLAG_ARCHIVE, BINDING_FLAG_EXPIRATION, BINDING_FLAG_ROYALTY,
+    EXPIRATION_TAG_NAME, LOCK_FLAG_ANYONE_CAN_PAY, LOCK_FLAG_RECOVERY, LOCK_FLAG_SUMMARY_CONTENT,
+    MAX_CYCLES, MINT_TO_AUTHOR_TAG_NAME, MINT_TO_TAG_NAME, ROYALTY_TAG_NAME,
 };
+use ckb_nostr_error::BindingError;
 use ckb_testtool::ckb_types::{bytes::Bytes, packed, prelude::*};
+use nostr::hashes::{sha256, Hash};
+use nostr::{Keys, Tag, TagKind};
 
 #[test]
 fn test_mint() {
@@ -18,21 +25,21 @@ fn test_mint() {
 fn test_mint_failed_wrong_id() {
     let (context, tx, _script) = new_type_mint_template(TestSchema::WrongId);
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 54);
+    assert_script_error(result.err().unwrap(), BindingError::InvalidEventId);
 }
 
 #[test]
 fn test_mint_failed_wrong_global_unique_id() {
     let (context, tx, _script) = new_type_mint_template(TestSchema::WrongGlobalUniqueId);
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 60);
+    assert_script_error(result.err().unwrap(), BindingError::TypeIdNotMatch);
 }
 
 #[test]
 fn test_mint_failed_wrong_global_unique_id2() {
     let (context, tx, _script) = new_type_mint_template(TestSchema::WrongGlobalUniqueId2);
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 58);
+    assert_script_error(result.err().unwrap(), BindingError::GlobalUniqueIdNotFound);
 }
 
 #[test]
@@ -40,7 +47,7 @@ fn test_mint_failed_args_len() {
     let (context, tx, _script) = new_type_mint_template(TestSchema::WrongArgsLen);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 52); // WrongArgsLength
+    assert_script_error(result.err().unwrap(), BindingError::WrongArgsLength);
 }
 
 #[test]
@@ -48,7 +55,7 @@ fn test_mint_failed_too_many_type_id_cell() {
     let (context, tx, _script) = new_type_mint_template(TestSchema::WrongMultiTypeCell);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 59); // WrongArgsLength
+    assert_script_error(result.err().unwrap(), BindingError::TooManyTypeIdCell);
 }
 
 #[test]
@@ -66,7 +73,7 @@ fn test_mint_failed_witness_utf8() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 57); // Json
+    assert_script_error(result.err().unwrap(), BindingError::Json);
 }
 
 #[test]
@@ -81,7 +88,7 @@ fn test_mint_failed_witness_json() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 57); // Json
+    assert_script_error(result.err().unwrap(), BindingError::Json);
 }
 
 #[test]
@@ -96,7 +103,7 @@ fn test_mint_failed_empty_witness() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 57); // Json
+    assert_script_error(result.err().unwrap(), BindingError::Json);
 }
 
 #[test]
@@ -106,7 +113,7 @@ fn test_mint_failed_empty_witnesses() {
     let tx = tx.as_advanced_builder().set_witnesses(Vec::new()).build();
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 1);
+    assert_script_error(result.err().unwrap(), BindingError::IndexOutOfBound);
 }
 
 #[test]
@@ -138,7 +145,7 @@ fn test_mint_failed_verify_id() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 54); // InvalidEventId
+    assert_script_error(result.err().unwrap(), BindingError::InvalidEventId);
 }
 
 #[test]
@@ -164,7 +171,7 @@ fn test_mint_failed_no_tags() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 57);
+    assert_script_error(result.err().unwrap(), BindingError::Json);
 }
 
 #[test]
@@ -196,7 +203,7 @@ fn test_mint_failed_sig() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 50); // ValidationFail
+    assert_script_error(result.err().unwrap(), BindingError::ValidationFail);
 }
 
 #[test]
@@ -228,7 +235,7 @@ fn test_mint_failed_sig_fmt() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 55); // InvalidSignatureFormat
+    assert_script_error(result.err().unwrap(), BindingError::InvalidSignatureFormat);
 }
 
 #[test]
@@ -260,5 +267,436 @@ fn test_mint_failed_sig_fmt2() {
     let tx = update_witness(tx, 0, witness);
 
     let result = context.verify_tx(&tx, MAX_CYCLES);
-    assert_script_error(result.err().unwrap(), 55); // InvalidSignatureFormat
+    assert_script_error(result.err().unwrap(), BindingError::InvalidSignatureFormat);
+}
+
+#[test]
+fn test_mint_data_content() {
+    let config = BindingConfig {
+        flags: Some(0x01),
+        data: Bytes::from("hello,world"),
+        ..Default::default()
+    };
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_mint_data_content_hash() {
+    let hash = sha256::Hash::hash("hello,world".as_bytes());
+    let config = BindingConfig {
+        flags: Some(0x02),
+        data: Bytes::copy_from_slice(hash.as_byte_array()),
+        ..Default::default()
+    };
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_mint_data_file_hash() {
+    let file_hash = [0x5au8; 32];
+    let config = BindingConfig {
+        kind: 1063,
+        content: "a picture".into(),
+        tags: vec![Tag::custom(
+            TagKind::from("x"),
+            vec![hex::encode(file_hash)],
+        )],
+        flags: Some(0x04),
+        data: Bytes::copy_from_slice(&file_hash),
+    };
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_mint_failed_data_content() {
+    let config = BindingConfig {
+        flags: Some(0x01),
+        data: Bytes::from("hello,world!"),
+        ..Default::default()
+    };
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(
+        result.err().unwrap(),
+        BindingError::DataCommitmentMismatched,
+    );
+}
+
+#[test]
+fn test_mint_failed_file_hash_not_found() {
+    let config = BindingConfig {
+        kind: 1063,
+        flags: Some(0x04),
+        data: Bytes::copy_from_slice(&[0x5au8; 32]),
+        ..Default::default()
+    };
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::FileHashNotFound);
+}
+
+#[test]
+fn test_mint_failed_invalid_flags() {
+    let config = BindingConfig {
+        flags: Some(0x03),
+        data: Bytes::from("hello,world"),
+        ..Default::default()
+    };
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::InvalidFlags);
+}
+
+#[test]
+fn test_transfer_data_commitment() {
+    let config = BindingConfig {
+        flags: Some(0x01),
+        data: Bytes::from("hello,world"),
+        ..Default::default()
+    };
+    let (context, tx) = new_type_transfer_template(config, Bytes::from("hello,world"));
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_transfer_failed_data_changed() {
+    let config = BindingConfig {
+        flags: Some(0x01),
+        data: Bytes::from("hello,world"),
+        ..Default::default()
+    };
+    let (context, tx) = new_type_transfer_template(config, Bytes::from("hello,nostr"));
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::DataChanged);
+}
+
+#[test]
+fn test_mint_archive() {
+    let config = BindingConfig {
+        flags: Some(BINDING_FLAG_ARCHIVE),
+        ..Default::default()
+    };
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    // the archived event is verified without witness
+    let tx = tx.as_advanced_builder().set_witnesses(Vec::new()).build();
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_mint_failed_archive_non_canonical() {
+    let config = BindingConfig {
+        flags: Some(BINDING_FLAG_ARCHIVE),
+        ..Default::default()
+    };
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let data = tx.outputs_data().get(0).unwrap().raw_data();
+    let event: serde_json::Value = serde_json::from_slice(&data).unwrap();
+    let data = serde_json::to_vec_pretty(&event).unwrap();
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs_data(vec![Bytes::from(data).pack()])
+        .build();
+
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::NonCanonicalEvent);
+}
+
+#[test]
+fn test_mint_failed_archive_with_commitment() {
+    let config = BindingConfig {
+        flags: Some(BINDING_FLAG_ARCHIVE | 0x01),
+        ..Default::default()
+    };
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::InvalidFlags);
+}
+
+#[test]
+fn test_transfer_failed_archive_changed() {
+    let config = BindingConfig {
+        flags: Some(BINDING_FLAG_ARCHIVE),
+        data: Bytes::from("{}"),
+        ..Default::default()
+    };
+    let (context, tx) = new_type_transfer_template(config, Bytes::from("{ }"));
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::DataChanged);
+}
+
+const EXPIRATION: u64 = 1_900_000_000;
+
+fn expiration_config() -> BindingConfig {
+    BindingConfig {
+        tags: vec![Tag::custom(
+            TagKind::from(EXPIRATION_TAG_NAME),
+            vec![EXPIRATION.to_string()],
+        )],
+        flags: Some(BINDING_FLAG_EXPIRATION),
+        data: Bytes::copy_from_slice(&EXPIRATION.to_le_bytes()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_mint_expiration() {
+    let (context, tx, _script) =
+        new_type_mint_template_with_config(TestSchema::Normal, expiration_config());
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_mint_expiration_archive() {
+    let mut config = expiration_config();
+    config.flags = Some(BINDING_FLAG_EXPIRATION | BINDING_FLAG_ARCHIVE);
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_mint_failed_expiration_not_found() {
+    let mut config = expiration_config();
+    config.tags.clear();
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::ExpirationNotFound);
+}
+
+#[test]
+fn test_mint_failed_expiration_mismatched() {
+    let mut config = expiration_config();
+    config.data = Bytes::copy_from_slice(&(EXPIRATION + 1).to_le_bytes());
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::ExpirationMismatched);
+}
+
+#[test]
+fn test_transfer_before_expiration() {
+    let config = expiration_config();
+    let data = config.data.clone();
+    let (mut context, tx) = new_type_transfer_template(config, data);
+    let tx = add_header_dep(&mut context, tx, EXPIRATION - 1);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_transfer_failed_expired_header_dep() {
+    let config = expiration_config();
+    let data = config.data.clone();
+    let (mut context, tx) = new_type_transfer_template(config, data);
+    let tx = add_header_dep(&mut context, tx, EXPIRATION);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::BindingExpired);
+}
+
+#[test]
+fn test_transfer_failed_expired_since() {
+    let config = expiration_config();
+    let data = config.data.clone();
+    let (context, tx) = new_type_transfer_template(config, data);
+    // absolute since with timestamp metric
+    let tx = set_inputs_since(tx, 0x4000_0000_0000_0000 | EXPIRATION);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::BindingExpired);
+}
+
+#[test]
+fn test_transfer_failed_expiration_changed() {
+    let config = expiration_config();
+    let (context, tx) = new_type_transfer_template(
+        config,
+        Bytes::copy_from_slice(&(EXPIRATION + 1).to_le_bytes()),
+    );
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::DataChanged);
+}
+
+#[test]
+fn test_burn_expired() {
+    let config = expiration_config();
+    let data = config.data.clone();
+    let (mut context, tx) = new_type_transfer_template(config, data);
+    let output = tx
+        .output(0)
+        .unwrap()
+        .as_builder()
+        .type_(packed::ScriptOpt::default())
+        .build();
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs(vec![output])
+        .set_outputs_data(vec![Bytes::new().pack()])
+        .build();
+    let tx = add_header_dep(&mut context, tx, EXPIRATION);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+const ROYALTY_RECIPIENT: [u8; 32] = [0x22; 32];
+
+fn royalty_config(tag_rate: u16, data_rate: u16) -> BindingConfig {
+    BindingConfig {
+        tags: vec![Tag::custom(
+            TagKind::from(ROYALTY_TAG_NAME),
+            vec![hex::encode(ROYALTY_RECIPIENT), tag_rate.to_string()],
+        )],
+        flags: Some(BINDING_FLAG_ROYALTY),
+        data: royalty_data(&ROYALTY_RECIPIENT, data_rate),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_mint_royalty() {
+    let (context, tx, _script) =
+        new_type_mint_template_with_config(TestSchema::Normal, royalty_config(500, 500));
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_mint_royalty_mismatched() {
+    let (context, tx, _script) =
+        new_type_mint_template_with_config(TestSchema::Normal, royalty_config(500, 100));
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::RoyaltyMismatched);
+}
+
+#[test]
+fn test_mint_royalty_invalid_rate() {
+    let (context, tx, _script) =
+        new_type_mint_template_with_config(TestSchema::Normal, royalty_config(10001, 10001));
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::InvalidRoyaltyRate);
+}
+
+#[test]
+fn test_sale_royalty_paid() {
+    let (context, tx) = new_type_sale_template(500, 20000, 1000);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_sale_royalty_not_paid() {
+    let (context, tx) = new_type_sale_template(500, 20000, 999);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::RoyaltyNotPaid);
+}
+
+#[test]
+fn test_transfer_royalty_without_sale() {
+    let (context, tx) = new_type_sale_template(500, 0, 0);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+fn mint_to_config(name: &str, value: [u8; 32]) -> BindingConfig {
+    BindingConfig {
+        tags: vec![Tag::custom(TagKind::from(name), vec![hex::encode(value)])],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_mint_to_lock_hash() {
+    let lock = packed::Script::new_builder()
+        .args(Bytes::from("owner").pack())
+        .build();
+    let config = mint_to_config(MINT_TO_TAG_NAME, lock.calc_script_hash().unpack());
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let tx = set_output_lock(tx, lock);
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_mint_to_lock_hash_mismatched() {
+    let config = mint_to_config(MINT_TO_TAG_NAME, [0x33; 32]);
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::MintLockMismatched);
+}
+
+#[test]
+fn test_mint_to_author() {
+    let config = mint_to_config(MINT_TO_AUTHOR_TAG_NAME, [0x33; 32]);
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let tx = set_output_lock(tx, author_nostr_lock([0x33; 32]));
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+// the author's nostr lock with version 1 args of `flags` and `fields`
+fn author_nostr_lock_v1(flags: u8, fields: &[u8]) -> packed::Script {
+    let lock = author_nostr_lock([0x33; 32]);
+    let mut args = lock.args().raw_data().to_vec();
+    args.extend(lock_args_v1(flags, fields));
+    lock.as_builder().args(Bytes::from(args).pack()).build()
+}
+
+#[test]
+fn test_mint_to_author_versioned_args() {
+    let config = mint_to_config(MINT_TO_AUTHOR_TAG_NAME, [0x33; 32]);
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let tx = set_output_lock(tx, author_nostr_lock_v1(LOCK_FLAG_SUMMARY_CONTENT, &[]));
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_mint_to_author_foreign_recovery_key() {
+    // the minter could take the cell with its recovery key after the delay
+    let config = mint_to_config(MINT_TO_AUTHOR_TAG_NAME, [0x33; 32]);
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let mut fields = blake160(&Keys::generate().public_key().to_bytes()).to_vec();
+    fields.extend((0x8000_0000_0000_0000u64 | 100).to_le_bytes());
+    let tx = set_output_lock(tx, author_nostr_lock_v1(LOCK_FLAG_RECOVERY, &fields));
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::MintLockMismatched);
+
+    // nor is an anyone-can-pay lock the author's
+    let (context, tx, _script) = new_type_mint_template_with_config(
+        TestSchema::Normal,
+        mint_to_config(MINT_TO_AUTHOR_TAG_NAME, [0x33; 32]),
+    );
+    let tx = set_output_lock(tx, author_nostr_lock_v1(LOCK_FLAG_ANYONE_CAN_PAY, &[]));
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::MintLockMismatched);
+}
+
+#[test]
+fn test_mint_to_author_wrong_code_hash() {
+    let config = mint_to_config(MINT_TO_AUTHOR_TAG_NAME, [0x33; 32]);
+    let (context, tx, _script) = new_type_mint_template_with_config(TestSchema::Normal, config);
+    let tx = set_output_lock(tx, author_nostr_lock([0x44; 32]));
+    let result = context.verify_tx(&tx, MAX_CYCLES);
+    assert_script_error(result.err().unwrap(), BindingError::MintLockMismatched);
 }